@@ -1,24 +1,29 @@
-extern crate cgmath;
 extern crate ggez;
-extern crate rand;
+extern crate serde_json;
 extern crate skunkworks;
 
 use ggez::{conf,
-           event::{self, MouseState},
-           graphics::{self,
-                      // Font,
-                      Image,
-                      Point2},
+           event::{self, Keycode, Mod, MouseButton, MouseState},
+           graphics::{self, Font, Image, Point2},
            Context,
            GameResult};
 
-use cgmath::{prelude::*, Rad, Vector2};
+use skunkworks::boids::{Boundary, FlockParams, Vehicle};
+use skunkworks::capture::{CaptureOptions, FrameCapture};
+use skunkworks::ecosystem::{Ecosystem, Species};
+use skunkworks::game_timer::{GameTimer, TimerState};
+use skunkworks::obstacle::Obstacle;
+use skunkworks::replay::{InputLog, Recording, Replayable, Replaying, StateHash, StateHasher};
+use skunkworks::rewind::{History, Snapshot};
+use skunkworks::rng::SimRng;
+use skunkworks::{draw_flock, draw_obstacles, draw_waypoint, GgezRenderer, Waypoint};
 
-use rand::{thread_rng, Rng, ThreadRng};
+use serde_json::Value;
 
 use std::cell::RefCell;
-
-use skunkworks::{affine_transform, bearing_to_target, game_timer::GameTimer, limit_vector2};
+use std::cmp::Ordering;
+use std::fs::File;
+use std::path::PathBuf;
 
 const HD: (u32, u32) = (1280, 720);
 const FULL_HD: (u32, u32) = (1920, 1080);
@@ -28,72 +33,287 @@ const WXGA: (u32, u32) = (1280, 800);
 const WUXGA: (u32, u32) = (1920, 1200);
 
 const BOID_COUNT: u32 = 200;
+const PIKE_COUNT: u32 = 3;
+
+// How far from the pointer koi notice it when attracting, how far a click
+// reaches to remove one, and how big a placed rock is.
+const ATTRACT_RANGE: f64 = 300.0;
+const REMOVE_RANGE: f64 = 50.0;
+const ROCK_RADIUS: f64 = 30.0;
 const RESOLUTION: (u32, u32) = WUXGA;
-const MAX_FORCE: f64 = 0.03;
-const MAX_SPEED: f64 = 2.0;
-const CIRCLE_RADIUS: f64 = 100.0; // Radius of the wandering circle
-const RADIAN_DELTA: f64 = 0.017_453_3f64 * 15.0; // Maximum degree of variance when wandering
-const WANDER_WEIGHT: f64 = 1.0;
-const SEPARATION_RANGE: f64 = 80.0;
-const SEPARATION_WEIGHT: f64 = 1.5;
-const ALIGN_RANGE: f64 = 160.0;
-const ALIGN_WEIGHT: f64 = 1.0;
-const COHESION_RANGE: f64 = 160.0;
-const COHESION_WEIGHT: f64 = 1.0;
-const CONSTRAIN_DISTANCE: f32 = 20.0;
 
 const BG_SCALE: f32 = RESOLUTION.0 as f32 / 1280 as f32;
 
-const ZERO_VECTOR: Vector2<f64> = Vector2 { x: 0f64, y: 0f64 };
+// Ten seconds of history at 60 ticks per second.
+const HISTORY_LENGTH: usize = 600;
+// The flock moves once per update, which runs at 60 per second.
+const STEP_TIME: f64 = 1.0 / 60.0;
+
+// What the left mouse button does to the koi, picked with the number keys.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MouseMode {
+    // Koi near the pointer swim towards it while the button is held.
+    Attract,
+    // Koi near the pointer scatter from it while the button is held.
+    Repel,
+    // A click marks a spot the whole shoal heads for and settles on.
+    Arrive,
+    PlaceObstacle,
+    // A click adds a koi heading off in a random direction.
+    Spawn,
+    // A click takes out the koi nearest the pointer.
+    Remove,
+}
+
+impl MouseMode {
+    fn from_keycode(keycode: Keycode) -> Option<MouseMode> {
+        match keycode {
+            Keycode::Num1 => Some(MouseMode::Attract),
+            Keycode::Num2 => Some(MouseMode::Repel),
+            Keycode::Num3 => Some(MouseMode::Arrive),
+            Keycode::Num4 => Some(MouseMode::PlaceObstacle),
+            Keycode::Num5 => Some(MouseMode::Spawn),
+            Keycode::Num6 => Some(MouseMode::Remove),
+            _ => None,
+        }
+    }
+}
 
 pub struct MainState {
     // circle_sprite: Image,
     bg_image: Image,
-    boid_image: Image,
+    // One per species.
+    sprites: Vec<Image>,
     game_timer: GameTimer,
-    // font: Font,
-    rng_seed: ThreadRng,
-    vehicles: Vec<RefCell<Vehicle>>,
-    mouse_position: cgmath::Point2<f64>,
-    default_offset: Point2,
+    font: Font,
+    seed: u64,
+    boundary: Boundary,
+    rng: SimRng,
+    ecosystem: Ecosystem,
+    mouse_position: skunkworks::Point2<f64>,
+    mouse_mode: MouseMode,
+    mouse_down: bool,
+    // Where the koi are arriving at, in `MouseMode::Arrive`.
+    target: Option<skunkworks::Point2<f64>>,
+    history: History<Frame>,
+    paused: bool,
+    capture: Option<FrameCapture>,
+    captured_tick: usize,
+}
+
+#[derive(Clone)]
+pub struct Frame {
+    vehicles: Vec<Vec<Vehicle>>,
+    obstacles: Vec<Obstacle>,
+    target: Option<skunkworks::Point2<f64>>,
+    rng: SimRng,
+    timer: TimerState,
 }
 
 impl MainState {
-    fn new(ctx: &mut Context) -> GameResult<MainState> {
-        // let font = Font::new(ctx, "/font.ttf", 12)?;
+    fn new(ctx: &mut Context, seed: u64, boundary: Boundary) -> GameResult<MainState> {
+        let font = Font::new(ctx, "/font.ttf", 12)?;
         let bg_image = Image::new(ctx, "/water.png")?;
-        let boid_image = Image::new(ctx, "/koi.png")?;
-        let mut rng_seed = thread_rng();
-        let mut vehicles = Vec::new();
-        for _i in 0..BOID_COUNT {
-            vehicles.push(RefCell::new(Vehicle::new(
-                Vector2::new(
-                    rng_seed.gen_range(-MAX_SPEED, MAX_SPEED),
-                    rng_seed.gen_range(-MAX_SPEED, MAX_SPEED),
-                ),
-                cgmath::Point2::new(
-                    rng_seed.gen_range(0f64, f64::from(RESOLUTION.0)),
-                    rng_seed.gen_range(0f64, f64::from(RESOLUTION.1)),
-                ),
-            )));
+        let mut rng = SimRng::new(seed);
+        let params = FlockParams {
+            width: f64::from(RESOLUTION.0),
+            height: f64::from(RESOLUTION.1),
+            boundary,
+            ..Default::default()
+        };
+        let (koi, pike) = pond_life(params);
+        let mut ecosystem = Ecosystem::new();
+        ecosystem.add_species(koi, BOID_COUNT, &mut rng);
+        ecosystem.add_species(pike, PIKE_COUNT, &mut rng);
+        let mut sprites = Vec::new();
+        for (species, flock) in ecosystem.species.iter().zip(&mut ecosystem.flocks) {
+            sprites.push(Image::new(ctx, &species.sprite)?);
+            flock.obstacles = rocks(f64::from(RESOLUTION.0), f64::from(RESOLUTION.1));
+            println!("Added {} {}.", flock.vehicles.len(), species.name);
         }
 
-        println!("Added {} vehicles.", vehicles.len());
-
-        let s = MainState {
-            // font,
+        let mut s = MainState {
+            font,
             bg_image,
-            boid_image,
-            vehicles,
-            rng_seed,
-            mouse_position: cgmath::Point2::new(0.0, 0.0),
+            sprites,
+            ecosystem,
+            seed,
+            boundary,
+            rng,
+            mouse_position: skunkworks::Point2::origin(),
+            mouse_mode: MouseMode::Attract,
+            mouse_down: false,
+            target: None,
             game_timer: GameTimer::new(),
-            default_offset: Point2::new(0.5, 0.5),
+            history: History::new(HISTORY_LENGTH),
+            paused: false,
+            capture: None,
+            captured_tick: 0,
         };
+        let frame = s.snapshot();
+        s.history.push(frame);
         println!("BG_SCALE: {}", BG_SCALE);
+        println!("Space pauses, Left and Right step through the last {} ticks.", HISTORY_LENGTH);
+        println!(
+            "The mouse attracts (1), repels (2), sets a spot to arrive at (3), places rocks (4), \
+             spawns koi (5) or removes them (6)."
+        );
 
         Ok(s)
     }
+
+    fn advance(&mut self) {
+        self.game_timer.tick();
+        self.steer_by_mouse();
+        for catch in self.ecosystem.update(&mut self.rng) {
+            println!(
+                "Tick {}: a {} caught a {}.",
+                self.game_timer.get_ticks(),
+                self.ecosystem.species[catch.predator].name,
+                self.ecosystem.species[catch.prey].name
+            );
+        }
+        let frame = self.snapshot();
+        self.history.push(frame);
+    }
+
+    // Adds the pull or push of the pointer to the koi before they move, the
+    // same way the pike's is added.
+    fn steer_by_mouse(&mut self) {
+        let pointer = self.mouse_position;
+        for vehicle in &self.ecosystem.flocks[0].vehicles {
+            let mut vehicle = vehicle.borrow_mut();
+            match (self.mouse_mode, self.target) {
+                (MouseMode::Attract, _)
+                    if self.mouse_down && vehicle.location.distance(pointer) < ATTRACT_RANGE =>
+                {
+                    vehicle.seek(pointer)
+                }
+                (MouseMode::Repel, _) if self.mouse_down => vehicle.flee(pointer),
+                (MouseMode::Arrive, Some(target)) => vehicle.arrive(target),
+                _ => {}
+            }
+        }
+    }
+
+    fn click(&mut self, position: skunkworks::Point2<f64>) {
+        match self.mouse_mode {
+            MouseMode::Attract | MouseMode::Repel => {}
+            MouseMode::Arrive => self.target = Some(position),
+            MouseMode::PlaceObstacle => {
+                for flock in &mut self.ecosystem.flocks {
+                    flock.obstacles.push(Obstacle::Circle {
+                        center: position,
+                        radius: ROCK_RADIUS,
+                    });
+                }
+            }
+            MouseMode::Spawn => {
+                let koi = &mut self.ecosystem.flocks[0];
+                let mut vehicle = Vehicle::new_random(&koi.params, &mut self.rng);
+                vehicle.location = position;
+                koi.vehicles.push(RefCell::new(vehicle));
+            }
+            MouseMode::Remove => {
+                let koi = &mut self.ecosystem.flocks[0];
+                let nearest = koi
+                    .vehicles
+                    .iter()
+                    .map(|vehicle| vehicle.borrow().location.distance(position))
+                    .enumerate()
+                    .filter(|&(_, distance)| distance < REMOVE_RANGE)
+                    .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal));
+                if let Some((index, _)) = nearest {
+                    koi.vehicles.remove(index);
+                }
+            }
+        }
+    }
+
+    fn step_back(&mut self) {
+        self.paused = true;
+        if let Some(frame) = self.history.step_back().cloned() {
+            self.restore(&frame);
+        }
+    }
+
+    fn step_forward(&mut self) {
+        self.paused = true;
+        match self.history.step_forward().cloned() {
+            Some(frame) => self.restore(&frame),
+            None => self.advance(),
+        }
+    }
+
+    // Resuming from a point in the past drops the history after it.
+    fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        if !self.paused {
+            if let Some(frame) = self.history.current() {
+                self.game_timer.restore(&frame.timer);
+            }
+        }
+    }
+
+    // Captures each tick once, so pausing or stepping back through the
+    // history doesn't repeat frames in the output.
+    fn capture_frame(&mut self, ctx: &mut Context) -> GameResult<()> {
+        let tick = self.game_timer.get_ticks();
+        let capture = match self.capture {
+            Some(ref mut capture) if tick > self.captured_tick => capture,
+            _ => return Ok(()),
+        };
+        self.captured_tick = tick;
+        if capture.wants_frame() {
+            let screenshot = graphics::screenshot(ctx)?;
+            let pixels = screenshot.to_rgba8(ctx)?;
+            capture.add_frame(screenshot.width(), screenshot.height(), &pixels)?;
+        } else {
+            capture.skip_frame();
+        }
+        Ok(())
+    }
+}
+
+impl Snapshot for MainState {
+    type State = Frame;
+
+    fn snapshot(&self) -> Frame {
+        Frame {
+            vehicles: self.ecosystem.snapshot(),
+            obstacles: self.ecosystem.flocks[0].obstacles.clone(),
+            target: self.target,
+            rng: self.rng.snapshot(),
+            timer: self.game_timer.snapshot(),
+        }
+    }
+
+    fn restore(&mut self, frame: &Frame) {
+        self.ecosystem.restore(&frame.vehicles);
+        for flock in &mut self.ecosystem.flocks {
+            flock.obstacles = frame.obstacles.clone();
+        }
+        self.target = frame.target;
+        self.rng.restore(&frame.rng);
+        self.game_timer.restore(&frame.timer);
+    }
+}
+
+impl Replayable for MainState {
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn checksum(&self) -> u64 {
+        let mut hasher = StateHasher::new();
+        self.ecosystem.hash_state(&mut hasher);
+        self.rng.hash_state(&mut hasher);
+        hasher.finish()
+    }
+
+    fn config(&self) -> Value {
+        serde_json::to_value(self.boundary).expect("Boundaries always serialize")
+    }
 }
 
 impl event::EventHandler for MainState {
@@ -110,13 +330,39 @@ impl event::EventHandler for MainState {
         self.mouse_position.y = f64::from(y);
     }
 
-    fn update(&mut self, _ctx: &mut Context) -> GameResult<()> {
-        self.game_timer.tick();
+    fn mouse_button_down_event(&mut self, _ctx: &mut Context, button: MouseButton, x: i32, y: i32) {
+        if let MouseButton::Left = button {
+            self.mouse_down = true;
+            self.mouse_position = skunkworks::Point2::new(f64::from(x), f64::from(y));
+            let position = self.mouse_position;
+            self.click(position);
+        }
+    }
+
+    fn mouse_button_up_event(&mut self, _ctx: &mut Context, button: MouseButton, _x: i32, _y: i32) {
+        if let MouseButton::Left = button {
+            self.mouse_down = false;
+        }
+    }
+
+    fn key_down_event(&mut self, ctx: &mut Context, keycode: Keycode, _keymod: Mod, repeat: bool) {
+        match keycode {
+            Keycode::Escape => ctx.quit().expect("Should never fail"),
+            Keycode::Space if !repeat => self.toggle_pause(),
+            Keycode::Left => self.step_back(),
+            Keycode::Right => self.step_forward(),
+            _ => if let Some(mode) = MouseMode::from_keycode(keycode) {
+                // The koi stop heading for the last spot when arriving ends.
+                self.mouse_mode = mode;
+                self.target = None;
+                println!("Mouse mode: {:?}", mode);
+            },
+        }
+    }
 
-        for vehicle in &self.vehicles {
-            vehicle
-                .borrow_mut()
-                .update(&self.vehicles, &mut self.rng_seed, self.mouse_position);
+    fn update(&mut self, _ctx: &mut Context) -> GameResult<()> {
+        if !self.paused {
+            self.advance();
         }
         // _ctx.quit();
         Ok(())
@@ -135,34 +381,167 @@ impl event::EventHandler for MainState {
             },
         )?;
 
-        for vehicle in &self.vehicles {
-            graphics::draw_ex(
-                ctx,
-                &self.boid_image,
-                graphics::DrawParam {
-                    dest: Point2::new(
-                        vehicle.borrow().location.x as f32,
-                        vehicle.borrow().location.y as f32,
-                    ),
-                    rotation: vehicle.borrow().get_bearing(),
-                    offset: self.default_offset,
-                    ..Default::default()
-                },
-            )?;
+        {
+            let renderer = &mut GgezRenderer::new(ctx, &self.font);
+            draw_obstacles(renderer, &self.ecosystem.flocks[0].obstacles, 4.0)?;
+            if let Some(target) = self.target {
+                draw_waypoint(renderer, 6.0, &Waypoint::new(target.x, target.y))?;
+            }
+            for (flock, sprite) in self.ecosystem.flocks.iter().zip(&self.sprites) {
+                draw_flock(renderer, flock, sprite)?;
+            }
         }
+        self.capture_frame(ctx)?;
 
         graphics::present(ctx);
 
-        if (self.game_timer.get_ticks() % 100) == 0 {
+        if !self.paused && (self.game_timer.get_ticks() % 100) == 0 {
             println!("FPS: {}", ggez::timer::get_fps(ctx));
         }
         Ok(())
     }
 }
 
+// Koi school together and scatter from the pike. The pike are faster and
+// turn harder, each hunting alone, so they pick off stragglers rather than
+// the whole shoal. Caught koi are replaced so the pond never empties.
+fn pond_life(params: FlockParams) -> (Species, Species) {
+    let koi = Species {
+        name: "koi".to_string(),
+        sprite: "/koi.png".to_string(),
+        params: params.clone(),
+        ..Default::default()
+    };
+    let pike = Species {
+        name: "pike".to_string(),
+        sprite: "/boid.png".to_string(),
+        params: FlockParams {
+            max_speed: 2.5,
+            max_force: 0.05,
+            separation_range: 300.0,
+            align_weight: 0.0,
+            cohesion_weight: 0.0,
+            ..params
+        },
+        prey: vec!["koi".to_string()],
+        hunt_range: 400.0,
+        catch_distance: 12.0,
+        ..Default::default()
+    };
+    (koi, pike)
+}
+
+// A couple of rocks and a jetty for the koi to swim round, placed relative
+// to the size of the pond.
+fn rocks(width: f64, height: f64) -> Vec<Obstacle> {
+    let at = |x: f64, y: f64| skunkworks::Point2::new(width * x, height * y);
+    vec![
+        Obstacle::Circle {
+            center: at(0.3, 0.35),
+            radius: height * 0.08,
+        },
+        Obstacle::Circle {
+            center: at(0.65, 0.6),
+            radius: height * 0.05,
+        },
+        Obstacle::Polygon {
+            points: vec![at(0.75, 0.2), at(0.85, 0.25), at(0.8, 0.35), at(0.7, 0.3)],
+        },
+        Obstacle::Wall {
+            start: at(0.15, 0.8),
+            end: at(0.45, 0.8),
+        },
+    ]
+}
+
+struct Options {
+    seed: Option<u64>,
+    record: Option<String>,
+    replay: Option<String>,
+    capture: Option<CaptureOptions>,
+    boundary: Boundary,
+}
+
+// `--seed N` repeats an earlier run, `--record FILE` logs the seed and every
+// input and `--replay FILE` plays such a log back. `--capture DIR` writes
+// the frames of every `--every N`th tick to DIR as PNGs, and `--gif FILE`
+// also encodes them as an animated GIF. `--boundary wrap|bounce|steer_away`
+// picks what the boids do at the window edges, replays use the one they were
+// recorded with.
+fn parse_options() -> Options {
+    let mut options = Options {
+        seed: None,
+        record: None,
+        replay: None,
+        capture: None,
+        boundary: Boundary::Wrap,
+    };
+    let mut every = 1;
+    let mut gif = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => {
+                options.seed = args.next().and_then(|value| value.parse().ok());
+                assert!(options.seed.is_some(), "--seed needs a number");
+            }
+            "--record" => options.record = Some(args.next().expect("--record needs a file name")),
+            "--replay" => options.replay = Some(args.next().expect("--replay needs a file name")),
+            "--capture" => {
+                let directory = args.next().expect("--capture needs a directory");
+                options.capture = Some(CaptureOptions {
+                    directory: PathBuf::from(directory),
+                    step_time: STEP_TIME,
+                    ..Default::default()
+                });
+            }
+            "--every" => {
+                every = args
+                    .next()
+                    .and_then(|value| value.parse().ok())
+                    .expect("--every needs a number");
+            }
+            "--boundary" => {
+                options.boundary = match args.next().as_ref().map(String::as_str) {
+                    Some("wrap") => Boundary::Wrap,
+                    Some("bounce") => Boundary::Bounce,
+                    Some("steer_away") => Boundary::SteerAway,
+                    _ => panic!("--boundary needs wrap, bounce or steer_away"),
+                };
+            }
+            "--gif" => gif = Some(PathBuf::from(args.next().expect("--gif needs a file name"))),
+            _ => panic!("Unexpected argument: {}", arg),
+        }
+    }
+    if let Some(ref mut capture) = options.capture {
+        capture.every = every;
+        capture.gif = gif;
+    } else {
+        assert!(gif.is_none(), "--gif needs --capture");
+    }
+    options
+}
+
 pub fn main() {
     use std::{env, path};
 
+    let options = parse_options();
+    let log = options.replay.as_ref().map(|path| {
+        let file = File::open(path).expect("Could not open the input log");
+        InputLog::from_json(file).expect("Could not read the input log")
+    });
+    let (seed, boundary) = match log {
+        Some(ref log) => (
+            log.seed,
+            serde_json::from_value(log.config.clone()).unwrap_or(options.boundary),
+        ),
+        None => (
+            options.seed.unwrap_or_else(|| SimRng::from_time().seed()),
+            options.boundary,
+        ),
+    };
+    println!("Seed: {}", seed);
+
     let mut c = conf::Conf::new();
     c.window_mode.width = RESOLUTION.0;
     c.window_mode.height = RESOLUTION.1;
@@ -177,234 +556,37 @@ pub fn main() {
     }
 
     println!("{}", graphics::get_renderer_info(ctx).unwrap());
-    let state = &mut MainState::new(ctx).unwrap();
-    if let Err(e) = event::run(ctx, state) {
-        println!("Error encountered: {}", e);
-    } else {
-        println!("Game exited cleanly.");
-    }
-}
-
-#[derive(Debug)]
-struct Vehicle {
-    location: cgmath::Point2<f64>,
-    wander_angle: Rad<f64>,
-    velocity: Vector2<f64>,
-    acceleration: Vector2<f64>,
-    max_force: f64,
-    max_speed: f64,
-}
-
-impl Vehicle {
-    fn new(velocity: Vector2<f64>, location: cgmath::Point2<f64>) -> Vehicle {
-        Vehicle {
-            location,
-            wander_angle: Rad(0.0),
-            velocity,
-            acceleration: Vector2::new(0.0, 0.0),
-            max_force: MAX_FORCE,
-            max_speed: MAX_SPEED,
-        }
-    }
-
-    fn update(
-        &mut self,
-        vehicles: &[RefCell<Vehicle>],
-        rng_seed: &mut ThreadRng,
-        mouse_position: cgmath::Point2<f64>,
-    ) {
-        let mut separate = self.separate(vehicles);
-        let mut align = self.align(vehicles);
-        let mut cohere = self.cohesion(vehicles);
-        let mut wander = self.wander(rng_seed.next_f64());
-
-        separate *= SEPARATION_WEIGHT;
-        align *= ALIGN_WEIGHT;
-        cohere *= COHESION_WEIGHT;
-        wander *= WANDER_WEIGHT;
-
-        self.apply_force(separate);
-        self.apply_force(align);
-        self.apply_force(cohere);
-        self.apply_force(wander);
-
-        self.apply_acceleration();
-
-        self.constrain_location(
-            -CONSTRAIN_DISTANCE,
-            RESOLUTION.0 as f32 + CONSTRAIN_DISTANCE,
-            -CONSTRAIN_DISTANCE,
-            RESOLUTION.1 as f32 + CONSTRAIN_DISTANCE,
+    let mut state = MainState::new(ctx, seed, boundary).unwrap();
+    if let Some(capture) = options.capture {
+        println!(
+            "Capturing every {} ticks to {}.",
+            capture.every,
+            capture.directory.display()
         );
+        state.capture = Some(FrameCapture::new(capture).expect("Could not start the capture"));
     }
-
-    fn apply_force(&mut self, force: Vector2<f64>) {
-        let force = limit_vector2(self.max_force, force);
-        self.acceleration += force;
-    }
-
-    fn apply_acceleration(&mut self) {
-        self.velocity += self.acceleration;
-        self.velocity = limit_vector2(self.max_speed, self.velocity);
-        self.location += self.velocity;
-        self.acceleration *= 0.0;
-    }
-
-    fn get_bearing(&self) -> f32 {
-        bearing_to_target(self.location, self.location + self.velocity)
-    }
-
-    fn seek(&mut self, target: cgmath::Point2<f64>) {
-        let mut desired = target - self.location;
-        let distance = desired.magnitude();
-
-        if distance > 1.0 {
-            desired = desired.normalize();
-            desired *= self.max_speed;
-            let steer = desired - self.velocity;
-            self.apply_force(steer)
-        }
-    }
-
-    fn constrain_location(&mut self, x_min: f32, x_max: f32, y_min: f32, y_max: f32) {
-        if (self.location.x as f32) < x_min {
-            self.location.x = f64::from(x_max);
-        } else if (self.location.x as f32) > x_max {
-            self.location.x = f64::from(x_min);
-        }
-
-        if (self.location.y as f32) < y_min {
-            self.location.y = f64::from(y_max);
-        } else if (self.location.y as f32) > y_max {
-            self.location.y = f64::from(y_min);
-        }
-    }
-
-    fn flee(&mut self, target: cgmath::Point2<f64>) {
-        let safety_range = 200.0;
-        let mut desired = self.location - target;
-
-        if desired.magnitude() < safety_range {
-            desired = desired.normalize();
-            desired *= self.max_speed;
-            let steer = desired - self.velocity;
-            self.apply_force(steer)
-        }
-    }
-
-    fn arrive(&mut self, target: cgmath::Point2<f64>) {
-        let mut desired = target - self.location;
-        let distance = desired.magnitude();
-        desired = desired.normalize();
-
-        if distance < 100.0 {
-            let m = affine_transform(distance, 0.0, 100.0, 0.0, self.max_speed);
-            desired *= m;
-        } else {
-            desired *= self.max_speed;
-        }
-
-        let steer = desired - self.velocity;
-        self.apply_force(steer);
-    }
-
-    fn wander(&mut self, rng_f64: f64) -> Vector2<f64> {
-        let center = match self.velocity {
-            Vector2 { x, y } if x == 0.0 && y == 0.0 => self.location,
-            Vector2 { x, y } if x.is_nan() || y.is_nan() => self.location,
-            _ => self.location + (self.velocity * CIRCLE_RADIUS),
-        };
-
-        self.wander_angle += Rad(rng_f64 * RADIAN_DELTA) - Rad(RADIAN_DELTA * 0.5);
-
-        let x = CIRCLE_RADIUS * Angle::cos(self.wander_angle);
-        let y = CIRCLE_RADIUS * Angle::sin(self.wander_angle);
-        let offset = Vector2::new(x, y);
-
-        let mut desired = center + offset - self.location;
-        let distance = desired.magnitude();
-
-        if distance > 1.0 {
-            desired = desired.normalize();
-            desired *= self.max_speed;
-            desired - self.velocity
-        } else {
-            ZERO_VECTOR
-        }
-    }
-
-    fn separate(&mut self, vehicles: &[RefCell<Vehicle>]) -> Vector2<f64> {
-        let mut sum = Vector2::new(0f64, 0f64);
-        let mut count = 0;
-
-        for vehicle in vehicles {
-            if vehicle.as_ptr() != self as *mut Vehicle {
-                let neighbour = vehicle.borrow();
-                let d = self.location.distance(neighbour.location);
-                if d < SEPARATION_RANGE {
-                    let mut diff = self.location - neighbour.location;
-                    diff = diff.normalize();
-                    diff /= d;
-                    sum += diff;
-                    count += 1;
-                }
+    let result = if let Some(log) = log {
+        Replaying::new(state, log).and_then(|mut replaying| {
+            let result = event::run(ctx, &mut replaying);
+            if replaying.is_finished() {
+                println!("Replay finished after {} ticks.", replaying.tick());
             }
-        }
-
-        if count > 0 {
-            sum /= f64::from(count);
-            sum = sum.normalize();
-            sum *= MAX_SPEED;
-            (sum - self.velocity)
-        } else {
-            ZERO_VECTOR
-        }
-    }
-
-    fn align(&mut self, vehicles: &[RefCell<Vehicle>]) -> cgmath::Vector2<f64> {
-        let mut sum = Vector2::new(0f64, 0f64);
-        let mut count = 0;
-
-        for vehicle in vehicles {
-            if vehicle.as_ptr() != self as *mut Vehicle {
-                let neighbour = vehicle.borrow();
-                let d = self.location.distance(neighbour.location);
-                if d < ALIGN_RANGE {
-                    sum += neighbour.velocity;
-                    count += 1;
-                }
-            }
-        }
-
-        if count > 0 {
-            sum /= f64::from(count);
-            sum = sum.normalize();
-            sum *= MAX_SPEED;
-            (sum - self.velocity)
-        } else {
-            ZERO_VECTOR
-        }
-    }
-
-    fn cohesion(&mut self, vehicles: &[RefCell<Vehicle>]) -> cgmath::Vector2<f64> {
-        let mut sum = ZERO_VECTOR.clone();
-        let mut count = 0f64;
-
-        for vehicle in vehicles {
-            if vehicle.as_ptr() != self as *mut Vehicle {
-                let neighbour = vehicle.borrow();
-                let d = self.location.distance(neighbour.location);
-                if d < COHESION_RANGE {
-                    sum += neighbour.location.to_vec();
-                    count += 1.0;
-                }
-            }
-        }
+            result
+        })
+    } else if let Some(ref path) = options.record {
+        let recording = &mut Recording::new(state);
+        let result = event::run(ctx, recording);
+        let file = File::create(path).expect("Could not create the input log");
+        recording.log().to_json(file).expect("Could not write the input log");
+        println!("Recorded {} ticks to {}.", recording.log().ticks.len(), path);
+        result
+    } else {
+        event::run(ctx, &mut state)
+    };
 
-        if count > 0.0 {
-            (sum / count)
-        } else {
-            ZERO_VECTOR
-        }
+    if let Err(e) = result {
+        println!("Error encountered: {}", e);
+    } else {
+        println!("Game exited cleanly.");
     }
 }
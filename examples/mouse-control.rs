@@ -1,48 +1,112 @@
 extern crate ggez;
-extern crate nalgebra;
 extern crate skunkworks;
 
-use ggez::event::MouseButton;
+use ggez::event::{Keycode, Mod, MouseButton};
 use ggez::{conf, event, Context, GameResult};
 
 use ggez::graphics;
-use ggez::graphics::{DrawMode, Font, Mesh, Point2};
-
-use nalgebra::Vector2;
+use ggez::graphics::Font;
 
 use std::{env, path};
 
-use skunkworks::game_timer::GameTimer;
+use skunkworks::game_timer::{GameTimer, TimerState};
+use skunkworks::rewind::{History, Snapshot};
+use skunkworks::waypoint::{optimize_route, RouteOptions};
 use skunkworks::{actor_at_waypoint, draw_player, draw_waypoint_labels, draw_waypoints,
-                 move_towards_next_waypoint, Actor, Waypoint};
+                 move_towards_next_waypoint, Actor, GgezRenderer, Point2, Waypoint};
+
+const CIRCLE_RADIUS: f32 = 14.0;
+
+// Ten seconds of history at 60 frames per second. Stepping forward past the
+// end of the history advances by one 60th of a second.
+const HISTORY_LENGTH: usize = 600;
+const STEP_TIME: f64 = 1.0 / 60.0;
 
 pub struct MainState {
     player: Actor,
-    circle_mesh: Mesh,
     game_timer: GameTimer,
     font: Font,
+    history: History<Frame>,
+    paused: bool,
+}
+
+#[derive(Clone)]
+pub struct Frame {
+    player: Actor,
+    timer: TimerState,
 }
 
 impl MainState {
     fn new(ctx: &mut Context) -> GameResult<MainState> {
         let font = Font::new(ctx, "/font.ttf", 12)?;
-        let circle_mesh = Mesh::new_circle(ctx, DrawMode::Fill, Point2::new(0.0, 0.0), 14.0, 0.4)?;
 
         let player = Actor {
-            position: Vector2::new(20.0, 20.0),
+            position: Point2::new(20.0, 20.0),
             speed: 200.0,
             waypoints: Vec::with_capacity(5),
         };
 
-        let s = MainState {
+        let mut s = MainState {
             player,
             font,
-            circle_mesh,
             game_timer: GameTimer::new(),
+            history: History::new(HISTORY_LENGTH),
+            paused: false,
         };
+        let frame = s.snapshot();
+        s.history.push(frame);
 
         Ok(s)
     }
+
+    fn advance(&mut self, frame_time: f64) -> GameResult<()> {
+        update_player(&mut self.player, &frame_time)?;
+        let frame = self.snapshot();
+        self.history.push(frame);
+        Ok(())
+    }
+
+    fn step_back(&mut self) {
+        self.paused = true;
+        if let Some(frame) = self.history.step_back().cloned() {
+            self.restore(&frame);
+        }
+    }
+
+    fn step_forward(&mut self) -> GameResult<()> {
+        self.paused = true;
+        match self.history.step_forward().cloned() {
+            Some(frame) => self.restore(&frame),
+            None => self.advance(STEP_TIME)?,
+        }
+        Ok(())
+    }
+
+    // Resuming from a point in the past drops the history after it.
+    fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        if !self.paused {
+            if let Some(frame) = self.history.current() {
+                self.game_timer.restore(&frame.timer);
+            }
+        }
+    }
+}
+
+impl Snapshot for MainState {
+    type State = Frame;
+
+    fn snapshot(&self) -> Frame {
+        Frame {
+            player: self.player.snapshot(),
+            timer: self.game_timer.snapshot(),
+        }
+    }
+
+    fn restore(&mut self, frame: &Frame) {
+        self.player.restore(&frame.player);
+        self.game_timer.restore(&frame.timer);
+    }
 }
 
 impl event::EventHandler for MainState {
@@ -54,21 +118,50 @@ impl event::EventHandler for MainState {
         }
     }
 
+    fn key_down_event(&mut self, ctx: &mut Context, keycode: Keycode, _keymod: Mod, repeat: bool) {
+        match keycode {
+            Keycode::Escape => ctx.quit().expect("Should never fail"),
+            Keycode::O if !repeat => {
+                let options = RouteOptions {
+                    origin: Some(self.player.position),
+                    ..Default::default()
+                };
+                let report = optimize_route(&mut self.player.waypoints, &options);
+                println!(
+                    "Optimized route: {:.1} -> {:.1} ({:.1} shorter)",
+                    report.length_before,
+                    report.length_after,
+                    report.saved()
+                );
+            }
+            Keycode::Space if !repeat => self.toggle_pause(),
+            Keycode::Left => self.step_back(),
+            Keycode::Right => self.step_forward().expect("Should never fail"),
+            _ => {}
+        }
+    }
+
     fn update(&mut self, _ctx: &mut Context) -> GameResult<()> {
-        self.game_timer.tick();
-        update_player(&mut self.player, &self.game_timer.get_frame_time())?;
+        if !self.paused {
+            self.game_timer.tick();
+            let frame_time = self.game_timer.get_frame_time();
+            self.advance(frame_time)?;
+        }
         Ok(())
     }
 
     fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
         graphics::clear(ctx);
-        draw_waypoints(ctx, &self.player.waypoints, &self.circle_mesh)?;
-        draw_waypoint_labels(ctx, &self.font, &self.player.waypoints)?;
-        draw_player(ctx, &self.player, &self.circle_mesh)?;
+        {
+            let renderer = &mut GgezRenderer::new(ctx, &self.font);
+            draw_waypoints(renderer, &self.player.waypoints, CIRCLE_RADIUS)?;
+            draw_waypoint_labels(renderer, &self.player.waypoints)?;
+            draw_player(renderer, &self.player, CIRCLE_RADIUS)?;
+        }
 
         graphics::present(ctx);
 
-        if (self.game_timer.get_ticks() % 100) == 0 {
+        if !self.paused && (self.game_timer.get_ticks() % 100) == 0 {
             println!("FPS: {}", ggez::timer::get_fps(ctx));
         }
         Ok(())
@@ -97,7 +190,7 @@ pub fn main() {
 }
 
 pub fn update_player(player: &mut Actor, frame_time: &f64) -> GameResult<()> {
-    move_towards_next_waypoint(player, frame_time);
+    move_towards_next_waypoint(player, &(*frame_time as f32));
     if actor_at_waypoint(player) {
         player.waypoints.remove(0);
     };
@@ -0,0 +1,196 @@
+extern crate ggez;
+extern crate skunkworks;
+
+use ggez::event::{Keycode, Mod};
+use ggez::{conf, event, Context, GameResult};
+
+use ggez::graphics;
+use ggez::graphics::Font;
+
+use std::{env, path};
+
+use skunkworks::game_timer::{GameTimer, TimerState};
+use skunkworks::rewind::{History, Snapshot};
+use skunkworks::tiled::{load_map, MapRenderer};
+use skunkworks::{actor_at_waypoint, draw_player, draw_waypoints, move_towards_next_waypoint,
+                 Actor, GgezRenderer};
+
+const LEVEL_DIRECTORY: &str = "/";
+const LEVEL_PATH: &str = "/level.tmx";
+const DEFAULT_SPEED: f32 = 100.0;
+const ACTOR_RADIUS: f32 = 14.0;
+const WAYPOINT_RADIUS: f32 = 4.0;
+
+// Ten seconds of history at 60 frames per second. Stepping forward past the
+// end of the history advances by one 60th of a second.
+const HISTORY_LENGTH: usize = 600;
+const STEP_TIME: f64 = 1.0 / 60.0;
+
+pub struct MainState {
+    actors: Vec<Actor>,
+    map_renderer: MapRenderer,
+    font: Font,
+    game_timer: GameTimer,
+    history: History<Frame>,
+    paused: bool,
+}
+
+#[derive(Clone)]
+pub struct Frame {
+    actors: Vec<Actor>,
+    timer: TimerState,
+}
+
+impl MainState {
+    fn new(ctx: &mut Context) -> GameResult<MainState> {
+        let map = load_map(ctx, LEVEL_PATH)?;
+        let map_renderer = MapRenderer::new(ctx, &map, LEVEL_DIRECTORY)?;
+        let font = Font::new(ctx, "/font.ttf", 12)?;
+
+        let actors = map.spawn_actors(DEFAULT_SPEED);
+        println!(
+            "Loaded {}x{} level with {} actors.",
+            map.width,
+            map.height,
+            actors.len()
+        );
+
+        let mut s = MainState {
+            actors,
+            map_renderer,
+            font,
+            game_timer: GameTimer::new(),
+            history: History::new(HISTORY_LENGTH),
+            paused: false,
+        };
+        let frame = s.snapshot();
+        s.history.push(frame);
+
+        Ok(s)
+    }
+
+    fn advance(&mut self, frame_time: f64) -> GameResult<()> {
+        for actor in &mut self.actors {
+            update_actor(actor, &frame_time)?;
+        }
+        let frame = self.snapshot();
+        self.history.push(frame);
+        Ok(())
+    }
+
+    fn step_back(&mut self) {
+        self.paused = true;
+        if let Some(frame) = self.history.step_back().cloned() {
+            self.restore(&frame);
+        }
+    }
+
+    fn step_forward(&mut self) -> GameResult<()> {
+        self.paused = true;
+        match self.history.step_forward().cloned() {
+            Some(frame) => self.restore(&frame),
+            None => self.advance(STEP_TIME)?,
+        }
+        Ok(())
+    }
+
+    // Resuming from a point in the past drops the history after it.
+    fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        if !self.paused {
+            if let Some(frame) = self.history.current() {
+                self.game_timer.restore(&frame.timer);
+            }
+        }
+    }
+}
+
+impl Snapshot for MainState {
+    type State = Frame;
+
+    fn snapshot(&self) -> Frame {
+        Frame {
+            actors: self.actors.clone(),
+            timer: self.game_timer.snapshot(),
+        }
+    }
+
+    fn restore(&mut self, frame: &Frame) {
+        self.actors = frame.actors.clone();
+        self.game_timer.restore(&frame.timer);
+    }
+}
+
+impl event::EventHandler for MainState {
+    fn key_down_event(&mut self, ctx: &mut Context, keycode: Keycode, _keymod: Mod, repeat: bool) {
+        match keycode {
+            Keycode::Escape => ctx.quit().expect("Should never fail"),
+            Keycode::Space if !repeat => self.toggle_pause(),
+            Keycode::Left => self.step_back(),
+            Keycode::Right => self.step_forward().expect("Should never fail"),
+            _ => {}
+        }
+    }
+
+    fn update(&mut self, _ctx: &mut Context) -> GameResult<()> {
+        if !self.paused {
+            self.game_timer.tick();
+            let frame_time = self.game_timer.get_frame_time();
+            self.advance(frame_time)?;
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
+        graphics::clear(ctx);
+        self.map_renderer.draw(ctx)?;
+        {
+            let renderer = &mut GgezRenderer::new(ctx, &self.font);
+            for actor in &self.actors {
+                draw_waypoints(renderer, &actor.waypoints, WAYPOINT_RADIUS)?;
+                draw_player(renderer, actor, ACTOR_RADIUS)?;
+            }
+        }
+
+        graphics::present(ctx);
+
+        if !self.paused && (self.game_timer.get_ticks() % 100) == 0 {
+            println!("FPS: {}", ggez::timer::get_fps(ctx));
+        }
+        Ok(())
+    }
+}
+
+pub fn main() {
+    let mut c = conf::Conf::new();
+    c.window_mode.width = 1280;
+    c.window_mode.height = 800;
+    let ctx = &mut Context::load_from_conf("Test", "Waypoint", c).unwrap();
+
+    // We add the CARGO_MANIFEST_DIR/resources do the filesystems paths so
+    // we we look in the cargo project for files.
+    if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
+        let mut path = path::PathBuf::from(manifest_dir);
+        path.push("resources");
+        ctx.filesystem.mount(&path, true);
+    }
+
+    println!("{}", graphics::get_renderer_info(ctx).unwrap());
+    let state = &mut MainState::new(ctx).unwrap();
+    if let Err(e) = event::run(ctx, state) {
+        println!("Error encountered: {}", e);
+    } else {
+        println!("Game exited cleanly.");
+    }
+}
+
+pub fn update_actor(actor: &mut Actor, frame_time: &f64) -> GameResult<()> {
+    // Actors spawn on the first waypoint of their route, so advance before
+    // moving to avoid normalizing a zero-length vector.
+    if actor_at_waypoint(actor) {
+        let reached = actor.waypoints.remove(0);
+        actor.waypoints.push(reached);
+    }
+    move_towards_next_waypoint(actor, &(*frame_time as f32));
+    Ok(())
+}
@@ -0,0 +1,262 @@
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+extern crate skunkworks;
+
+use std::borrow::Cow;
+use std::env;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::process;
+
+use skunkworks::rng::SimRng;
+use skunkworks::simulation::{Scenario, SimEvent, Simulation};
+
+const USAGE: &str = "usage: simulate <scenario.json> [--ticks N] [--seed N] [--format csv|jsonl]
+                [--output FILE]
+
+Runs a scenario without a window and writes one record per actor and boid per
+tick, plus one record per event. Velocities are in pixels per second. The seed
+is printed to stderr, pass it back with --seed to repeat a run exactly.";
+
+#[derive(Clone, Copy)]
+enum Format {
+    Csv,
+    JsonLines,
+}
+
+struct Options {
+    scenario: String,
+    ticks: Option<u64>,
+    seed: Option<u64>,
+    format: Format,
+    output: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Record<'a> {
+    tick: u64,
+    time: f64,
+    entity: &'a str,
+    id: usize,
+    name: &'a str,
+    x: f64,
+    y: f64,
+    vx: f64,
+    vy: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    event: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    waypoint: Option<usize>,
+}
+
+pub fn main() {
+    let options = match parse_options(env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+
+    if let Err(e) = run(&options) {
+        eprintln!("Error encountered: {}", e);
+        process::exit(1);
+    }
+}
+
+// Gives `None` when asked for help instead of a run.
+fn parse_options<I: Iterator<Item = String>>(mut args: I) -> Result<Option<Options>, String> {
+    let mut scenario = None;
+    let mut ticks = None;
+    let mut seed = None;
+    let mut format = Format::Csv;
+    let mut output = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--ticks" => {
+                let value = args.next().ok_or("--ticks needs a value")?;
+                ticks = Some(value
+                    .parse()
+                    .map_err(|_| format!("Invalid tick count: {}", value))?);
+            }
+            "--seed" => {
+                let value = args.next().ok_or("--seed needs a value")?;
+                seed = Some(value
+                    .parse()
+                    .map_err(|_| format!("Invalid seed: {}", value))?);
+            }
+            "--format" => {
+                format = match args.next().as_ref().map(|value| value.as_str()) {
+                    Some("csv") => Format::Csv,
+                    Some("jsonl") => Format::JsonLines,
+                    _ => return Err("--format must be csv or jsonl".to_string()),
+                };
+            }
+            "--output" => {
+                output = Some(args.next().ok_or("--output needs a file name")?);
+            }
+            "-h" | "--help" => return Ok(None),
+            _ if scenario.is_none() && !arg.starts_with("--") => scenario = Some(arg),
+            _ => return Err(format!("Unexpected argument: {}", arg)),
+        }
+    }
+
+    Ok(Some(Options {
+        scenario: scenario.ok_or("No scenario file given")?,
+        ticks,
+        seed,
+        format,
+        output,
+    }))
+}
+
+fn run(options: &Options) -> Result<(), Box<dyn std::error::Error>> {
+    let scenario = Scenario::from_json(File::open(&options.scenario)?)?;
+    let ticks = options.ticks.unwrap_or(scenario.ticks);
+
+    let output: Box<dyn Write> = match options.output {
+        Some(ref path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout()),
+    };
+    let mut output = BufWriter::new(output);
+    if let Format::Csv = options.format {
+        writeln!(output, "tick,time,entity,id,name,x,y,vx,vy,event,waypoint")?;
+    }
+
+    let seed = options
+        .seed
+        .or(scenario.seed)
+        .unwrap_or_else(|| SimRng::from_time().seed());
+    eprintln!("Seed: {}", seed);
+
+    let mut simulation = Simulation::new(&scenario, seed);
+    write_state(&mut output, options.format, &simulation)?;
+    for _ in 0..ticks {
+        let events = simulation.step();
+        for event in &events {
+            write_event(&mut output, options.format, &simulation, event)?;
+        }
+        write_state(&mut output, options.format, &simulation)?;
+    }
+
+    output.flush()?;
+    Ok(())
+}
+
+fn write_state<W: Write>(output: &mut W, format: Format, simulation: &Simulation) -> io::Result<()> {
+    for (id, sim_actor) in simulation.actors.iter().enumerate() {
+        write_record(
+            output,
+            format,
+            &Record {
+                tick: simulation.tick,
+                time: simulation.time,
+                entity: "actor",
+                id,
+                name: &sim_actor.name,
+                x: f64::from(sim_actor.actor.position.x),
+                y: f64::from(sim_actor.actor.position.y),
+                vx: f64::from(sim_actor.velocity.x),
+                vy: f64::from(sim_actor.velocity.y),
+                event: None,
+                waypoint: None,
+            },
+        )?;
+    }
+
+    if let Some(ref flock) = simulation.flock {
+        for (id, vehicle) in flock.vehicles.iter().enumerate() {
+            let vehicle = vehicle.borrow();
+            // Boids move by their velocity once per tick.
+            write_record(
+                output,
+                format,
+                &Record {
+                    tick: simulation.tick,
+                    time: simulation.time,
+                    entity: "boid",
+                    id,
+                    name: "",
+                    x: vehicle.location.x,
+                    y: vehicle.location.y,
+                    vx: vehicle.velocity.x / simulation.delta_t,
+                    vy: vehicle.velocity.y / simulation.delta_t,
+                    event: None,
+                    waypoint: None,
+                },
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn write_event<W: Write>(
+    output: &mut W,
+    format: Format,
+    simulation: &Simulation,
+    event: &SimEvent,
+) -> io::Result<()> {
+    let (id, name, waypoint) = match *event {
+        SimEvent::WaypointReached { actor, waypoint } => (actor, "waypoint_reached", Some(waypoint)),
+        SimEvent::RouteFinished { actor } => (actor, "route_finished", None),
+    };
+    let sim_actor = &simulation.actors[id];
+    write_record(
+        output,
+        format,
+        &Record {
+            tick: simulation.tick,
+            time: simulation.time,
+            entity: "actor",
+            id,
+            name: &sim_actor.name,
+            x: f64::from(sim_actor.actor.position.x),
+            y: f64::from(sim_actor.actor.position.y),
+            vx: f64::from(sim_actor.velocity.x),
+            vy: f64::from(sim_actor.velocity.y),
+            event: Some(name),
+            waypoint,
+        },
+    )
+}
+
+fn write_record<W: Write>(output: &mut W, format: Format, record: &Record) -> io::Result<()> {
+    match format {
+        Format::Csv => writeln!(
+            output,
+            "{},{},{},{},{},{},{},{},{},{},{}",
+            record.tick,
+            record.time,
+            record.entity,
+            record.id,
+            csv_field(record.name),
+            record.x,
+            record.y,
+            record.vx,
+            record.vy,
+            record.event.unwrap_or(""),
+            record
+                .waypoint
+                .map_or_else(String::new, |waypoint| waypoint.to_string())
+        ),
+        Format::JsonLines => {
+            serde_json::to_writer(&mut *output, record)?;
+            writeln!(output)
+        }
+    }
+}
+
+// Quotes a field that would otherwise break the row, doubling inner quotes.
+fn csv_field<'a>(text: &'a str) -> Cow<'a, str> {
+    if text.contains(&[',', '"', '\n', '\r'][..]) {
+        Cow::Owned(format!("\"{}\"", text.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
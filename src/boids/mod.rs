@@ -0,0 +1,577 @@
+use std::cell::RefCell;
+
+use serde::Deserialize;
+
+use math::{LimitMode, Point2, Real, Vec2};
+use obstacle::{avoidance_force, Avoidance, Obstacle};
+use rng::SimRng;
+use {affine_transform, bearing_to_target};
+
+// What happens to vehicles at the edges of the flock's `width` by `height`
+// area.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Boundary {
+    // Vehicles leaving `constrain_distance` past one edge come back in at the
+    // opposite one, and see neighbours across the seam as if the area were a
+    // torus.
+    Wrap,
+    // Vehicles reflect off the edges like balls off a cushion.
+    Bounce,
+    // No hard edge, vehicles within `boundary_margin` of one are steered back
+    // towards the middle.
+    SteerAway,
+}
+
+impl Default for Boundary {
+    fn default() -> Self {
+        Boundary::Wrap
+    }
+}
+
+// `N` is the float type the flock runs on, f64 unless chosen otherwise.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, bound(deserialize = "N: Real + Deserialize<'de>"))]
+pub struct FlockParams<N = f64> {
+    pub width: N,
+    pub height: N,
+    pub max_force: N,
+    pub max_speed: N,
+    // How `max_force` and `max_speed` are enforced.
+    pub limit_mode: LimitMode,
+    // Radius of the wandering circle
+    pub circle_radius: N,
+    // Maximum degree of variance when wandering
+    pub radian_delta: N,
+    pub wander_weight: N,
+    pub separation_range: N,
+    pub separation_weight: N,
+    pub align_range: N,
+    pub align_weight: N,
+    pub cohesion_range: N,
+    pub cohesion_weight: N,
+    pub constrain_distance: N,
+    pub boundary: Boundary,
+    // How close to an edge `Boundary::SteerAway` starts turning vehicles, and
+    // how much harder than any other force it turns them.
+    pub boundary_margin: N,
+    pub boundary_weight: N,
+    pub avoidance: Avoidance<N>,
+    // Like `boundary_weight`, a multiple of `max_force` that steering round
+    // the flock's obstacles may use.
+    pub avoid_weight: N,
+}
+
+impl<N: Real> Default for FlockParams<N> {
+    fn default() -> Self {
+        FlockParams {
+            width: N::from_f64(1920.0),
+            height: N::from_f64(1200.0),
+            max_force: N::from_f64(0.03),
+            max_speed: N::from_f64(2.0),
+            limit_mode: LimitMode::Magnitude,
+            circle_radius: N::from_f64(100.0),
+            radian_delta: N::from_f64(0.017_453_3f64 * 15.0),
+            wander_weight: N::from_f64(1.0),
+            separation_range: N::from_f64(80.0),
+            separation_weight: N::from_f64(1.5),
+            align_range: N::from_f64(160.0),
+            align_weight: N::from_f64(1.0),
+            cohesion_range: N::from_f64(160.0),
+            cohesion_weight: N::from_f64(1.0),
+            constrain_distance: N::from_f64(20.0),
+            boundary: Boundary::Wrap,
+            boundary_margin: N::from_f64(100.0),
+            boundary_weight: N::from_f64(5.0),
+            avoidance: Avoidance::default(),
+            avoid_weight: N::from_f64(5.0),
+        }
+    }
+}
+
+impl<N: Real> FlockParams<N> {
+    // Where `from` sees `to`. When wrapping that's whichever copy of `to` is
+    // nearest, which may be across an edge, otherwise just `to`.
+    pub fn nearest_image(&self, from: Point2<N>, to: Point2<N>) -> Point2<N> {
+        if self.boundary != Boundary::Wrap {
+            return to;
+        }
+        let period_x = self.width + self.constrain_distance * N::from_f64(2.0);
+        let period_y = self.height + self.constrain_distance * N::from_f64(2.0);
+        Point2::new(
+            nearest_on_loop(from.x, to.x, period_x),
+            nearest_on_loop(from.y, to.y, period_y),
+        )
+    }
+}
+
+fn nearest_on_loop<N: Real>(from: N, to: N, period: N) -> N {
+    let half = period / N::from_f64(2.0);
+    if to - from > half {
+        to - period
+    } else if to - from < -half {
+        to + period
+    } else {
+        to
+    }
+}
+
+#[derive(Clone)]
+pub struct Flock<N = f64> {
+    pub params: FlockParams<N>,
+    pub vehicles: Vec<RefCell<Vehicle<N>>>,
+    pub obstacles: Vec<Obstacle<N>>,
+}
+
+impl<N: Real> Flock<N> {
+    pub fn new(params: FlockParams<N>) -> Flock<N> {
+        Flock {
+            params,
+            vehicles: Vec::new(),
+            obstacles: Vec::new(),
+        }
+    }
+
+    // Scatters `count` vehicles across the flock's bounds with random headings.
+    pub fn new_random(count: u32, params: FlockParams<N>, rng: &mut SimRng) -> Flock<N> {
+        let mut flock = Flock::new(params);
+        for _i in 0..count {
+            let vehicle = Vehicle::new_random(&flock.params, rng);
+            flock.vehicles.push(RefCell::new(vehicle));
+        }
+        flock
+    }
+
+    pub fn add(&mut self, velocity: Vec2<N>, location: Point2<N>) {
+        let vehicle = Vehicle::new(velocity, location, &self.params);
+        self.vehicles.push(RefCell::new(vehicle));
+    }
+
+    pub fn update(&mut self, rng: &mut SimRng) {
+        for vehicle in &self.vehicles {
+            vehicle
+                .borrow_mut()
+                .update(&self.vehicles, &self.obstacles, &self.params, rng);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Vehicle<N = f64> {
+    pub location: Point2<N>,
+    // Radians.
+    pub wander_angle: N,
+    pub velocity: Vec2<N>,
+    pub acceleration: Vec2<N>,
+    pub max_force: N,
+    pub max_speed: N,
+    pub limit_mode: LimitMode,
+}
+
+impl<N: Real> Vehicle<N> {
+    pub fn new(
+        velocity: Vec2<N>,
+        location: Point2<N>,
+        params: &FlockParams<N>,
+    ) -> Vehicle<N> {
+        Vehicle {
+            location,
+            wander_angle: N::zero(),
+            velocity,
+            acceleration: Vec2::zero(),
+            max_force: params.max_force,
+            max_speed: params.max_speed,
+            limit_mode: params.limit_mode,
+        }
+    }
+
+    // Somewhere in the flock's bounds, heading anywhere.
+    pub fn new_random(params: &FlockParams<N>, rng: &mut SimRng) -> Vehicle<N> {
+        let max_speed = params.max_speed.as_f64();
+        let velocity = Vec2::new(
+            N::from_f64(rng.gen_range(-max_speed, max_speed)),
+            N::from_f64(rng.gen_range(-max_speed, max_speed)),
+        );
+        let location = Point2::new(
+            N::from_f64(rng.gen_range(0.0, params.width.as_f64())),
+            N::from_f64(rng.gen_range(0.0, params.height.as_f64())),
+        );
+        Vehicle::new(velocity, location, params)
+    }
+
+    pub fn update(
+        &mut self,
+        vehicles: &[RefCell<Vehicle<N>>],
+        obstacles: &[Obstacle<N>],
+        params: &FlockParams<N>,
+        rng: &mut SimRng,
+    ) {
+        let mut separate = self.separate(vehicles, params);
+        let mut align = self.align(vehicles, params);
+        let mut cohere = self.cohesion(vehicles, params);
+        let mut wander = self.wander(N::from_f64(rng.next_f64()), params);
+
+        separate *= params.separation_weight;
+        align *= params.align_weight;
+        cohere *= params.cohesion_weight;
+        wander *= params.wander_weight;
+
+        self.apply_force(separate);
+        self.apply_force(align);
+        self.apply_force(cohere);
+        self.apply_force(wander);
+
+        // Held to `boundary_weight` times the usual force limit rather than
+        // the limit itself, so it can win out over the other forces combined.
+        if params.boundary == Boundary::SteerAway {
+            let contain = self.contain(params);
+            let limit = self.max_force * params.boundary_weight;
+            self.acceleration += self.limit_mode.apply(limit, contain);
+        }
+
+        // Avoiding an obstacle gets first call on `avoid_weight` times the
+        // usual force and is added to as much of the other forces as fits in
+        // what's left, or a crowded flock jostles its members into it.
+        if !obstacles.is_empty() {
+            let avoid = self.avoid(obstacles, params);
+            if avoid != Vec2::zero() {
+                let limit = self.max_force * params.avoid_weight;
+                let avoid = self.limit_mode.apply(limit, avoid);
+                let room = (limit - avoid.magnitude()).max(N::zero());
+                self.acceleration = avoid + self.limit_mode.apply(room, self.acceleration);
+            }
+        }
+
+        self.apply_acceleration();
+
+        match params.boundary {
+            Boundary::Wrap => self.constrain_location(
+                -params.constrain_distance,
+                params.width + params.constrain_distance,
+                -params.constrain_distance,
+                params.height + params.constrain_distance,
+            ),
+            Boundary::Bounce => {
+                self.bounce_location(N::zero(), params.width, N::zero(), params.height)
+            }
+            Boundary::SteerAway => {}
+        }
+    }
+
+    pub fn apply_force(&mut self, force: Vec2<N>) {
+        let force = self.limit_mode.apply(self.max_force, force);
+        self.acceleration += force;
+    }
+
+    pub fn apply_acceleration(&mut self) {
+        self.velocity += self.acceleration;
+        self.velocity = self.limit_mode.apply(self.max_speed, self.velocity);
+        self.location += self.velocity;
+        self.acceleration *= N::zero();
+    }
+
+    pub fn get_bearing(&self) -> N {
+        bearing_to_target(self.location, self.location + self.velocity)
+    }
+
+    pub fn seek(&mut self, target: Point2<N>) {
+        let mut desired = target - self.location;
+        let distance = desired.magnitude();
+
+        if distance > N::one() {
+            desired = desired.normalize();
+            desired *= self.max_speed;
+            let steer = desired - self.velocity;
+            self.apply_force(steer)
+        }
+    }
+
+    pub fn constrain_location(&mut self, x_min: N, x_max: N, y_min: N, y_max: N) {
+        if self.location.x < x_min {
+            self.location.x = x_max;
+        } else if self.location.x > x_max {
+            self.location.x = x_min;
+        }
+
+        if self.location.y < y_min {
+            self.location.y = y_max;
+        } else if self.location.y > y_max {
+            self.location.y = y_min;
+        }
+    }
+
+    // Reflects the vehicle back inside, turning its velocity round on the
+    // axis it crossed.
+    pub fn bounce_location(&mut self, x_min: N, x_max: N, y_min: N, y_max: N) {
+        let (x, velocity_x) = bounce(self.location.x, self.velocity.x, x_min, x_max);
+        let (y, velocity_y) = bounce(self.location.y, self.velocity.y, y_min, y_max);
+        self.location = Point2::new(x, y);
+        self.velocity = Vec2::new(velocity_x, velocity_y);
+    }
+
+    // Reynolds' containment: near an edge, the desired velocity keeps its
+    // speed along the edge and heads inwards at full speed.
+    pub fn contain(&self, params: &FlockParams<N>) -> Vec2<N> {
+        let margin = params.boundary_margin;
+        let mut desired = self.velocity;
+        if self.location.x < margin {
+            desired.x = self.max_speed;
+        } else if self.location.x > params.width - margin {
+            desired.x = -self.max_speed;
+        }
+        if self.location.y < margin {
+            desired.y = self.max_speed;
+        } else if self.location.y > params.height - margin {
+            desired.y = -self.max_speed;
+        }
+
+        if desired == self.velocity {
+            Vec2::zero()
+        } else {
+            desired.with_magnitude(self.max_speed) - self.velocity
+        }
+    }
+
+    pub fn avoid(&self, obstacles: &[Obstacle<N>], params: &FlockParams<N>) -> Vec2<N> {
+        avoidance_force(self.location, self.velocity, obstacles, &params.avoidance)
+    }
+
+    pub fn flee(&mut self, target: Point2<N>) {
+        let safety_range = N::from_f64(200.0);
+        let mut desired = self.location - target;
+
+        if desired.magnitude() < safety_range {
+            desired = desired.normalize();
+            desired *= self.max_speed;
+            let steer = desired - self.velocity;
+            self.apply_force(steer)
+        }
+    }
+
+    pub fn arrive(&mut self, target: Point2<N>) {
+        let slowing_distance = N::from_f64(100.0);
+        let mut desired = target - self.location;
+        let distance = desired.magnitude();
+
+        // Sitting right on the target leaves `desired` zero, which brakes.
+        if distance < slowing_distance {
+            let m = affine_transform(
+                distance,
+                N::zero(),
+                slowing_distance,
+                N::zero(),
+                self.max_speed,
+            );
+            desired = desired.with_magnitude(m);
+        } else {
+            desired = desired.with_magnitude(self.max_speed);
+        }
+
+        let steer = desired - self.velocity;
+        self.apply_force(steer);
+    }
+
+    // Ticks until the vehicle could reach `target`, closing at its own top
+    // speed plus `target_velocity`'s. How far ahead a moving target is worth
+    // predicting. Nothing can close on a still target, so don't look ahead.
+    fn look_ahead(&self, target: Point2<N>, target_velocity: Vec2<N>) -> N {
+        let closing_speed = self.max_speed + target_velocity.magnitude();
+        if closing_speed <= N::zero() {
+            return N::zero();
+        }
+        self.location.distance(target) / closing_speed
+    }
+
+    // Where `target` will be by the time the vehicle gets there, if it keeps
+    // its current velocity. The further away it is the further ahead this
+    // looks.
+    pub fn predict(&self, target: &Vehicle<N>) -> Point2<N> {
+        target.location + target.velocity * self.look_ahead(target.location, target.velocity)
+    }
+
+    // Seeks where `target` is going rather than where it is.
+    pub fn pursue(&mut self, target: &Vehicle<N>) {
+        let steer = self.pursuit(target);
+        self.apply_force(steer);
+    }
+
+    // The steering `pursue` applies, before it's limited. A target ahead and
+    // coming straight back at the vehicle will meet it anyway, so that one
+    // is headed for directly.
+    pub fn pursuit(&self, target: &Vehicle<N>) -> Vec2<N> {
+        let ahead = (target.location - self.location).dot(self.velocity) > N::zero();
+        let head_on = self.velocity.dot(target.velocity)
+            < N::from_f64(-0.95) * self.velocity.magnitude() * target.velocity.magnitude();
+        let aim = if ahead && head_on {
+            target.location
+        } else {
+            self.predict(target)
+        };
+        let desired = aim - self.location;
+        if desired.magnitude() > N::one() {
+            desired.with_magnitude(self.max_speed) - self.velocity
+        } else {
+            Vec2::zero()
+        }
+    }
+
+    // Flees from where `target` is going, once that's within `flee`'s range.
+    pub fn evade(&mut self, target: &Vehicle<N>) {
+        let predicted = self.predict(target);
+        self.flee(predicted);
+    }
+
+    // Steering straight away from where `target` is going, at any range and
+    // before it's limited.
+    pub fn evasion(&self, target: &Vehicle<N>) -> Vec2<N> {
+        let away = self.location - self.predict(target);
+        away.with_magnitude(self.max_speed) - self.velocity
+    }
+
+    // Keeps station beside `leader` like an escort. `offset` is in the
+    // leader's frame, x ahead of it and y to its right on screen. The vehicle
+    // matches the leader's velocity and closes on where its spot is heading
+    // on top of that, easing off like `arrive` so it settles on the spot
+    // instead of trailing behind it.
+    pub fn offset_pursuit(&mut self, leader: &Vehicle<N>, offset: Vec2<N>) {
+        let slowing_distance = N::from_f64(100.0);
+        let slot = leader.location + offset.rotate(leader.velocity.angle());
+        let predicted = slot + leader.velocity * self.look_ahead(slot, leader.velocity);
+        let to_slot = predicted - self.location;
+        let closing_speed = affine_transform(
+            to_slot.magnitude().min(slowing_distance),
+            N::zero(),
+            slowing_distance,
+            N::zero(),
+            self.max_speed,
+        );
+        let desired = leader.velocity + to_slot.with_magnitude(closing_speed);
+        let steer = self.limit_mode.apply(self.max_speed, desired) - self.velocity;
+        self.apply_force(steer);
+    }
+
+    // `random` is uniform in [0, 1).
+    pub fn wander(&mut self, random: N, params: &FlockParams<N>) -> Vec2<N> {
+        let half = N::from_f64(0.5);
+        let center = match self.velocity {
+            Vec2 { x, y } if x == N::zero() && y == N::zero() => self.location,
+            Vec2 { x, y } if x.is_nan() || y.is_nan() => self.location,
+            _ => self.location + (self.velocity * params.circle_radius),
+        };
+
+        self.wander_angle += random * params.radian_delta - params.radian_delta * half;
+
+        let x = params.circle_radius * self.wander_angle.cos();
+        let y = params.circle_radius * self.wander_angle.sin();
+        let offset = Vec2::new(x, y);
+
+        let mut desired = center + offset - self.location;
+        let distance = desired.magnitude();
+
+        if distance > N::one() {
+            desired = desired.normalize();
+            desired *= self.max_speed;
+            desired - self.velocity
+        } else {
+            Vec2::zero()
+        }
+    }
+
+    pub fn separate(
+        &mut self,
+        vehicles: &[RefCell<Vehicle<N>>],
+        params: &FlockParams<N>,
+    ) -> Vec2<N> {
+        let mut sum = Vec2::zero();
+        let mut count = 0;
+
+        for vehicle in vehicles {
+            if vehicle.as_ptr() != self as *mut Vehicle<N> {
+                let neighbour = vehicle.borrow();
+                let position = params.nearest_image(self.location, neighbour.location);
+                let d = self.location.distance(position);
+                if d < params.separation_range {
+                    let mut diff = self.location - position;
+                    diff = diff.normalize();
+                    diff /= d;
+                    sum += diff;
+                    count += 1;
+                }
+            }
+        }
+
+        if count > 0 {
+            sum /= N::from_f64(f64::from(count));
+            sum = sum.normalize();
+            sum *= params.max_speed;
+            sum - self.velocity
+        } else {
+            Vec2::zero()
+        }
+    }
+
+    pub fn align(&mut self, vehicles: &[RefCell<Vehicle<N>>], params: &FlockParams<N>) -> Vec2<N> {
+        let mut sum = Vec2::zero();
+        let mut count = 0;
+
+        for vehicle in vehicles {
+            if vehicle.as_ptr() != self as *mut Vehicle<N> {
+                let neighbour = vehicle.borrow();
+                let position = params.nearest_image(self.location, neighbour.location);
+                let d = self.location.distance(position);
+                if d < params.align_range {
+                    sum += neighbour.velocity;
+                    count += 1;
+                }
+            }
+        }
+
+        if count > 0 {
+            sum /= N::from_f64(f64::from(count));
+            sum = sum.normalize();
+            sum *= params.max_speed;
+            sum - self.velocity
+        } else {
+            Vec2::zero()
+        }
+    }
+
+    pub fn cohesion(
+        &mut self,
+        vehicles: &[RefCell<Vehicle<N>>],
+        params: &FlockParams<N>,
+    ) -> Vec2<N> {
+        let mut sum = Vec2::zero();
+        let mut count = N::zero();
+
+        for vehicle in vehicles {
+            if vehicle.as_ptr() != self as *mut Vehicle<N> {
+                let neighbour = vehicle.borrow();
+                let position = params.nearest_image(self.location, neighbour.location);
+                let d = self.location.distance(position);
+                if d < params.cohesion_range {
+                    sum += position.to_vec();
+                    count = count + N::one();
+                }
+            }
+        }
+
+        if count > N::zero() {
+            sum / count
+        } else {
+            Vec2::zero()
+        }
+    }
+}
+
+// One axis of `Vehicle::bounce_location`. Clamped as well, for vehicles fast
+// enough to overshoot by more than the whole width.
+fn bounce<N: Real>(position: N, velocity: N, min: N, max: N) -> (N, N) {
+    if position < min {
+        ((min + min - position).min(max), velocity.abs())
+    } else if position > max {
+        ((max + max - position).max(min), -velocity.abs())
+    } else {
+        (position, velocity)
+    }
+}
@@ -0,0 +1,162 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+use gif;
+use png::{self, HasParameters};
+
+// Quantization speed for GIF frames, 1 is the slowest and best, 30 the
+// fastest.
+const GIF_SPEED: i32 = 10;
+
+#[derive(Clone, Debug)]
+pub struct CaptureOptions {
+    // Where the numbered PNGs go, created if missing.
+    pub directory: PathBuf,
+    // Only every Nth tick is written.
+    pub every: u32,
+    // Simulated seconds per tick. Frame timing in the GIF comes from this
+    // rather than the wall clock.
+    pub step_time: f64,
+    pub gif: Option<PathBuf>,
+}
+
+impl Default for CaptureOptions {
+    fn default() -> Self {
+        CaptureOptions {
+            directory: PathBuf::from("frames"),
+            every: 1,
+            step_time: 1.0 / 60.0,
+            gif: None,
+        }
+    }
+}
+
+// Writes one frame per fixed timestep tick (or every Nth) as
+// `frame_00000.png`, `frame_00001.png`, ... and optionally appends it to an
+// animated GIF. Call `wants_frame` before grabbing the pixels, since that's
+// usually the expensive part, then `add_frame` or `skip_frame` once per tick.
+pub struct FrameCapture {
+    options: CaptureOptions,
+    ticks: u64,
+    frames: u32,
+    gif: Option<gif::Encoder<BufWriter<File>>>,
+    // Centiseconds of GIF delay written so far, to keep rounding errors from
+    // adding up over a long capture.
+    gif_time: u64,
+}
+
+impl FrameCapture {
+    pub fn new(options: CaptureOptions) -> io::Result<FrameCapture> {
+        if options.every == 0 || !(options.step_time > 0.0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Capture needs a positive interval and step time",
+            ));
+        }
+        fs::create_dir_all(&options.directory)?;
+        Ok(FrameCapture {
+            options,
+            ticks: 0,
+            frames: 0,
+            gif: None,
+            gif_time: 0,
+        })
+    }
+
+    pub fn options(&self) -> &CaptureOptions {
+        &self.options
+    }
+
+    pub fn frames_written(&self) -> u32 {
+        self.frames
+    }
+
+    pub fn wants_frame(&self) -> bool {
+        self.ticks % u64::from(self.options.every) == 0
+    }
+
+    pub fn skip_frame(&mut self) {
+        self.ticks += 1;
+    }
+
+    // `pixels` is RGBA, row by row from the top.
+    pub fn add_frame(&mut self, width: u32, height: u32, pixels: &[u8]) -> io::Result<()> {
+        if pixels.len() != width as usize * height as usize * 4 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Expected {}x{} RGBA pixels, got {} bytes", width, height, pixels.len()),
+            ));
+        }
+        if !self.wants_frame() {
+            self.skip_frame();
+            return Ok(());
+        }
+
+        let path = self
+            .options
+            .directory
+            .join(format!("frame_{:05}.png", self.frames));
+        write_png(BufWriter::new(File::create(path)?), width, height, pixels)?;
+        if self.options.gif.is_some() {
+            self.add_gif_frame(width, height, pixels)?;
+        }
+
+        self.frames += 1;
+        self.ticks += 1;
+        Ok(())
+    }
+
+    // Ends the GIF. Dropping the capture does the same.
+    pub fn finish(mut self) {
+        self.gif.take();
+    }
+
+    // A frame stays on screen until the next captured tick, so its delay is
+    // the time up to there, rounded to the GIF's hundredths of a second.
+    fn add_gif_frame(&mut self, width: u32, height: u32, pixels: &[u8]) -> io::Result<()> {
+        // Scoped here, since it clashes with png's `HasParameters`.
+        use gif::SetParameter;
+
+        let (gif_width, gif_height) = gif_size(width, height)?;
+        if self.gif.is_none() {
+            let path = self.options.gif.clone().expect("Only called with a GIF path");
+            let file = BufWriter::new(File::create(path)?);
+            let mut encoder = gif::Encoder::new(file, gif_width, gif_height, &[])?;
+            encoder.set(gif::Repeat::Infinite)?;
+            self.gif = Some(encoder);
+        }
+
+        let end_tick = self.ticks + u64::from(self.options.every);
+        let end_time = (end_tick as f64 * self.options.step_time * 100.0).round() as u64;
+        let delay = end_time.saturating_sub(self.gif_time);
+        self.gif_time += delay;
+
+        let mut pixels = pixels.to_vec();
+        let mut frame = gif::Frame::from_rgba_speed(gif_width, gif_height, &mut pixels, GIF_SPEED);
+        frame.delay = delay.min(u64::from(u16::max_value())) as u16;
+        let encoder = self.gif.as_mut().expect("Created above");
+        encoder.write_frame(&frame)
+    }
+}
+
+pub fn write_png<W: Write>(writer: W, width: u32, height: u32, pixels: &[u8]) -> io::Result<()> {
+    let mut encoder = png::Encoder::new(writer, width, height);
+    encoder
+        .set(png::ColorType::RGBA)
+        .set(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(pixels)?;
+    Ok(())
+}
+
+fn gif_size(width: u32, height: u32) -> io::Result<(u16, u16)> {
+    let max = u32::from(u16::max_value());
+    if width > max || height > max {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{}x{} is too large for a GIF", width, height),
+        ));
+    }
+    Ok((width as u16, height as u16))
+}
@@ -0,0 +1,77 @@
+use ggez::graphics::{self, DrawMode, DrawParam, Font, Image, Text};
+use ggez::{Context, GameError};
+
+use math::Point2;
+
+use super::{Color, Renderer};
+
+// Flatness of the circles ggez tessellates, same as the example meshes.
+const CIRCLE_TOLERANCE: f32 = 0.4;
+
+// Draws to the ggez window. Text is set in `font`.
+pub struct GgezRenderer<'a> {
+    pub ctx: &'a mut Context,
+    pub font: &'a Font,
+}
+
+impl<'a> GgezRenderer<'a> {
+    pub fn new(ctx: &'a mut Context, font: &'a Font) -> GgezRenderer<'a> {
+        GgezRenderer { ctx, font }
+    }
+}
+
+impl From<Color> for graphics::Color {
+    fn from(color: Color) -> graphics::Color {
+        graphics::Color::new(color.r, color.g, color.b, color.a)
+    }
+}
+
+impl<'a> Renderer for GgezRenderer<'a> {
+    type Sprite = Image;
+    type Error = GameError;
+
+    fn set_color(&mut self, color: Color) -> Result<(), GameError> {
+        graphics::set_color(self.ctx, color.into())
+    }
+
+    fn circle(&mut self, center: Point2<f32>, radius: f32) -> Result<(), GameError> {
+        graphics::circle(self.ctx, DrawMode::Fill, to_ggez(center), radius, CIRCLE_TOLERANCE)
+    }
+
+    fn line(&mut self, points: &[Point2<f32>], width: f32) -> Result<(), GameError> {
+        if points.len() < 2 {
+            return Ok(());
+        }
+        let points: Vec<graphics::Point2> = points.iter().map(|&point| to_ggez(point)).collect();
+        graphics::line(self.ctx, &points, width)
+    }
+
+    fn sprite(
+        &mut self,
+        sprite: &Image,
+        position: Point2<f32>,
+        rotation: f32,
+    ) -> Result<(), GameError> {
+        graphics::draw_ex(
+            self.ctx,
+            sprite,
+            DrawParam {
+                dest: to_ggez(position),
+                rotation,
+                offset: graphics::Point2::new(0.5, 0.5),
+                ..Default::default()
+            },
+        )
+    }
+
+    fn text(&mut self, position: Point2<f32>, text: &str) -> Result<(), GameError> {
+        let text = Text::new(self.ctx, text, self.font)?;
+        graphics::draw(self.ctx, &text, to_ggez(position), 0.0)
+    }
+}
+
+// Built field by field so it doesn't matter which nalgebra ggez was built
+// against.
+fn to_ggez(point: Point2<f32>) -> graphics::Point2 {
+    graphics::Point2::new(point.x, point.y)
+}
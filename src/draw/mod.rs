@@ -0,0 +1,205 @@
+use math::{Point2, Real};
+
+use boids::{Flock, Vehicle};
+use obstacle::Obstacle;
+use {Actor, Waypoint};
+
+#[cfg(feature = "render")]
+mod context;
+mod raster;
+mod svg;
+
+#[cfg(feature = "render")]
+pub use self::context::GgezRenderer;
+pub use self::raster::Raster;
+pub use self::svg::{Svg, SvgSprite};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+pub const PLAYER_COLOR: Color = Color {
+    r: 1.0,
+    g: 1.0,
+    b: 0.5,
+    a: 1.0,
+};
+pub const WAYPOINT_COLOR: Color = Color {
+    r: 0.0,
+    g: 1.0,
+    b: 0.0,
+    a: 1.0,
+};
+pub const WAYPOINT_LABEL_COLOR: Color = Color {
+    r: 0.0,
+    g: 0.2,
+    b: 0.0,
+    a: 1.0,
+};
+pub const ROUTE_COLOR: Color = Color {
+    r: 0.0,
+    g: 1.0,
+    b: 0.0,
+    a: 0.5,
+};
+pub const OBSTACLE_COLOR: Color = Color {
+    r: 0.6,
+    g: 0.45,
+    b: 0.3,
+    a: 1.0,
+};
+pub const PREDATOR_COLOR: Color = Color {
+    r: 1.0,
+    g: 0.3,
+    b: 0.2,
+    a: 1.0,
+};
+// Sprites are tinted by the current color, white leaves them as they are.
+const SPRITE_COLOR: Color = Color {
+    r: 1.0,
+    g: 1.0,
+    b: 1.0,
+    a: 1.0,
+};
+
+// The drawing primitives the draw functions are written against. Circles are
+// filled and centred on `center`, sprites are centred on `position` and
+// rotated about it, text hangs down from its top left corner at `position`.
+pub trait Renderer {
+    type Sprite;
+    type Error;
+
+    fn set_color(&mut self, color: Color) -> Result<(), Self::Error>;
+    fn circle(&mut self, center: Point2<f32>, radius: f32) -> Result<(), Self::Error>;
+    fn line(&mut self, points: &[Point2<f32>], width: f32) -> Result<(), Self::Error>;
+    fn sprite(
+        &mut self,
+        sprite: &Self::Sprite,
+        position: Point2<f32>,
+        rotation: f32,
+    ) -> Result<(), Self::Error>;
+    fn text(&mut self, position: Point2<f32>, text: &str) -> Result<(), Self::Error>;
+}
+
+impl Color {
+    pub fn new(r: f32, g: f32, b: f32, a: f32) -> Color {
+        Color { r, g, b, a }
+    }
+}
+
+pub fn draw_player<R: Renderer, N: Real>(
+    ctx: &mut R,
+    player: &Actor<N>,
+    radius: f32,
+) -> Result<(), R::Error> {
+    ctx.set_color(PLAYER_COLOR)?;
+    ctx.circle(player.position.cast(), radius)
+}
+
+pub fn draw_waypoint<R: Renderer, N: Real>(
+    ctx: &mut R,
+    radius: f32,
+    waypoint: &Waypoint<N>,
+) -> Result<(), R::Error> {
+    ctx.set_color(WAYPOINT_COLOR)?;
+    ctx.circle(waypoint.position.cast(), radius)
+}
+
+pub fn draw_waypoint_labels<R: Renderer, N: Real>(
+    ctx: &mut R,
+    waypoints: &[Waypoint<N>],
+) -> Result<(), R::Error> {
+    for (index, waypoint) in waypoints.iter().enumerate() {
+        let position: Point2<f32> = waypoint.position.cast();
+        let offset_position = Point2::new(position.x - 5.0, position.y - 11.0);
+        ctx.set_color(WAYPOINT_LABEL_COLOR)?;
+        ctx.text(offset_position, &(index + 1).to_string())?;
+    }
+    Ok(())
+}
+
+pub fn draw_waypoints<R: Renderer, N: Real>(
+    ctx: &mut R,
+    waypoints: &[Waypoint<N>],
+    radius: f32,
+) -> Result<(), R::Error> {
+    for waypoint in waypoints {
+        draw_waypoint(ctx, radius, waypoint)?;
+    }
+    Ok(())
+}
+
+// Connects the waypoints in order, back to the first one when `closed`.
+pub fn draw_route<R: Renderer, N: Real>(
+    ctx: &mut R,
+    waypoints: &[Waypoint<N>],
+    closed: bool,
+    width: f32,
+) -> Result<(), R::Error> {
+    let mut points: Vec<Point2<f32>> = waypoints
+        .iter()
+        .map(|waypoint| waypoint.position.cast())
+        .collect();
+    if closed && points.len() > 2 {
+        let first = points[0];
+        points.push(first);
+    }
+    ctx.set_color(ROUTE_COLOR)?;
+    ctx.line(&points, width)
+}
+
+pub fn draw_flock<R: Renderer, N: Real>(
+    ctx: &mut R,
+    flock: &Flock<N>,
+    sprite: &R::Sprite,
+) -> Result<(), R::Error> {
+    ctx.set_color(SPRITE_COLOR)?;
+    for vehicle in &flock.vehicles {
+        let vehicle = vehicle.borrow();
+        let bearing = vehicle.get_bearing().as_f64() as f32;
+        ctx.sprite(sprite, vehicle.location.cast(), bearing)?;
+    }
+    Ok(())
+}
+
+// A single vehicle outside the flock, its sprite tinted `color`.
+pub fn draw_vehicle<R: Renderer, N: Real>(
+    ctx: &mut R,
+    vehicle: &Vehicle<N>,
+    sprite: &R::Sprite,
+    color: Color,
+) -> Result<(), R::Error> {
+    ctx.set_color(color)?;
+    let bearing = vehicle.get_bearing().as_f64() as f32;
+    ctx.sprite(sprite, vehicle.location.cast(), bearing)
+}
+
+// Circles are filled, polygons and walls drawn as outlines `width` wide.
+pub fn draw_obstacles<R: Renderer, N: Real>(
+    ctx: &mut R,
+    obstacles: &[Obstacle<N>],
+    width: f32,
+) -> Result<(), R::Error> {
+    ctx.set_color(OBSTACLE_COLOR)?;
+    for obstacle in obstacles {
+        match *obstacle {
+            Obstacle::Circle { center, radius } => {
+                ctx.circle(center.cast(), radius.as_f64() as f32)?;
+            }
+            Obstacle::Polygon { ref points } => {
+                let mut outline: Vec<Point2<f32>> =
+                    points.iter().map(|point| point.cast()).collect();
+                if let Some(&first) = outline.first() {
+                    outline.push(first);
+                }
+                ctx.line(&outline, width)?;
+            }
+            Obstacle::Wall { start, end } => ctx.line(&[start.cast(), end.cast()], width)?,
+        }
+    }
+    Ok(())
+}
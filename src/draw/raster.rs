@@ -0,0 +1,318 @@
+use std::io::{self, Read, Write};
+
+use math::{segment_distance, Point2};
+
+use png;
+
+use capture::write_png;
+use error::{LoadError, LoadResult};
+
+use super::{Color, Renderer};
+
+// Supersamples per pixel along each axis when filling shapes.
+const SAMPLES: u32 = 4;
+
+const GLYPH_WIDTH: usize = 5;
+const GLYPH_HEIGHT: usize = 7;
+
+// 5x7 digits, one row per byte with the leftmost pixel in bit 4. Waypoint
+// labels are numbers, so that is all the built-in font carries; any other
+// character only advances the pen.
+const DIGITS: [[u8; GLYPH_HEIGHT]; 10] = [
+    [0x0e, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0e],
+    [0x04, 0x0c, 0x04, 0x04, 0x04, 0x04, 0x0e],
+    [0x0e, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1f],
+    [0x1f, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0e],
+    [0x02, 0x06, 0x0a, 0x12, 0x1f, 0x02, 0x02],
+    [0x1f, 0x10, 0x1e, 0x01, 0x01, 0x11, 0x0e],
+    [0x06, 0x08, 0x10, 0x1e, 0x11, 0x11, 0x0e],
+    [0x1f, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+    [0x0e, 0x11, 0x11, 0x0e, 0x11, 0x11, 0x0e],
+    [0x0e, 0x11, 0x11, 0x0f, 0x01, 0x02, 0x0c],
+];
+
+// A CPU-only render target holding 8-bit RGBA pixels. It draws the same
+// scenes as the ggez renderer without needing a window or a GPU, which is
+// what the golden-image tests render into. Sprites are rasters themselves.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Raster {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+    color: Color,
+    // Size of one font pixel in raster pixels.
+    text_scale: u32,
+}
+
+impl Raster {
+    pub fn new(width: u32, height: u32, background: Color) -> Raster {
+        let mut raster = Raster {
+            width,
+            height,
+            pixels: vec![0; (width * height * 4) as usize],
+            color: Color::new(1.0, 1.0, 1.0, 1.0),
+            text_scale: 1,
+        };
+        raster.clear(background);
+        raster
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    // Rows top to bottom, four bytes per pixel.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let index = ((y * self.width + x) * 4) as usize;
+        [
+            self.pixels[index],
+            self.pixels[index + 1],
+            self.pixels[index + 2],
+            self.pixels[index + 3],
+        ]
+    }
+
+    pub fn set_pixel(&mut self, x: u32, y: u32, rgba: [u8; 4]) {
+        let index = ((y * self.width + x) * 4) as usize;
+        self.pixels[index..index + 4].copy_from_slice(&rgba);
+    }
+
+    pub fn set_text_scale(&mut self, scale: u32) {
+        self.text_scale = scale.max(1);
+    }
+
+    pub fn clear(&mut self, color: Color) {
+        let rgba = to_rgba8(color);
+        for pixel in self.pixels.chunks_mut(4) {
+            pixel.copy_from_slice(&rgba);
+        }
+    }
+
+    pub fn fill_circle(&mut self, center: Point2<f32>, radius: f32) {
+        if radius <= 0.0 {
+            return;
+        }
+        let radius_squared = radius * radius;
+        let bounds = (center.x - radius, center.y - radius, center.x + radius, center.y + radius);
+        self.fill_coverage(bounds, |x, y| {
+            let dx = x - center.x;
+            let dy = y - center.y;
+            dx * dx + dy * dy <= radius_squared
+        });
+    }
+
+    // Strokes the polyline through `points` with round joins and caps.
+    pub fn stroke_line(&mut self, points: &[Point2<f32>], width: f32) {
+        if points.len() < 2 || width <= 0.0 {
+            return;
+        }
+        let half_width = width / 2.0;
+        let mut bounds = (points[0].x, points[0].y, points[0].x, points[0].y);
+        for point in points {
+            bounds.0 = bounds.0.min(point.x - half_width);
+            bounds.1 = bounds.1.min(point.y - half_width);
+            bounds.2 = bounds.2.max(point.x + half_width);
+            bounds.3 = bounds.3.max(point.y + half_width);
+        }
+        self.fill_coverage(bounds, |x, y| {
+            let sample = Point2::new(x, y);
+            points
+                .windows(2)
+                .any(|segment| segment_distance(sample, segment[0], segment[1]) <= half_width)
+        });
+    }
+
+    // Draws `sprite` centred on `position`, rotated by `rotation` radians and
+    // tinted by the current color. Pixels are sampled nearest-neighbour.
+    pub fn draw_sprite(&mut self, sprite: &Raster, position: Point2<f32>, rotation: f32) {
+        let half_width = sprite.width as f32 / 2.0;
+        let half_height = sprite.height as f32 / 2.0;
+        let reach = (half_width * half_width + half_height * half_height).sqrt();
+        let x_min = clamp_to(position.x - reach, self.width);
+        let x_max = clamp_to(position.x + reach + 1.0, self.width);
+        let y_min = clamp_to(position.y - reach, self.height);
+        let y_max = clamp_to(position.y + reach + 1.0, self.height);
+        let (sin, cos) = (-rotation).sin_cos();
+        let tint = self.color;
+
+        for y in y_min..y_max {
+            for x in x_min..x_max {
+                let dx = x as f32 + 0.5 - position.x;
+                let dy = y as f32 + 0.5 - position.y;
+                let source_x = (dx * cos - dy * sin + half_width).floor();
+                let source_y = (dx * sin + dy * cos + half_height).floor();
+                if source_x < 0.0 || source_y < 0.0 || source_x >= sprite.width as f32
+                    || source_y >= sprite.height as f32
+                {
+                    continue;
+                }
+                let texel = sprite.pixel(source_x as u32, source_y as u32);
+                self.color = Color::new(
+                    tint.r * f32::from(texel[0]) / 255.0,
+                    tint.g * f32::from(texel[1]) / 255.0,
+                    tint.b * f32::from(texel[2]) / 255.0,
+                    tint.a,
+                );
+                self.blend(x, y, f32::from(texel[3]) / 255.0);
+            }
+        }
+        self.color = tint;
+    }
+
+    // Draws `text` with its top left corner at `position`.
+    pub fn fill_text(&mut self, text: &str, position: Point2<f32>) {
+        let scale = i64::from(self.text_scale);
+        let mut pen_x = position.x.round() as i64;
+        let top = position.y.round() as i64;
+
+        for character in text.chars() {
+            if let Some(digit) = character.to_digit(10) {
+                for (row, bits) in DIGITS[digit as usize].iter().enumerate() {
+                    for column in 0..GLYPH_WIDTH {
+                        if bits & (0x10 >> column) != 0 {
+                            let x = pen_x + column as i64 * scale;
+                            let y = top + row as i64 * scale;
+                            self.fill_block(x, y, scale);
+                        }
+                    }
+                }
+            }
+            pen_x += (GLYPH_WIDTH as i64 + 1) * scale;
+        }
+    }
+
+    pub fn to_png<W: Write>(&self, writer: W) -> io::Result<()> {
+        write_png(writer, self.width, self.height, &self.pixels)
+    }
+
+    pub fn from_png<R: Read>(reader: R) -> LoadResult<Raster> {
+        let invalid = |e: png::DecodingError| LoadError::invalid(format!("Invalid PNG: {}", e));
+        let (info, mut reader) = png::Decoder::new(reader).read_info().map_err(invalid)?;
+        let mut data = vec![0; info.buffer_size()];
+        reader.next_frame(&mut data).map_err(invalid)?;
+
+        let pixels = match (info.color_type, info.bit_depth) {
+            (png::ColorType::RGBA, png::BitDepth::Eight) => data,
+            (png::ColorType::RGB, png::BitDepth::Eight) => data.chunks(3)
+                .flat_map(|rgb| vec![rgb[0], rgb[1], rgb[2], 255])
+                .collect(),
+            (color_type, bit_depth) => {
+                return Err(LoadError::invalid(format!(
+                    "Unsupported PNG format: {:?} at {:?}",
+                    color_type, bit_depth
+                )))
+            }
+        };
+
+        Ok(Raster {
+            width: info.width,
+            height: info.height,
+            pixels,
+            color: Color::new(1.0, 1.0, 1.0, 1.0),
+            text_scale: 1,
+        })
+    }
+
+    // Blends the current color into every pixel of `bounds` (left, top,
+    // right, bottom) by the share of its supersamples that are `inside`.
+    fn fill_coverage<F: Fn(f32, f32) -> bool>(&mut self, bounds: (f32, f32, f32, f32), inside: F) {
+        let x_min = clamp_to(bounds.0, self.width);
+        let x_max = clamp_to(bounds.2 + 1.0, self.width);
+        let y_min = clamp_to(bounds.1, self.height);
+        let y_max = clamp_to(bounds.3 + 1.0, self.height);
+        let step = 1.0 / SAMPLES as f32;
+
+        for y in y_min..y_max {
+            for x in x_min..x_max {
+                let mut covered = 0;
+                for sample in 0..SAMPLES * SAMPLES {
+                    let sample_x = x as f32 + (sample % SAMPLES) as f32 * step + step / 2.0;
+                    let sample_y = y as f32 + (sample / SAMPLES) as f32 * step + step / 2.0;
+                    if inside(sample_x, sample_y) {
+                        covered += 1;
+                    }
+                }
+                if covered > 0 {
+                    self.blend(x, y, covered as f32 / (SAMPLES * SAMPLES) as f32);
+                }
+            }
+        }
+    }
+
+    fn fill_block(&mut self, x: i64, y: i64, size: i64) {
+        for block_y in y.max(0)..(y + size).min(i64::from(self.height)) {
+            for block_x in x.max(0)..(x + size).min(i64::from(self.width)) {
+                self.blend(block_x as u32, block_y as u32, 1.0);
+            }
+        }
+    }
+
+    // Source-over blending of the current color, weighted by `coverage`.
+    fn blend(&mut self, x: u32, y: u32, coverage: f32) {
+        let alpha = self.color.a * coverage;
+        let source = [self.color.r, self.color.g, self.color.b];
+        let index = ((y * self.width + x) * 4) as usize;
+        for (channel, value) in source.iter().enumerate() {
+            let destination = f32::from(self.pixels[index + channel]) / 255.0;
+            let blended = value * alpha + destination * (1.0 - alpha);
+            self.pixels[index + channel] = to_u8(blended);
+        }
+        let destination_alpha = f32::from(self.pixels[index + 3]) / 255.0;
+        self.pixels[index + 3] = to_u8(alpha + destination_alpha * (1.0 - alpha));
+    }
+}
+
+impl Renderer for Raster {
+    type Sprite = Raster;
+    type Error = io::Error;
+
+    fn set_color(&mut self, color: Color) -> io::Result<()> {
+        self.color = color;
+        Ok(())
+    }
+
+    fn circle(&mut self, center: Point2<f32>, radius: f32) -> io::Result<()> {
+        self.fill_circle(center, radius);
+        Ok(())
+    }
+
+    fn line(&mut self, points: &[Point2<f32>], width: f32) -> io::Result<()> {
+        self.stroke_line(points, width);
+        Ok(())
+    }
+
+    fn sprite(
+        &mut self,
+        sprite: &Raster,
+        position: Point2<f32>,
+        rotation: f32,
+    ) -> io::Result<()> {
+        self.draw_sprite(sprite, position, rotation);
+        Ok(())
+    }
+
+    fn text(&mut self, position: Point2<f32>, text: &str) -> io::Result<()> {
+        self.fill_text(text, position);
+        Ok(())
+    }
+}
+
+fn to_u8(value: f32) -> u8 {
+    (value.max(0.0).min(1.0) * 255.0).round() as u8
+}
+
+fn to_rgba8(color: Color) -> [u8; 4] {
+    [to_u8(color.r), to_u8(color.g), to_u8(color.b), to_u8(color.a)]
+}
+
+fn clamp_to(value: f32, limit: u32) -> u32 {
+    (value.floor().max(0.0) as u32).min(limit)
+}
@@ -0,0 +1,169 @@
+use std::io::{self, Write};
+
+use math::Point2;
+
+use super::{Color, Renderer};
+
+const FONT_SIZE: f32 = 12.0;
+
+// Collects a scene as SVG elements, for vector snapshots that can be viewed
+// in a browser or diffed as text.
+#[derive(Clone, Debug)]
+pub struct Svg {
+    width: u32,
+    height: u32,
+    body: String,
+    color: Color,
+}
+
+// An image the SVG links to rather than embeds. `width` and `height` are
+// the size it is drawn at.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SvgSprite {
+    pub href: String,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Svg {
+    pub fn new(width: u32, height: u32) -> Svg {
+        Svg {
+            width,
+            height,
+            body: String::new(),
+            color: Color::new(1.0, 1.0, 1.0, 1.0),
+        }
+    }
+
+    // Drops everything drawn so far and fills the background with `color`.
+    pub fn clear(&mut self, color: Color) {
+        self.body.clear();
+        let element = format!(
+            "<rect width=\"{}\" height=\"{}\" {}/>",
+            self.width,
+            self.height,
+            paint("fill", color)
+        );
+        self.element(&element);
+    }
+
+    pub fn to_svg_string(&self) -> String {
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" \
+             xmlns:xlink=\"http://www.w3.org/1999/xlink\" \
+             width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\n{2}</svg>\n",
+            self.width, self.height, self.body
+        )
+    }
+
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(self.to_svg_string().as_bytes())
+    }
+
+    fn element(&mut self, element: &str) {
+        self.body.push_str("  ");
+        self.body.push_str(element);
+        self.body.push('\n');
+    }
+}
+
+impl Renderer for Svg {
+    type Sprite = SvgSprite;
+    type Error = io::Error;
+
+    fn set_color(&mut self, color: Color) -> io::Result<()> {
+        self.color = color;
+        Ok(())
+    }
+
+    fn circle(&mut self, center: Point2<f32>, radius: f32) -> io::Result<()> {
+        let fill = paint("fill", self.color);
+        let element = format!(
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" {}/>",
+            center.x, center.y, radius, fill
+        );
+        self.element(&element);
+        Ok(())
+    }
+
+    fn line(&mut self, points: &[Point2<f32>], width: f32) -> io::Result<()> {
+        if points.len() < 2 {
+            return Ok(());
+        }
+        let points: Vec<String> = points
+            .iter()
+            .map(|point| format!("{},{}", point.x, point.y))
+            .collect();
+        let stroke = paint("stroke", self.color);
+        let element = format!(
+            "<polyline points=\"{}\" fill=\"none\" stroke-width=\"{}\" \
+             stroke-linecap=\"round\" stroke-linejoin=\"round\" {}/>",
+            points.join(" "),
+            width,
+            stroke
+        );
+        self.element(&element);
+        Ok(())
+    }
+
+    // SVG has no simple way to tint an image, so sprites ignore the color.
+    fn sprite(
+        &mut self,
+        sprite: &SvgSprite,
+        position: Point2<f32>,
+        rotation: f32,
+    ) -> io::Result<()> {
+        let element = format!(
+            "<image xlink:href=\"{}\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" \
+             transform=\"rotate({} {} {})\"/>",
+            escape(&sprite.href),
+            position.x - sprite.width / 2.0,
+            position.y - sprite.height / 2.0,
+            sprite.width,
+            sprite.height,
+            rotation.to_degrees(),
+            position.x,
+            position.y
+        );
+        self.element(&element);
+        Ok(())
+    }
+
+    fn text(&mut self, position: Point2<f32>, text: &str) -> io::Result<()> {
+        let fill = paint("fill", self.color);
+        let element = format!(
+            "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"{}\" \
+             dominant-baseline=\"hanging\" {}>{}</text>",
+            position.x,
+            position.y,
+            FONT_SIZE,
+            fill,
+            escape(text)
+        );
+        self.element(&element);
+        Ok(())
+    }
+}
+
+// `fill="rgb(...)"` plus an opacity attribute when the color isn't opaque.
+fn paint(attribute: &str, color: Color) -> String {
+    let channel = |value: f32| (value.max(0.0).min(1.0) * 255.0).round() as u8;
+    let mut paint = format!(
+        "{}=\"rgb({},{},{})\"",
+        attribute,
+        channel(color.r),
+        channel(color.g),
+        channel(color.b)
+    );
+    if color.a < 1.0 {
+        paint.push_str(&format!(" {}-opacity=\"{}\"", attribute, color.a.max(0.0)));
+    }
+    paint
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
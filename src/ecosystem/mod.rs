@@ -0,0 +1,222 @@
+use std::cell::RefCell;
+
+use serde::Deserialize;
+
+use boids::{Flock, FlockParams, Vehicle};
+use math::{Point2, Real};
+use rng::SimRng;
+
+// One kind of animal in an `Ecosystem`. Members flock by their own `params`
+// and only with each other, so species don't align or school together.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, bound(deserialize = "N: Real + Deserialize<'de>"))]
+pub struct Species<N = f64> {
+    pub name: String,
+    // Image each member is drawn with, loaded by whoever draws them.
+    pub sprite: String,
+    // Every species should share the same area and boundary.
+    pub params: FlockParams<N>,
+    // Names of the species this one hunts. Nothing hunts its own kind.
+    pub prey: Vec<String>,
+    // Members pursue the nearest prey within `hunt_range` and catch it once
+    // within `catch_distance`.
+    pub hunt_range: N,
+    pub catch_distance: N,
+    // Members flee the nearest predator within `flee_range`.
+    pub flee_range: N,
+    // Like `boundary_weight`, multiples of `max_force` that hunting and
+    // fleeing may use, so they win out over flocking.
+    pub hunt_weight: N,
+    pub flee_weight: N,
+    // Caught members turn up again somewhere random rather than being gone
+    // for good.
+    pub respawn: bool,
+}
+
+impl<N: Real> Default for Species<N> {
+    fn default() -> Self {
+        Species {
+            name: String::new(),
+            sprite: String::new(),
+            params: FlockParams::default(),
+            prey: Vec::new(),
+            hunt_range: N::from_f64(300.0),
+            catch_distance: N::from_f64(10.0),
+            flee_range: N::from_f64(150.0),
+            hunt_weight: N::from_f64(2.0),
+            flee_weight: N::from_f64(3.0),
+            respawn: true,
+        }
+    }
+}
+
+// A member of species `prey` caught at `location` by one of species
+// `predator`, both indices into `Ecosystem::species`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Catch<N = f64> {
+    pub predator: usize,
+    pub prey: usize,
+    pub location: Point2<N>,
+}
+
+// Several species sharing one area. `flocks[i]` holds the members of
+// `species[i]`.
+#[derive(Clone)]
+pub struct Ecosystem<N = f64> {
+    pub species: Vec<Species<N>>,
+    pub flocks: Vec<Flock<N>>,
+}
+
+impl<N: Real> Ecosystem<N> {
+    pub fn new() -> Ecosystem<N> {
+        Ecosystem {
+            species: Vec::new(),
+            flocks: Vec::new(),
+        }
+    }
+
+    // Adds `count` members scattered at random, returning the species' index.
+    pub fn add_species(&mut self, species: Species<N>, count: u32, rng: &mut SimRng) -> usize {
+        let flock = Flock::new_random(count, species.params.clone(), rng);
+        self.species.push(species);
+        self.flocks.push(flock);
+        self.species.len() - 1
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.species.iter().position(|species| species.name == name)
+    }
+
+    // Indices of the species `index` hunts.
+    pub fn prey_of(&self, index: usize) -> Vec<usize> {
+        let hunted = &self.species[index].prey;
+        (0..self.species.len())
+            .filter(|&other| other != index && hunted.contains(&self.species[other].name))
+            .collect()
+    }
+
+    // Indices of the species that hunt `index`.
+    pub fn predators_of(&self, index: usize) -> Vec<usize> {
+        (0..self.species.len())
+            .filter(|&other| self.prey_of(other).contains(&index))
+            .collect()
+    }
+
+    // Everyone reacts to where the others were at the start of the tick, so
+    // the order species are updated in doesn't matter. Then every species
+    // flocks and moves, and predators catch whatever prey they've reached.
+    pub fn update(&mut self, rng: &mut SimRng) -> Vec<Catch<N>> {
+        let members: Vec<Vec<Vehicle<N>>> = self
+            .flocks
+            .iter()
+            .map(|flock| {
+                flock
+                    .vehicles
+                    .iter()
+                    .map(|vehicle| vehicle.borrow().clone())
+                    .collect()
+            })
+            .collect();
+
+        for (index, species) in self.species.iter().enumerate() {
+            let predators: Vec<&Vehicle<N>> = self
+                .predators_of(index)
+                .into_iter()
+                .flat_map(|other| &members[other])
+                .collect();
+            let prey: Vec<&Vehicle<N>> = self
+                .prey_of(index)
+                .into_iter()
+                .flat_map(|other| &members[other])
+                .collect();
+            for vehicle in &self.flocks[index].vehicles {
+                let mut vehicle = vehicle.borrow_mut();
+                if let Some(threat) = nearest(&vehicle, &predators, species.flee_range, species)
+                {
+                    let limit = vehicle.max_force * species.flee_weight;
+                    let flee = vehicle.limit_mode.apply(limit, vehicle.evasion(&threat));
+                    vehicle.acceleration += flee;
+                }
+                if let Some(target) = nearest(&vehicle, &prey, species.hunt_range, species) {
+                    let limit = vehicle.max_force * species.hunt_weight;
+                    let hunt = vehicle.limit_mode.apply(limit, vehicle.pursuit(&target));
+                    vehicle.acceleration += hunt;
+                }
+            }
+        }
+
+        for flock in &mut self.flocks {
+            flock.update(rng);
+        }
+        self.catch(rng)
+    }
+
+    // Each predator catches at most one prey a tick, the first in range going
+    // by the order of its prey species.
+    fn catch(&mut self, rng: &mut SimRng) -> Vec<Catch<N>> {
+        let mut catches = Vec::new();
+        for hunter in 0..self.species.len() {
+            let hunters: Vec<Point2<N>> = self.flocks[hunter]
+                .vehicles
+                .iter()
+                .map(|vehicle| vehicle.borrow().location)
+                .collect();
+            let prey_species = self.prey_of(hunter);
+            for &location in &hunters {
+                for &prey in &prey_species {
+                    let distance = self.species[hunter].catch_distance;
+                    let params = &self.species[hunter].params;
+                    let respawn = self.species[prey].respawn;
+                    let flock = &mut self.flocks[prey];
+                    let caught = flock.vehicles.iter().position(|vehicle| {
+                        let image = params.nearest_image(location, vehicle.borrow().location);
+                        location.distance(image) < distance
+                    });
+                    if let Some(caught) = caught {
+                        catches.push(Catch {
+                            predator: hunter,
+                            prey,
+                            location: flock.vehicles[caught].borrow().location,
+                        });
+                        if respawn {
+                            let vehicle = Vehicle::new_random(&flock.params, rng);
+                            flock.vehicles[caught] = RefCell::new(vehicle);
+                        } else {
+                            flock.vehicles.remove(caught);
+                        }
+                        break;
+                    }
+                }
+            }
+        }
+        catches
+    }
+}
+
+impl<N: Real> Default for Ecosystem<N> {
+    fn default() -> Self {
+        Ecosystem::new()
+    }
+}
+
+// The closest of `others` within `range`, moved to wherever `vehicle` sees
+// it from, which may be across the seam of a wrapping area.
+fn nearest<N: Real>(
+    vehicle: &Vehicle<N>,
+    others: &[&Vehicle<N>],
+    range: N,
+    species: &Species<N>,
+) -> Option<Vehicle<N>> {
+    let mut nearest: Option<(N, &Vehicle<N>, Point2<N>)> = None;
+    for &other in others {
+        let image = species.params.nearest_image(vehicle.location, other.location);
+        let distance = vehicle.location.distance(image);
+        if distance < range && nearest.map_or(true, |(closest, _, _)| distance < closest) {
+            nearest = Some((distance, other, image));
+        }
+    }
+    nearest.map(|(_, other, image)| Vehicle {
+        location: image,
+        ..other.clone()
+    })
+}
@@ -0,0 +1,54 @@
+use std::error;
+use std::fmt;
+use std::io;
+
+#[cfg(feature = "render")]
+use ggez::GameError;
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    Invalid(String),
+}
+
+pub type LoadResult<T> = Result<T, LoadError>;
+
+impl LoadError {
+    pub fn invalid<S: Into<String>>(message: S) -> LoadError {
+        LoadError::Invalid(message.into())
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LoadError::Io(ref e) => write!(f, "IO error: {}", e),
+            LoadError::Invalid(ref message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl error::Error for LoadError {
+    fn description(&self) -> &str {
+        match *self {
+            LoadError::Io(ref e) => e.description(),
+            LoadError::Invalid(ref message) => message,
+        }
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(e: io::Error) -> LoadError {
+        LoadError::Io(e)
+    }
+}
+
+#[cfg(feature = "render")]
+impl From<LoadError> for GameError {
+    fn from(e: LoadError) -> GameError {
+        match e {
+            LoadError::Io(e) => GameError::IOError(e),
+            LoadError::Invalid(message) => GameError::ResourceLoadError(message),
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use std::f64::consts::PI;
+
+use math::{Point2, Real, Vec2};
+use orca::preferred_velocity;
+use {actor_at_waypoint, Actor};
+
+// Where followers stand relative to their leader. Offsets are in the
+// leader's frame, x ahead of it and y to its right on screen. Slots are
+// filled in order, which for lines, columns and wedges is nearest the leader
+// first.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Formation<N = f32> {
+    // Abreast of the leader, alternating right and left.
+    Line { spacing: N },
+    // Single file behind the leader.
+    Column { spacing: N },
+    // A V trailing back from the leader at its point.
+    Wedge { spacing: N },
+    // Evenly round the leader, starting straight ahead.
+    Circle { radius: N },
+    // Followers past the last slot queue up behind it, as far apart as that
+    // slot is from the leader.
+    Custom { slots: Vec<Vec2<N>> },
+}
+
+impl<N: Real> Formation<N> {
+    // Offsets for `count` followers, in the order they're filled.
+    pub fn offsets(&self, count: usize) -> Vec<Vec2<N>> {
+        (0..count).map(|slot| self.offset(slot, count)).collect()
+    }
+
+    fn offset(&self, slot: usize, count: usize) -> Vec2<N> {
+        // Pairs of slots share a rank, the first of each pair on the right.
+        let rank = N::from_f64((slot / 2 + 1) as f64);
+        let side = if slot % 2 == 0 { N::one() } else { -N::one() };
+        match *self {
+            Formation::Line { spacing } => Vec2::new(N::zero(), side * rank * spacing),
+            Formation::Column { spacing } => {
+                Vec2::new(-N::from_f64((slot + 1) as f64) * spacing, N::zero())
+            }
+            Formation::Wedge { spacing } => Vec2::new(-rank * spacing, side * rank * spacing),
+            Formation::Circle { radius } => {
+                let angle = N::from_f64(2.0 * PI * slot as f64 / count as f64);
+                Vec2::from_angle(angle) * radius
+            }
+            Formation::Custom { ref slots } => match slots.get(slot) {
+                Some(&offset) => offset,
+                None => match slots.last() {
+                    Some(&last) => {
+                        let behind = N::from_f64((slot + 1 - slots.len()) as f64);
+                        last - Vec2::new(last.magnitude() * behind, N::zero())
+                    }
+                    None => Vec2::zero(),
+                },
+            },
+        }
+    }
+}
+
+// A leader walking a route with followers keeping their places around it.
+// Followers' own waypoints are ignored.
+#[derive(Clone)]
+pub struct Squad<N = f32> {
+    pub leader: Actor<N>,
+    // Cycles through the leader's waypoints rather than stopping at the last.
+    pub looping: bool,
+    // Followers further than this from their slot close in at full speed,
+    // nearer ones ease off so they settle on it instead of overshooting.
+    pub slowing_distance: N,
+    // Which way the formation faces, the leader's last direction of travel.
+    pub heading: Vec2<N>,
+    followers: Vec<Actor<N>>,
+    formation: Formation<N>,
+    // `followers[i]` stands in slot `slots[i]`.
+    slots: Vec<usize>,
+}
+
+impl<N: Real> Squad<N> {
+    // Faces along +x until the leader first moves.
+    pub fn new(leader: Actor<N>, formation: Formation<N>) -> Squad<N> {
+        Squad {
+            leader,
+            looping: false,
+            slowing_distance: N::from_f64(50.0),
+            heading: Vec2::new(N::one(), N::zero()),
+            followers: Vec::new(),
+            formation,
+            slots: Vec::new(),
+        }
+    }
+
+    pub fn followers(&self) -> &[Actor<N>] {
+        &self.followers
+    }
+
+    pub fn formation(&self) -> &Formation<N> {
+        &self.formation
+    }
+
+    pub fn set_formation(&mut self, formation: Formation<N>) {
+        self.formation = formation;
+        self.assign_slots();
+    }
+
+    // Adds a follower and shuffles everyone into the slots nearest them.
+    // Returns the follower's index.
+    pub fn add(&mut self, follower: Actor<N>) -> usize {
+        self.followers.push(follower);
+        self.assign_slots();
+        self.followers.len() - 1
+    }
+
+    // Takes a follower out, closing the gap it leaves. Later followers move
+    // down an index.
+    pub fn remove(&mut self, index: usize) -> Actor<N> {
+        let follower = self.followers.remove(index);
+        self.assign_slots();
+        follower
+    }
+
+    // The slot `index` is in, 0 being the one filled first.
+    pub fn slot(&self, index: usize) -> usize {
+        self.slots[index]
+    }
+
+    // Where follower `index` should be standing right now.
+    pub fn slot_position(&self, index: usize) -> Point2<N> {
+        let offset = self.formation.offset(self.slots[index], self.followers.len());
+        self.leader.position + offset.rotate(self.heading.angle())
+    }
+
+    // Moves the leader along its route, then every follower towards its
+    // slot. Followers match the leader's velocity and close the gap to their
+    // slot on top of that, so they keep their places on the move rather than
+    // trailing behind them. A step of no time changes nothing.
+    pub fn update(&mut self, delta_t: N) {
+        if delta_t <= N::zero() {
+            return;
+        }
+        if actor_at_waypoint(&self.leader) {
+            let reached = self.leader.waypoints.remove(0);
+            if self.looping {
+                self.leader.waypoints.push(reached);
+            }
+        }
+        let leader_velocity = preferred_velocity(&self.leader, delta_t);
+        self.leader.position += leader_velocity * delta_t;
+        if leader_velocity != Vec2::zero() {
+            self.heading = leader_velocity.normalize();
+        }
+
+        for index in 0..self.followers.len() {
+            let slot = self.slot_position(index);
+            let follower = &mut self.followers[index];
+            let to_slot = slot - follower.position;
+            let distance = to_slot.magnitude();
+            let closing_speed = (follower.speed * distance / self.slowing_distance)
+                .min(follower.speed)
+                .min(distance / delta_t);
+            let velocity =
+                (leader_velocity + to_slot.with_magnitude(closing_speed)).limit(follower.speed);
+            follower.position += velocity * delta_t;
+        }
+    }
+
+    // Hands out the slots in order, each to whichever follower left is
+    // closest to it. Not the best possible matching, but cheap and it keeps
+    // settled followers where they are.
+    fn assign_slots(&mut self) {
+        let count = self.followers.len();
+        let angle = self.heading.angle();
+        let mut taken = vec![false; count];
+        self.slots = vec![0; count];
+        for (slot, offset) in self.formation.offsets(count).into_iter().enumerate() {
+            let position = self.leader.position + offset.rotate(angle);
+            let mut nearest: Option<(usize, N)> = None;
+            for (index, follower) in self.followers.iter().enumerate() {
+                let distance = follower.position.distance_squared(position);
+                if !taken[index] && nearest.map_or(true, |(_, closest)| distance < closest) {
+                    nearest = Some((index, distance));
+                }
+            }
+            if let Some((index, _)) = nearest {
+                taken[index] = true;
+                self.slots[index] = slot;
+            }
+        }
+    }
+}
@@ -1,11 +1,19 @@
 use std::time::{Duration, Instant};
 
+use rewind::Snapshot;
+
 pub struct GameTimer {
     previous_instant: Instant,
     frame_time: f64,
     tick_counter: usize,
 }
 
+#[derive(Clone, Copy, Debug)]
+pub struct TimerState {
+    pub frame_time: f64,
+    pub tick_counter: usize,
+}
+
 impl GameTimer {
     pub fn new() -> GameTimer {
         GameTimer {
@@ -36,6 +44,25 @@ impl Default for GameTimer {
     }
 }
 
+// Restoring restarts the frame clock, so the time spent scrubbing doesn't
+// show up as one huge frame afterwards.
+impl Snapshot for GameTimer {
+    type State = TimerState;
+
+    fn snapshot(&self) -> TimerState {
+        TimerState {
+            frame_time: self.frame_time,
+            tick_counter: self.tick_counter,
+        }
+    }
+
+    fn restore(&mut self, state: &TimerState) {
+        self.previous_instant = Instant::now();
+        self.frame_time = state.frame_time;
+        self.tick_counter = state.tick_counter;
+    }
+}
+
 fn duration_to_f64(duration: Duration) -> f64 {
     duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) * 1e-9
 }
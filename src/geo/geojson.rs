@@ -0,0 +1,102 @@
+use std::io::Read;
+
+use error::{LoadError, LoadResult};
+
+use serde_json::{self, Value};
+
+use super::{parse_timestamp, GeoPoint, GeoTrack, GeoTrackPoint};
+
+// Reads LineString and MultiLineString geometries, on their own or wrapped in
+// Features and FeatureCollections. Timestamps are taken from a "coordTimes"
+// or "times" feature property, the convention used by GPX converters.
+pub fn parse<R: Read>(reader: R) -> LoadResult<Vec<GeoTrack>> {
+    let root: Value = serde_json::from_reader(reader)
+        .map_err(|e| LoadError::invalid(format!("Invalid GeoJSON document: {}", e)))?;
+
+    let mut tracks = Vec::new();
+    collect_tracks(&root, &Value::Null, &mut tracks)?;
+    Ok(tracks)
+}
+
+fn collect_tracks(value: &Value, properties: &Value, tracks: &mut Vec<GeoTrack>) -> LoadResult<()> {
+    let name = properties
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or("")
+        .to_string();
+    let times = properties
+        .get("coordTimes")
+        .or_else(|| properties.get("times"));
+
+    match value.get("type").and_then(Value::as_str) {
+        Some("FeatureCollection") => {
+            for feature in array(value, "features")? {
+                collect_tracks(feature, &Value::Null, tracks)?;
+            }
+        }
+        Some("Feature") => {
+            let properties = value.get("properties").unwrap_or(&Value::Null);
+            if let Some(geometry) = value.get("geometry") {
+                collect_tracks(geometry, properties, tracks)?;
+            }
+        }
+        Some("GeometryCollection") => {
+            for geometry in array(value, "geometries")? {
+                collect_tracks(geometry, properties, tracks)?;
+            }
+        }
+        Some("LineString") => {
+            tracks.push(GeoTrack {
+                name,
+                points: parse_line(array(value, "coordinates")?, times)?,
+            });
+        }
+        Some("MultiLineString") => {
+            for (index, line) in array(value, "coordinates")?.iter().enumerate() {
+                let line = line
+                    .as_array()
+                    .ok_or_else(|| LoadError::invalid("MultiLineString lines must be arrays"))?;
+                tracks.push(GeoTrack {
+                    name: name.clone(),
+                    points: parse_line(line, times.and_then(|times| times.get(index)))?,
+                });
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn array<'a>(value: &'a Value, key: &str) -> LoadResult<&'a [Value]> {
+    value
+        .get(key)
+        .and_then(Value::as_array)
+        .map(|values| values.as_slice())
+        .ok_or_else(|| LoadError::invalid(format!("Expected \"{}\" to be an array", key)))
+}
+
+fn parse_line(coordinates: &[Value], times: Option<&Value>) -> LoadResult<Vec<GeoTrackPoint>> {
+    let mut points = Vec::with_capacity(coordinates.len());
+    for (index, position) in coordinates.iter().enumerate() {
+        let longitude = position.get(0).and_then(Value::as_f64);
+        let latitude = position.get(1).and_then(Value::as_f64);
+        let (latitude, longitude) = match (latitude, longitude) {
+            (Some(latitude), Some(longitude)) => (latitude, longitude),
+            _ => return Err(LoadError::invalid(format!("Invalid position: {}", position))),
+        };
+        let timestamp = match times
+            .and_then(|times| times.get(index))
+            .and_then(Value::as_str)
+        {
+            Some(time) => Some(parse_timestamp(time)?),
+            None => None,
+        };
+
+        points.push(GeoTrackPoint {
+            location: GeoPoint::new(latitude, longitude),
+            elevation: position.get(2).and_then(Value::as_f64),
+            timestamp,
+        });
+    }
+    Ok(points)
+}
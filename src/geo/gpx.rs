@@ -0,0 +1,68 @@
+use std::io::Read;
+
+use error::{LoadError, LoadResult};
+
+use xml_tree::{read_tree, Element};
+
+use super::{parse_timestamp, GeoPoint, GeoTrack, GeoTrackPoint};
+
+// Every <trk> becomes one track with its segments joined, every <rte> becomes
+// one track as well.
+pub fn parse<R: Read>(reader: R) -> LoadResult<Vec<GeoTrack>> {
+    let root = read_tree(reader)?;
+    if root.name != "gpx" {
+        return Err(LoadError::invalid("GPX document does not start with <gpx>"));
+    }
+
+    let mut tracks = Vec::new();
+    for track in root.children_named("trk") {
+        let mut points = Vec::new();
+        for segment in track.children_named("trkseg") {
+            for point in segment.children_named("trkpt") {
+                points.push(parse_point(point)?);
+            }
+        }
+        tracks.push(GeoTrack {
+            name: child_text(track, "name"),
+            points,
+        });
+    }
+
+    for route in root.children_named("rte") {
+        let mut points = Vec::new();
+        for point in route.children_named("rtept") {
+            points.push(parse_point(point)?);
+        }
+        tracks.push(GeoTrack {
+            name: child_text(route, "name"),
+            points,
+        });
+    }
+
+    Ok(tracks)
+}
+
+fn parse_point(element: &Element) -> LoadResult<GeoTrackPoint> {
+    let elevation = match element.child("ele") {
+        Some(ele) => Some(ele.text.trim().parse().map_err(|_| {
+            LoadError::invalid(format!("Invalid elevation: {}", ele.text))
+        })?),
+        None => None,
+    };
+    let timestamp = match element.child("time") {
+        Some(time) => Some(parse_timestamp(&time.text)?),
+        None => None,
+    };
+
+    Ok(GeoTrackPoint {
+        location: GeoPoint::new(element.parse_attribute("lat")?, element.parse_attribute("lon")?),
+        elevation,
+        timestamp,
+    })
+}
+
+fn child_text(element: &Element, name: &str) -> String {
+    element
+        .child(name)
+        .map_or_else(String::new, |child| child.text.trim().to_string())
+}
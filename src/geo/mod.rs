@@ -0,0 +1,219 @@
+use std::f64::consts::PI;
+use std::io::Read;
+
+use chrono::{DateTime, Utc};
+
+#[cfg(feature = "render")]
+use ggez::{Context, GameResult};
+
+use math::Point2;
+
+use error::{LoadError, LoadResult};
+use Waypoint;
+
+mod geojson;
+mod gpx;
+
+const EARTH_RADIUS: f64 = 6_378_137.0;
+// Web Mercator is undefined at the poles, slippy map tiles stop here.
+const MERCATOR_MAX_LATITUDE: f64 = 85.051_128_78;
+
+#[derive(Clone, Copy, Debug)]
+pub struct GeoPoint {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+pub struct GeoTrackPoint {
+    pub location: GeoPoint,
+    pub elevation: Option<f64>,
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+pub struct GeoTrack {
+    pub name: String,
+    pub points: Vec<GeoTrackPoint>,
+}
+
+pub struct TrackPoint {
+    pub waypoint: Waypoint,
+    pub elevation: Option<f32>,
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+pub struct Track {
+    pub name: String,
+    pub points: Vec<TrackPoint>,
+}
+
+// Both projections produce planar coordinates in metres with y pointing north.
+#[derive(Clone, Copy, Debug)]
+pub enum Projection {
+    WebMercator,
+    Equirectangular { origin: GeoPoint },
+}
+
+// Maps planar metres to screen pixels, flipping y so north is up.
+#[derive(Clone, Copy, Debug)]
+pub struct ScreenTransform {
+    pub pixels_per_metre: f64,
+    pub planar_origin: (f64, f64),
+    pub screen_origin: (f32, f32),
+}
+
+impl GeoPoint {
+    pub fn new(latitude: f64, longitude: f64) -> GeoPoint {
+        GeoPoint {
+            latitude,
+            longitude,
+        }
+    }
+}
+
+impl GeoTrack {
+    pub fn from_gpx<R: Read>(reader: R) -> LoadResult<Vec<GeoTrack>> {
+        gpx::parse(reader)
+    }
+
+    pub fn from_geojson<R: Read>(reader: R) -> LoadResult<Vec<GeoTrack>> {
+        geojson::parse(reader)
+    }
+}
+
+impl Track {
+    pub fn waypoints(&self) -> Vec<Waypoint> {
+        self.points
+            .iter()
+            .map(|point| point.waypoint.clone())
+            .collect()
+    }
+
+    // Seconds since the first timestamped point, if the point has a timestamp.
+    pub fn elapsed_seconds(&self, index: usize) -> Option<f64> {
+        let start = self.points.iter().filter_map(|point| point.timestamp).next()?;
+        let timestamp = self.points.get(index)?.timestamp?;
+        let elapsed = timestamp.signed_duration_since(start);
+        Some(elapsed.num_milliseconds() as f64 / 1000.0)
+    }
+}
+
+impl Projection {
+    // Centres an equirectangular projection on the first point of the tracks,
+    // which keeps distortion low for city-sized areas.
+    pub fn local_to(tracks: &[GeoTrack]) -> Option<Projection> {
+        tracks
+            .iter()
+            .flat_map(|track| track.points.iter())
+            .next()
+            .map(|point| Projection::Equirectangular {
+                origin: point.location,
+            })
+    }
+
+    pub fn project(&self, point: GeoPoint) -> (f64, f64) {
+        match *self {
+            Projection::WebMercator => {
+                let latitude = point
+                    .latitude
+                    .max(-MERCATOR_MAX_LATITUDE)
+                    .min(MERCATOR_MAX_LATITUDE)
+                    .to_radians();
+                let x = EARTH_RADIUS * point.longitude.to_radians();
+                let y = EARTH_RADIUS * (PI / 4.0 + latitude / 2.0).tan().ln();
+                (x, y)
+            }
+            Projection::Equirectangular { origin } => {
+                let x = EARTH_RADIUS * (point.longitude - origin.longitude).to_radians()
+                    * origin.latitude.to_radians().cos();
+                let y = EARTH_RADIUS * (point.latitude - origin.latitude).to_radians();
+                (x, y)
+            }
+        }
+    }
+}
+
+impl ScreenTransform {
+    // Scales and centres the projected tracks so they fill a `width` by
+    // `height` screen, leaving `margin` pixels free on every side.
+    pub fn fit(
+        tracks: &[GeoTrack],
+        projection: &Projection,
+        width: f32,
+        height: f32,
+        margin: f32,
+    ) -> ScreenTransform {
+        let mut min = (::std::f64::INFINITY, ::std::f64::INFINITY);
+        let mut max = (::std::f64::NEG_INFINITY, ::std::f64::NEG_INFINITY);
+        for point in tracks.iter().flat_map(|track| track.points.iter()) {
+            let (x, y) = projection.project(point.location);
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        }
+
+        if min.0 > max.0 {
+            return ScreenTransform {
+                pixels_per_metre: 1.0,
+                planar_origin: (0.0, 0.0),
+                screen_origin: (width / 2.0, height / 2.0),
+            };
+        }
+
+        let usable_width = f64::from((width - 2.0 * margin).max(1.0));
+        let usable_height = f64::from((height - 2.0 * margin).max(1.0));
+        let extent_x = (max.0 - min.0).max(::std::f64::EPSILON);
+        let extent_y = (max.1 - min.1).max(::std::f64::EPSILON);
+
+        ScreenTransform {
+            pixels_per_metre: (usable_width / extent_x).min(usable_height / extent_y),
+            planar_origin: ((min.0 + max.0) / 2.0, (min.1 + max.1) / 2.0),
+            screen_origin: (width / 2.0, height / 2.0),
+        }
+    }
+
+    pub fn to_screen(&self, planar: (f64, f64)) -> Point2<f32> {
+        let x = (planar.0 - self.planar_origin.0) * self.pixels_per_metre;
+        let y = (planar.1 - self.planar_origin.1) * self.pixels_per_metre;
+        Point2::new(self.screen_origin.0 + x as f32, self.screen_origin.1 - y as f32)
+    }
+}
+
+pub fn project_tracks(
+    tracks: &[GeoTrack],
+    projection: &Projection,
+    transform: &ScreenTransform,
+) -> Vec<Track> {
+    tracks
+        .iter()
+        .map(|track| Track {
+            name: track.name.clone(),
+            points: track
+                .points
+                .iter()
+                .map(|point| TrackPoint {
+                    waypoint: Waypoint {
+                        position: transform.to_screen(projection.project(point.location)),
+                    },
+                    elevation: point.elevation.map(|elevation| elevation as f32),
+                    timestamp: point.timestamp,
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+// Loads a `.gpx`, `.geojson` or `.json` file through the ggez filesystem.
+#[cfg(feature = "render")]
+pub fn load_tracks(ctx: &mut Context, path: &str) -> GameResult<Vec<GeoTrack>> {
+    let file = ctx.filesystem.open(path)?;
+    if path.ends_with(".gpx") {
+        Ok(GeoTrack::from_gpx(file)?)
+    } else {
+        Ok(GeoTrack::from_geojson(file)?)
+    }
+}
+
+fn parse_timestamp(text: &str) -> LoadResult<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(text.trim())
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+        .map_err(|e| LoadError::invalid(format!("Invalid timestamp {}: {}", text, e)))
+}
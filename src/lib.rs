@@ -1,142 +1,112 @@
 extern crate cgmath;
+extern crate chrono;
+#[cfg(feature = "render")]
 extern crate ggez;
+extern crate gif;
 extern crate nalgebra;
+extern crate png;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+extern crate xml;
 
-use std::f32::consts::PI;
+use std::f64::consts::FRAC_PI_2;
 
-use ggez::{Context, GameResult};
+use cgmath::num_traits::Num;
 
-use ggez::graphics;
-use ggez::graphics::{Color, Font, Mesh, Point2, Text};
-
-use nalgebra::normalize;
-
-use cgmath::{num_traits::{abs, signum, Num},
-             prelude::*,
-             Angle,
-             Rad,
-             Vector2};
+use obstacle::{avoidance_force, Avoidance, Obstacle};
 
+pub mod boids;
+pub mod capture;
+mod draw;
+pub mod ecosystem;
+pub mod error;
+pub mod formation;
 pub mod game_timer;
-
-pub const PLAYER_COLOR: Color = Color {
-    r: 1.0,
-    g: 1.0,
-    b: 0.5,
-    a: 1.0,
-};
-pub const WAYPOINT_COLOR: Color = Color {
-    r: 0.0,
-    g: 1.0,
-    b: 0.0,
-    a: 1.0,
-};
-pub const WAYPOINT_LABEL_COLOR: Color = Color {
-    r: 0.0,
-    g: 0.2,
-    b: 0.0,
-    a: 1.0,
-};
-
-pub struct Actor {
-    pub position: nalgebra::Vector2<f32>,
-    pub speed: f64,
-    pub waypoints: Vec<Waypoint>,
-}
-
-pub fn draw_player(ctx: &mut Context, player: &Actor, circle_mesh: &Mesh) -> GameResult<()> {
-    graphics::set_color(ctx, PLAYER_COLOR)?;
-    graphics::draw(
-        ctx,
-        circle_mesh,
-        Point2::from_coordinates(player.position),
-        0.0,
-    )
-}
-
-pub struct Waypoint {
-    pub position: nalgebra::Vector2<f32>,
-}
-
-impl Waypoint {
-    pub fn new(x: f32, y: f32) -> Waypoint {
-        Waypoint {
-            position: nalgebra::Vector2::new(x, y),
-        }
-    }
+pub mod geo;
+pub mod math;
+pub mod obstacle;
+pub mod orca;
+pub mod replay;
+pub mod rewind;
+pub mod rng;
+pub mod simulation;
+pub mod spatial;
+pub mod tiled;
+pub mod tween;
+pub mod waypoint;
+
+mod xml_tree;
+
+pub use draw::*;
+pub use math::{LimitMode, Point2, Real, Vec2};
+pub use waypoint::Waypoint;
+
+// Moves at `speed` units per second. `N` is the float type, f32 or f64.
+#[derive(Clone)]
+pub struct Actor<N = f32> {
+    pub position: Point2<N>,
+    pub speed: N,
+    pub waypoints: Vec<Waypoint<N>>,
 }
 
-pub fn draw_waypoint(ctx: &mut Context, mesh: &Mesh, waypoint: &Waypoint) -> GameResult<()> {
-    graphics::set_color(ctx, WAYPOINT_COLOR)?;
-    graphics::draw(ctx, mesh, Point2::from_coordinates(waypoint.position), 0.0)
-}
-
-pub fn draw_waypoint_labels(
-    ctx: &mut Context,
-    font: &Font,
-    waypoints: &[Waypoint],
-) -> GameResult<()> {
-    for (index, waypoint) in waypoints.iter().enumerate() {
-        let label = Text::new(ctx, &(index + 1).to_string(), font)?;
-        let offset_x = waypoint.position.x - 5.0;
-        let offset_y = waypoint.position.y - 11.0;
-        let offset_position = Point2::new(offset_x, offset_y);
-        graphics::set_color(ctx, WAYPOINT_LABEL_COLOR)?;
-        graphics::draw(ctx, &label, offset_position, 0.0)?;
-    }
-    Ok(())
-}
-
-pub fn draw_waypoints(
-    ctx: &mut Context,
-    waypoints: &[Waypoint],
-    circle_mesh: &Mesh,
-) -> GameResult<()> {
-    for waypoint in waypoints {
-        draw_waypoint(ctx, circle_mesh, waypoint)?;
-    }
-    Ok(())
-}
-
-pub fn actor_at_waypoint(actor: &Actor) -> bool {
+pub fn actor_at_waypoint<N: Real>(actor: &Actor<N>) -> bool {
     if !actor.waypoints.is_empty() {
-        let distance = nalgebra::distance(
-            &Point2::from_coordinates(actor.waypoints[0].position),
-            &Point2::from_coordinates(actor.position),
-        ) as f64;
-        if distance < (actor.speed * 0.01) {
+        let distance = actor.waypoints[0].position.distance(actor.position);
+        if distance < (actor.speed * N::from_f64(0.01)) {
             return true;
         }
     }
     false
 }
 
-pub fn move_towards_next_waypoint(actor: &mut Actor, delta_t: &f64) {
+pub fn move_towards_next_waypoint<N: Real>(actor: &mut Actor<N>, delta_t: &N) {
     if !actor.waypoints.is_empty() {
-        let velocity = (actor.speed * delta_t) as f32;
-        let vector_to_destination = normalize(&(actor.waypoints[0].position - actor.position));
+        let velocity = actor.speed * *delta_t;
+        let vector_to_destination = (actor.waypoints[0].position - actor.position).normalize();
         actor.position += vector_to_destination * velocity;
     }
 }
 
-pub fn limit_vector2(limit: f64, vector: Vector2<f64>) -> Vector2<f64> {
+// Like `move_towards_next_waypoint`, but bends the path round `obstacles`.
+// The detour is worked out afresh each step from the straight line to the
+// waypoint, so actors need no steering state of their own.
+pub fn move_around_obstacles<N: Real>(
+    actor: &mut Actor<N>,
+    obstacles: &[Obstacle<N>],
+    avoidance: &Avoidance<N>,
+    delta_t: &N,
+) {
+    if !actor.waypoints.is_empty() {
+        let direct = (actor.waypoints[0].position - actor.position).with_magnitude(actor.speed);
+        let steering = avoidance_force(actor.position, direct, obstacles, avoidance);
+        let velocity = (direct + steering).with_magnitude(actor.speed);
+        actor.position += velocity * *delta_t;
+    }
+}
+
+// Clamps x and y to `limit` separately. `Vec2::limit` keeps the direction
+// and the magnitude within `limit` instead.
+pub fn limit_vector2<N: Real>(limit: N, vector: Vec2<N>) -> Vec2<N> {
     let mut result = vector;
-    if abs(vector.x) > limit {
-        result.x = signum(result.x) * limit
+    if vector.x.abs() > limit {
+        result.x = result.x.signum() * limit
     }
-    if abs(vector.y) > limit {
-        result.y = signum(result.y) * limit
+    if vector.y.abs() > limit {
+        result.y = result.y.signum() * limit
     }
 
     result
 }
 
-pub fn bearing_to_target(origin: cgmath::Point2<f64>, target: cgmath::Point2<f64>) -> f32 {
-    let vector: Vector2<f64> = target - origin;
-    let rad: Rad<f64> = Angle::atan2(vector.y, vector.x);
-    rad.0 as f32 + PI / 2.0
+// Radians clockwise from straight up, in screen coordinates.
+pub fn bearing_to_target<N: Real>(origin: Point2<N>, target: Point2<N>) -> N {
+    let vector = target - origin;
+    vector.angle() + N::from_f64(FRAC_PI_2)
 }
 
+// Linear remap, see `tween::ease_transform` for the eased version.
 pub fn affine_transform<T>(value: T, from_min: T, from_max: T, to_min: T, to_max: T) -> T
 where
     T: Num + Copy,
@@ -144,8 +114,10 @@ where
     (value - from_min) * ((to_max - to_min) / (from_max - from_min)) + to_min
 }
 
-pub fn rotate_vector2(vector2: &mut Vector2<f64>, angle: Rad<f64>) {
-    let magnitude: f64 = vector2.magnitude();
-    vector2.x = Angle::cos(angle) * magnitude;
-    vector2.y = Angle::sin(angle) * magnitude;
+// Points the vector `angle` radians from the x axis, keeping its length.
+// This sets an absolute heading, `Vec2::rotate` turns by a relative angle.
+pub fn rotate_vector2<N: Real>(vector2: &mut Vec2<N>, angle: N) {
+    let magnitude = vector2.magnitude();
+    vector2.x = angle.cos() * magnitude;
+    vector2.y = angle.sin() * magnitude;
 }
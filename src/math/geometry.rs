@@ -0,0 +1,146 @@
+use std::f64::consts::PI;
+
+use super::{Point2, Real, Vec2};
+
+// Wraps an angle in radians into [-π, π).
+pub fn wrap_angle<N: Real>(angle: N) -> N {
+    let pi = N::from_f64(PI);
+    let two_pi = pi + pi;
+    angle - two_pi * ((angle + pi) / two_pi).floor()
+}
+
+// The signed turn in radians that takes `from` to `to` the short way round,
+// in [-π, π).
+pub fn angle_difference<N: Real>(from: N, to: N) -> N {
+    wrap_angle(to - from)
+}
+
+// Where segment a1-a2 crosses segment b1-b2, ends included. Parallel and
+// collinear segments give None even when they overlap.
+pub fn segment_intersection<N: Real>(
+    a1: Point2<N>,
+    a2: Point2<N>,
+    b1: Point2<N>,
+    b2: Point2<N>,
+) -> Option<Point2<N>> {
+    let a = a2 - a1;
+    let b = b2 - b1;
+    let denominator = a.perp_dot(b);
+    if denominator == N::zero() {
+        return None;
+    }
+    let between = b1 - a1;
+    let along_a = between.perp_dot(b) / denominator;
+    let along_b = between.perp_dot(a) / denominator;
+    let inside = |t: N| t >= N::zero() && t <= N::one();
+    if inside(along_a) && inside(along_b) {
+        Some(a1 + a * along_a)
+    } else {
+        None
+    }
+}
+
+// Distance from `point` to the nearest point of segment start-end. A
+// segment of no length is measured as the single point it is.
+pub fn segment_distance<N: Real>(point: Point2<N>, start: Point2<N>, end: Point2<N>) -> N {
+    let direction = end - start;
+    let length_squared = direction.dot(direction);
+    let fraction = if length_squared > N::zero() {
+        ((point - start).dot(direction) / length_squared)
+            .max(N::zero())
+            .min(N::one())
+    } else {
+        N::zero()
+    };
+    point.distance(start + direction * fraction)
+}
+
+// How far along a ray from `origin` heading `direction` it first touches the
+// circle, in world units rather than multiples of `direction`. A ray starting
+// inside the circle hits straight away at 0. A zero direction never hits.
+pub fn ray_circle_intersection<N: Real>(
+    origin: Point2<N>,
+    direction: Vec2<N>,
+    center: Point2<N>,
+    radius: N,
+) -> Option<N> {
+    let length = direction.magnitude();
+    if length == N::zero() {
+        return None;
+    }
+    let direction = direction / length;
+    let offset = origin - center;
+    let along = offset.dot(direction);
+    let outside = offset.magnitude_squared() - radius * radius;
+    if outside <= N::zero() {
+        return Some(N::zero());
+    }
+    if along > N::zero() {
+        return None;
+    }
+    let discriminant = along * along - outside;
+    if discriminant < N::zero() {
+        return None;
+    }
+    Some(-along - discriminant.sqrt())
+}
+
+// Even-odd rule, so self-intersecting polygons have holes where they
+// overlap. Points exactly on an edge may land on either side.
+pub fn point_in_polygon<N: Real>(point: Point2<N>, polygon: &[Point2<N>]) -> bool {
+    let mut inside = false;
+    let mut previous = match polygon.last() {
+        Some(&last) => last,
+        None => return false,
+    };
+    for &current in polygon {
+        if (current.y > point.y) != (previous.y > point.y) {
+            let along_edge = (point.y - current.y) / (previous.y - current.y);
+            let crossing = current.x + along_edge * (previous.x - current.x);
+            if point.x < crossing {
+                inside = !inside;
+            }
+        }
+        previous = current;
+    }
+    inside
+}
+
+// Positive when the vertices run counter-clockwise in a y-up frame, which is
+// clockwise on screen.
+pub fn polygon_signed_area<N: Real>(polygon: &[Point2<N>]) -> N {
+    let mut twice_area = N::zero();
+    for (&a, &b) in edges(polygon) {
+        twice_area += a.to_vec().perp_dot(b.to_vec());
+    }
+    twice_area / N::from_f64(2.0)
+}
+
+pub fn polygon_area<N: Real>(polygon: &[Point2<N>]) -> N {
+    polygon_signed_area(polygon).abs()
+}
+
+// The centre of mass of the polygon's area, either winding. None when the
+// polygon has no area to balance.
+pub fn polygon_centroid<N: Real>(polygon: &[Point2<N>]) -> Option<Point2<N>> {
+    let area = polygon_signed_area(polygon);
+    if area == N::zero() {
+        return None;
+    }
+    let mut x = N::zero();
+    let mut y = N::zero();
+    for (&a, &b) in edges(polygon) {
+        let cross = a.to_vec().perp_dot(b.to_vec());
+        x += (a.x + b.x) * cross;
+        y += (a.y + b.y) * cross;
+    }
+    let scale = N::from_f64(6.0) * area;
+    Some(Point2::new(x / scale, y / scale))
+}
+
+// Each vertex paired with the next, wrapping back to the first.
+fn edges<'a, N>(
+    polygon: &'a [Point2<N>],
+) -> impl Iterator<Item = (&'a Point2<N>, &'a Point2<N>)> + 'a {
+    polygon.iter().zip(polygon.iter().cycle().skip(1))
+}
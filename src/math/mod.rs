@@ -0,0 +1,389 @@
+use std::fmt::Debug;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use cgmath;
+use cgmath::num_traits::{Float, Num};
+use nalgebra::{self, Scalar};
+
+use limit_vector2;
+
+mod geometry;
+mod transform;
+
+pub use self::geometry::{angle_difference, point_in_polygon, polygon_area, polygon_centroid,
+                         polygon_signed_area, ray_circle_intersection, segment_distance,
+                         segment_intersection, wrap_angle};
+pub use self::transform::Affine2;
+
+// The float types movement and steering can run on: f32 for big crowds
+// where memory and cache use matter, f64 for long runs that need the
+// precision.
+pub trait Real:
+    Float + AddAssign + SubAssign + MulAssign + DivAssign + Debug + Default + Scalar
+{
+    fn from_f64(value: f64) -> Self;
+    fn as_f64(self) -> f64;
+}
+
+impl Real for f32 {
+    fn from_f64(value: f64) -> f32 {
+        value as f32
+    }
+
+    fn as_f64(self) -> f64 {
+        f64::from(self)
+    }
+}
+
+impl Real for f64 {
+    fn from_f64(value: f64) -> f64 {
+        value
+    }
+
+    fn as_f64(self) -> f64 {
+        self
+    }
+}
+
+// How forces and speeds are held to a limit. `Magnitude` keeps the
+// direction, `PerAxis` clamps x and y separately like `limit_vector2`, so
+// diagonals can reach √2 times the limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitMode {
+    Magnitude,
+    PerAxis,
+}
+
+impl LimitMode {
+    pub fn apply<N: Real>(self, limit: N, vector: Vec2<N>) -> Vec2<N> {
+        match self {
+            LimitMode::Magnitude => vector.limit(limit),
+            LimitMode::PerAxis => limit_vector2(limit, vector),
+        }
+    }
+}
+
+impl Default for LimitMode {
+    fn default() -> Self {
+        LimitMode::Magnitude
+    }
+}
+
+// A displacement, velocity or force. Positions are `Point2`s, subtracting
+// two of them gives the `Vec2` between them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Vec2<N> {
+    pub x: N,
+    pub y: N,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Point2<N> {
+    pub x: N,
+    pub y: N,
+}
+
+impl<N> Vec2<N> {
+    pub fn new(x: N, y: N) -> Vec2<N> {
+        Vec2 { x, y }
+    }
+}
+
+impl<N: Num + Copy> Vec2<N> {
+    pub fn zero() -> Vec2<N> {
+        Vec2::new(N::zero(), N::zero())
+    }
+
+    pub fn dot(self, other: Vec2<N>) -> N {
+        self.x * other.x + self.y * other.y
+    }
+
+    // The z component of the 3D cross product, positive when `other` lies
+    // counter-clockwise of `self` in a y-up frame.
+    pub fn perp_dot(self, other: Vec2<N>) -> N {
+        self.x * other.y - self.y * other.x
+    }
+
+    pub fn magnitude_squared(self) -> N {
+        self.dot(self)
+    }
+}
+
+impl<N: Float> Vec2<N> {
+    // A unit vector pointing `angle` radians from the x axis.
+    pub fn from_angle(angle: N) -> Vec2<N> {
+        Vec2::new(angle.cos(), angle.sin())
+    }
+
+    pub fn magnitude(self) -> N {
+        self.magnitude_squared().sqrt()
+    }
+
+    // Like nalgebra and cgmath, a zero vector comes out as NaNs.
+    pub fn normalize(self) -> Vec2<N> {
+        self / self.magnitude()
+    }
+
+    // Angle from the x axis in radians, between -π and π.
+    pub fn angle(self) -> N {
+        self.y.atan2(self.x)
+    }
+
+    pub fn lerp(self, other: Vec2<N>, amount: N) -> Vec2<N> {
+        self + (other - self) * amount
+    }
+
+    // Turns the vector by `angle` radians from wherever it points now,
+    // counter-clockwise in a y-up frame.
+    pub fn rotate(self, angle: N) -> Vec2<N> {
+        let (sin, cos) = angle.sin_cos();
+        Vec2::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
+
+    // Scales the vector down to `max` long if it's any longer, keeping its
+    // direction.
+    pub fn limit(self, max: N) -> Vec2<N> {
+        self.clamp_magnitude(N::zero(), max)
+    }
+
+    // Same direction, `magnitude` long. A zero vector has no direction and
+    // stays zero.
+    pub fn with_magnitude(self, magnitude: N) -> Vec2<N> {
+        let current = self.magnitude();
+        if current > N::zero() {
+            self * (magnitude / current)
+        } else {
+            self
+        }
+    }
+
+    // Stretches or shrinks the vector to between `min` and `max` long. A zero
+    // vector stays zero.
+    pub fn clamp_magnitude(self, min: N, max: N) -> Vec2<N> {
+        let current = self.magnitude();
+        if current > max {
+            self.with_magnitude(max)
+        } else if current < min {
+            self.with_magnitude(min)
+        } else {
+            self
+        }
+    }
+}
+
+impl<N: Real> Vec2<N> {
+    // Converts between f32 and f64 vectors.
+    pub fn cast<M: Real>(self) -> Vec2<M> {
+        Vec2::new(M::from_f64(self.x.as_f64()), M::from_f64(self.y.as_f64()))
+    }
+}
+
+impl<N> Point2<N> {
+    pub fn new(x: N, y: N) -> Point2<N> {
+        Point2 { x, y }
+    }
+}
+
+impl<N: Num + Copy> Point2<N> {
+    pub fn origin() -> Point2<N> {
+        Point2::new(N::zero(), N::zero())
+    }
+
+    pub fn from_vec(vector: Vec2<N>) -> Point2<N> {
+        Point2::new(vector.x, vector.y)
+    }
+
+    // The vector from the origin to this point.
+    pub fn to_vec(self) -> Vec2<N> {
+        Vec2::new(self.x, self.y)
+    }
+
+    pub fn distance_squared(self, other: Point2<N>) -> N {
+        (other - self).magnitude_squared()
+    }
+}
+
+impl<N: Float> Point2<N> {
+    pub fn distance(self, other: Point2<N>) -> N {
+        (other - self).magnitude()
+    }
+
+    pub fn lerp(self, other: Point2<N>, amount: N) -> Point2<N> {
+        self + (other - self) * amount
+    }
+
+    pub fn rotate_about(self, center: Point2<N>, angle: N) -> Point2<N> {
+        center + (self - center).rotate(angle)
+    }
+}
+
+impl<N: Real> Point2<N> {
+    pub fn cast<M: Real>(self) -> Point2<M> {
+        Point2::new(M::from_f64(self.x.as_f64()), M::from_f64(self.y.as_f64()))
+    }
+}
+
+impl<N: Num + Copy> Add for Vec2<N> {
+    type Output = Vec2<N>;
+
+    fn add(self, other: Vec2<N>) -> Vec2<N> {
+        Vec2::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl<N: Num + Copy> Sub for Vec2<N> {
+    type Output = Vec2<N>;
+
+    fn sub(self, other: Vec2<N>) -> Vec2<N> {
+        Vec2::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl<N: Num + Copy> Mul<N> for Vec2<N> {
+    type Output = Vec2<N>;
+
+    fn mul(self, scalar: N) -> Vec2<N> {
+        Vec2::new(self.x * scalar, self.y * scalar)
+    }
+}
+
+impl<N: Num + Copy> Div<N> for Vec2<N> {
+    type Output = Vec2<N>;
+
+    fn div(self, scalar: N) -> Vec2<N> {
+        Vec2::new(self.x / scalar, self.y / scalar)
+    }
+}
+
+impl<N: Neg<Output = N>> Neg for Vec2<N> {
+    type Output = Vec2<N>;
+
+    fn neg(self) -> Vec2<N> {
+        Vec2::new(-self.x, -self.y)
+    }
+}
+
+impl<N: Num + Copy> AddAssign for Vec2<N> {
+    fn add_assign(&mut self, other: Vec2<N>) {
+        *self = *self + other;
+    }
+}
+
+impl<N: Num + Copy> SubAssign for Vec2<N> {
+    fn sub_assign(&mut self, other: Vec2<N>) {
+        *self = *self - other;
+    }
+}
+
+impl<N: Num + Copy> MulAssign<N> for Vec2<N> {
+    fn mul_assign(&mut self, scalar: N) {
+        *self = *self * scalar;
+    }
+}
+
+impl<N: Num + Copy> DivAssign<N> for Vec2<N> {
+    fn div_assign(&mut self, scalar: N) {
+        *self = *self / scalar;
+    }
+}
+
+impl<N: Num + Copy> Add<Vec2<N>> for Point2<N> {
+    type Output = Point2<N>;
+
+    fn add(self, vector: Vec2<N>) -> Point2<N> {
+        Point2::new(self.x + vector.x, self.y + vector.y)
+    }
+}
+
+impl<N: Num + Copy> Sub<Vec2<N>> for Point2<N> {
+    type Output = Point2<N>;
+
+    fn sub(self, vector: Vec2<N>) -> Point2<N> {
+        Point2::new(self.x - vector.x, self.y - vector.y)
+    }
+}
+
+impl<N: Num + Copy> Sub for Point2<N> {
+    type Output = Vec2<N>;
+
+    fn sub(self, other: Point2<N>) -> Vec2<N> {
+        Vec2::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl<N: Num + Copy> AddAssign<Vec2<N>> for Point2<N> {
+    fn add_assign(&mut self, vector: Vec2<N>) {
+        *self = *self + vector;
+    }
+}
+
+impl<N: Num + Copy> SubAssign<Vec2<N>> for Point2<N> {
+    fn sub_assign(&mut self, vector: Vec2<N>) {
+        *self = *self - vector;
+    }
+}
+
+// `2.0 * vector` as well as `vector * 2.0`. Coherence rules only allow this
+// for concrete scalar types.
+macro_rules! impl_scalar_mul {
+    ($($scalar:ty),*) => {$(
+        impl Mul<Vec2<$scalar>> for $scalar {
+            type Output = Vec2<$scalar>;
+
+            fn mul(self, vector: Vec2<$scalar>) -> Vec2<$scalar> {
+                vector * self
+            }
+        }
+    )*}
+}
+
+impl_scalar_mul!(f32, f64);
+
+impl<N: Scalar> From<nalgebra::Vector2<N>> for Vec2<N> {
+    fn from(vector: nalgebra::Vector2<N>) -> Vec2<N> {
+        Vec2::new(vector.x, vector.y)
+    }
+}
+
+impl<N: Scalar> From<Vec2<N>> for nalgebra::Vector2<N> {
+    fn from(vector: Vec2<N>) -> nalgebra::Vector2<N> {
+        nalgebra::Vector2::new(vector.x, vector.y)
+    }
+}
+
+impl<N: Scalar> From<nalgebra::Point2<N>> for Point2<N> {
+    fn from(point: nalgebra::Point2<N>) -> Point2<N> {
+        Point2::new(point.x, point.y)
+    }
+}
+
+impl<N: Scalar> From<Point2<N>> for nalgebra::Point2<N> {
+    fn from(point: Point2<N>) -> nalgebra::Point2<N> {
+        nalgebra::Point2::new(point.x, point.y)
+    }
+}
+
+impl<N> From<cgmath::Vector2<N>> for Vec2<N> {
+    fn from(vector: cgmath::Vector2<N>) -> Vec2<N> {
+        Vec2::new(vector.x, vector.y)
+    }
+}
+
+impl<N> From<Vec2<N>> for cgmath::Vector2<N> {
+    fn from(vector: Vec2<N>) -> cgmath::Vector2<N> {
+        cgmath::Vector2::new(vector.x, vector.y)
+    }
+}
+
+impl<N> From<cgmath::Point2<N>> for Point2<N> {
+    fn from(point: cgmath::Point2<N>) -> Point2<N> {
+        Point2::new(point.x, point.y)
+    }
+}
+
+impl<N> From<Point2<N>> for cgmath::Point2<N> {
+    fn from(point: Point2<N>) -> cgmath::Point2<N> {
+        cgmath::Point2::new(point.x, point.y)
+    }
+}
@@ -0,0 +1,147 @@
+use std::ops::Mul;
+
+use super::{Point2, Real, Vec2};
+
+// A 2D affine transform, the top two rows of
+//
+//     | a  b  tx |
+//     | c  d  ty |
+//     | 0  0  1  |
+//
+// applied to column vectors. `first.then(second)` and `second * first` both
+// apply `first` and then `second`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Affine2<N> {
+    pub a: N,
+    pub b: N,
+    pub c: N,
+    pub d: N,
+    pub tx: N,
+    pub ty: N,
+}
+
+impl<N: Real> Affine2<N> {
+    pub fn identity() -> Affine2<N> {
+        Affine2::scale(N::one(), N::one())
+    }
+
+    pub fn translation(offset: Vec2<N>) -> Affine2<N> {
+        Affine2 {
+            tx: offset.x,
+            ty: offset.y,
+            ..Affine2::identity()
+        }
+    }
+
+    // Counter-clockwise by `angle` radians in a y-up frame, which is
+    // clockwise on screen.
+    pub fn rotation(angle: N) -> Affine2<N> {
+        let (sin, cos) = angle.sin_cos();
+        Affine2 {
+            a: cos,
+            b: -sin,
+            c: sin,
+            d: cos,
+            tx: N::zero(),
+            ty: N::zero(),
+        }
+    }
+
+    pub fn scale(x: N, y: N) -> Affine2<N> {
+        Affine2 {
+            a: x,
+            b: N::zero(),
+            c: N::zero(),
+            d: y,
+            tx: N::zero(),
+            ty: N::zero(),
+        }
+    }
+
+    // Rotation by `angle` radians around `center` instead of the origin.
+    pub fn rotation_about(center: Point2<N>, angle: N) -> Affine2<N> {
+        Affine2::translation(-center.to_vec())
+            .then(Affine2::rotation(angle))
+            .then(Affine2::translation(center.to_vec()))
+    }
+
+    pub fn then(self, next: Affine2<N>) -> Affine2<N> {
+        next * self
+    }
+
+    pub fn transform_point(&self, point: Point2<N>) -> Point2<N> {
+        Point2::new(
+            self.a * point.x + self.b * point.y + self.tx,
+            self.c * point.x + self.d * point.y + self.ty,
+        )
+    }
+
+    // Vectors are directions and offsets, so translation doesn't apply.
+    pub fn transform_vector(&self, vector: Vec2<N>) -> Vec2<N> {
+        Vec2::new(
+            self.a * vector.x + self.b * vector.y,
+            self.c * vector.x + self.d * vector.y,
+        )
+    }
+
+    pub fn determinant(&self) -> N {
+        self.a * self.d - self.b * self.c
+    }
+
+    // None when the transform collapses the plane onto a line or a point.
+    pub fn inverse(&self) -> Option<Affine2<N>> {
+        let determinant = self.determinant();
+        if determinant == N::zero() {
+            return None;
+        }
+        let a = self.d / determinant;
+        let b = -self.b / determinant;
+        let c = -self.c / determinant;
+        let d = self.a / determinant;
+        Some(Affine2 {
+            a,
+            b,
+            c,
+            d,
+            tx: -(a * self.tx + b * self.ty),
+            ty: -(c * self.tx + d * self.ty),
+        })
+    }
+}
+
+impl<N: Real> Default for Affine2<N> {
+    fn default() -> Self {
+        Affine2::identity()
+    }
+}
+
+impl<N: Real> Mul for Affine2<N> {
+    type Output = Affine2<N>;
+
+    fn mul(self, other: Affine2<N>) -> Affine2<N> {
+        Affine2 {
+            a: self.a * other.a + self.b * other.c,
+            b: self.a * other.b + self.b * other.d,
+            c: self.c * other.a + self.d * other.c,
+            d: self.c * other.b + self.d * other.d,
+            tx: self.a * other.tx + self.b * other.ty + self.tx,
+            ty: self.c * other.tx + self.d * other.ty + self.ty,
+        }
+    }
+}
+
+impl<N: Real> Mul<Point2<N>> for Affine2<N> {
+    type Output = Point2<N>;
+
+    fn mul(self, point: Point2<N>) -> Point2<N> {
+        self.transform_point(point)
+    }
+}
+
+impl<N: Real> Mul<Vec2<N>> for Affine2<N> {
+    type Output = Vec2<N>;
+
+    fn mul(self, vector: Vec2<N>) -> Vec2<N> {
+        self.transform_vector(vector)
+    }
+}
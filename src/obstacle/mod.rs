@@ -0,0 +1,277 @@
+use std::f64::consts::FRAC_PI_4;
+
+use serde::Deserialize;
+
+use math::{polygon_centroid, segment_intersection, Affine2, Point2, Real, Vec2};
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Obstacle<N = f64> {
+    Circle { center: Point2<N>, radius: N },
+    // Closed, the last point joins back up with the first.
+    Polygon { points: Vec<Point2<N>> },
+    // A line segment agents slide along rather than steer round.
+    Wall { start: Point2<N>, end: Point2<N> },
+}
+
+impl<N: Real> Obstacle<N> {
+    pub fn cast<M: Real>(&self) -> Obstacle<M> {
+        match *self {
+            Obstacle::Circle { center, radius } => Obstacle::Circle {
+                center: center.cast(),
+                radius: M::from_f64(radius.as_f64()),
+            },
+            Obstacle::Polygon { ref points } => Obstacle::Polygon {
+                points: points.iter().map(|point| point.cast()).collect(),
+            },
+            Obstacle::Wall { start, end } => Obstacle::Wall {
+                start: start.cast(),
+                end: end.cast(),
+            },
+        }
+    }
+}
+
+// How agents look out for obstacles. Lengths and speeds are in the units of
+// the velocity passed in, per tick for boids and per second for actors. The
+// defaults suit boids, `Avoidance::for_actors` suits actors.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, bound(deserialize = "N: Real + Deserialize<'de>"))]
+pub struct Avoidance<N = f32> {
+    // The detection box reaches `min_length` plus `look_ahead` times the
+    // speed ahead, so faster agents see further.
+    pub min_length: N,
+    pub look_ahead: N,
+    // Half the width of the detection box, the agent's own size.
+    pub radius: N,
+    // Share of the speed taken off when an obstacle is right ahead.
+    pub braking_weight: N,
+    // Wall feelers, one straight ahead and two half as long at 45 degrees.
+    pub feeler_length: N,
+}
+
+impl<N: Real> Default for Avoidance<N> {
+    fn default() -> Self {
+        Avoidance {
+            min_length: N::from_f64(40.0),
+            look_ahead: N::from_f64(20.0),
+            radius: N::from_f64(12.0),
+            braking_weight: N::from_f64(0.2),
+            feeler_length: N::from_f64(40.0),
+        }
+    }
+}
+
+impl<N: Real> Avoidance<N> {
+    // Actors move in pixels per second, so they look half a second ahead
+    // rather than twenty ticks.
+    pub fn for_actors() -> Avoidance<N> {
+        Avoidance {
+            look_ahead: N::from_f64(0.5),
+            ..Avoidance::default()
+        }
+    }
+}
+
+// Steering away from the circles and walls and polygons around an agent, in
+// the same units as its velocity. Callers limit it like any other force.
+pub fn avoidance_force<N: Real>(
+    position: Point2<N>,
+    velocity: Vec2<N>,
+    obstacles: &[Obstacle<N>],
+    avoidance: &Avoidance<N>,
+) -> Vec2<N> {
+    avoid_obstacles(position, velocity, obstacles, avoidance)
+        + follow_walls(position, velocity, obstacles, avoidance)
+}
+
+// Sweeps a box as wide as the agent ahead along its velocity and steers
+// round the nearest circle or polygon inside it. The sideways push grows the
+// closer the obstacle is and the more squarely it sits in the way, and the
+// agent brakes as well so it has time to turn. Walls are left to
+// `follow_walls`.
+pub fn avoid_obstacles<N: Real>(
+    position: Point2<N>,
+    velocity: Vec2<N>,
+    obstacles: &[Obstacle<N>],
+    avoidance: &Avoidance<N>,
+) -> Vec2<N> {
+    let speed = velocity.magnitude();
+    if speed == N::zero() {
+        return Vec2::zero();
+    }
+    let length = avoidance.min_length + speed * avoidance.look_ahead;
+    // In the agent's frame it sits at the origin heading along +x.
+    let to_local =
+        Affine2::translation(-position.to_vec()).then(Affine2::rotation(-velocity.angle()));
+
+    let mut nearest: Option<Hit<N>> = None;
+    for obstacle in obstacles {
+        let hit = match *obstacle {
+            Obstacle::Circle { center, radius } => {
+                circle_hit(to_local * center, radius + avoidance.radius, length)
+            }
+            Obstacle::Polygon { ref points } => {
+                let local: Vec<_> = points.iter().map(|&point| to_local * point).collect();
+                polygon_hit(&local, avoidance.radius, length)
+            }
+            Obstacle::Wall { .. } => None,
+        };
+        if let Some(hit) = hit {
+            if nearest.map_or(true, |nearest| hit.distance < nearest.distance) {
+                nearest = Some(hit);
+            }
+        }
+    }
+
+    let hit = match nearest {
+        Some(hit) => hit,
+        None => return Vec2::zero(),
+    };
+    let urgency = ((length - hit.distance) / length).max(N::zero()).min(N::one());
+    let away = if hit.offset < N::zero() { N::one() } else { -N::one() };
+    let lateral = away * speed * hit.overlap * (N::one() + urgency);
+    let braking = -speed * urgency * avoidance.braking_weight;
+    Affine2::rotation(velocity.angle()) * Vec2::new(braking, lateral)
+}
+
+// Feels ahead for walls and pushes back out along the wall's normal by as
+// far as the feeler reaches through it. That leaves the part of the velocity
+// running along the wall alone, so agents slide along walls and follow them
+// round instead of bouncing off.
+pub fn follow_walls<N: Real>(
+    position: Point2<N>,
+    velocity: Vec2<N>,
+    obstacles: &[Obstacle<N>],
+    avoidance: &Avoidance<N>,
+) -> Vec2<N> {
+    if velocity.magnitude_squared() == N::zero() {
+        return Vec2::zero();
+    }
+    let heading = velocity.normalize();
+    let diagonal = N::from_f64(FRAC_PI_4);
+    let half = avoidance.feeler_length / N::from_f64(2.0);
+    let feelers = [
+        heading * avoidance.feeler_length,
+        heading.rotate(diagonal) * half,
+        heading.rotate(-diagonal) * half,
+    ];
+
+    let mut force = Vec2::zero();
+    for &feeler in &feelers {
+        let tip = position + feeler;
+        let mut nearest: Option<(N, Point2<N>, Vec2<N>)> = None;
+        for obstacle in obstacles {
+            if let Obstacle::Wall { start, end } = *obstacle {
+                if let Some(hit) = segment_intersection(position, tip, start, end) {
+                    let distance = position.distance_squared(hit);
+                    if nearest.map_or(true, |(nearest, _, _)| distance < nearest) {
+                        nearest = Some((distance, hit, wall_normal(start, end, position)));
+                    }
+                }
+            }
+        }
+        if let Some((_, hit, normal)) = nearest {
+            force += normal * (tip - hit).magnitude();
+        }
+    }
+    force
+}
+
+// The nearest part of an obstacle in the detection box, in the agent's
+// frame. `offset` is which side of the agent's path the obstacle mostly lies
+// on and `overlap` how squarely it blocks it, from 0 to 1.
+#[derive(Clone, Copy)]
+struct Hit<N> {
+    distance: N,
+    offset: N,
+    overlap: N,
+}
+
+// Where the path along the x axis first enters the circle grown by the
+// agent's radius, if it does so within `length`. Right away if the agent is
+// already inside it.
+fn circle_hit<N: Real>(center: Point2<N>, radius: N, length: N) -> Option<Hit<N>> {
+    if center.x < N::zero() || center.y.abs() >= radius {
+        return None;
+    }
+    let half_chord = (radius * radius - center.y * center.y).sqrt();
+    let distance = (center.x - half_chord).max(N::zero());
+    if distance > length {
+        return None;
+    }
+    Some(Hit {
+        distance,
+        offset: center.y,
+        overlap: (radius - center.y.abs()) / radius,
+    })
+}
+
+// The closest point of any edge crossing the box from x = 0 to `length`
+// and y = -`radius` to `radius`.
+fn polygon_hit<N: Real>(points: &[Point2<N>], radius: N, length: N) -> Option<Hit<N>> {
+    let mut nearest: Option<Point2<N>> = None;
+    for (index, &start) in points.iter().enumerate() {
+        let end = points[(index + 1) % points.len()];
+        if let Some((a, b)) = clip_to_box(start, end, radius, length) {
+            let closer = if a.x < b.x { a } else { b };
+            if nearest.map_or(true, |nearest| closer.x < nearest.x) {
+                nearest = Some(closer);
+            }
+        }
+    }
+    let nearest = nearest?;
+    let offset = polygon_centroid(points).map_or(nearest.y, |centroid| centroid.y);
+    Some(Hit {
+        distance: nearest.x,
+        offset,
+        overlap: N::one(),
+    })
+}
+
+// Liang-Barsky clipping of the segment to the detection box.
+fn clip_to_box<N: Real>(
+    start: Point2<N>,
+    end: Point2<N>,
+    radius: N,
+    length: N,
+) -> Option<(Point2<N>, Point2<N>)> {
+    let delta = end - start;
+    let mut enter = N::zero();
+    let mut leave = N::one();
+    let edges = [
+        (-delta.x, start.x),
+        (delta.x, length - start.x),
+        (-delta.y, start.y + radius),
+        (delta.y, radius - start.y),
+    ];
+    for &(towards, room) in &edges {
+        if towards == N::zero() {
+            if room < N::zero() {
+                return None;
+            }
+            continue;
+        }
+        let along = room / towards;
+        if towards < N::zero() {
+            enter = enter.max(along);
+        } else {
+            leave = leave.min(along);
+        }
+        if enter > leave {
+            return None;
+        }
+    }
+    Some((start + delta * enter, start + delta * leave))
+}
+
+// Unit normal of the wall on the side `position` is on.
+fn wall_normal<N: Real>(start: Point2<N>, end: Point2<N>, position: Point2<N>) -> Vec2<N> {
+    let along = end - start;
+    let normal = Vec2::new(-along.y, along.x).normalize();
+    if normal.dot(position - start) < N::zero() {
+        -normal
+    } else {
+        normal
+    }
+}
@@ -0,0 +1,372 @@
+use std::cmp::Ordering;
+
+use serde::Deserialize;
+
+use math::{Point2, Real, Vec2};
+use spatial::SpatialGrid;
+use Actor;
+
+// Optimal Reciprocal Collision Avoidance, after van den Berg et al. and the
+// RVO2 library. Each agent takes half the responsibility for avoiding each
+// neighbour, which turns every neighbour into a half-plane of allowed
+// velocities, and picks the allowed velocity closest to the one it wants.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, bound(deserialize = "N: Real + Deserialize<'de>"))]
+pub struct OrcaParams<N = f32> {
+    // Seconds ahead agents make sure they can't collide. Longer is safer but
+    // makes agents shy away from each other sooner.
+    pub time_horizon: N,
+    // Only neighbours this close are considered, at most `max_neighbors` of
+    // them, nearest first. Keep it local: agents that react to the far side
+    // of a crowd all hold back for each other and can jam.
+    pub neighbor_distance: N,
+    pub max_neighbors: usize,
+}
+
+impl<N: Real> Default for OrcaParams<N> {
+    fn default() -> Self {
+        OrcaParams {
+            time_horizon: N::from_f64(2.0),
+            neighbor_distance: N::from_f64(80.0),
+            max_neighbors: 10,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OrcaAgent<N = f32> {
+    pub position: Point2<N>,
+    // The velocity the agent moved at last step.
+    pub velocity: Vec2<N>,
+    // The velocity it would move at if nobody were in the way.
+    pub preferred_velocity: Vec2<N>,
+    pub radius: N,
+    pub max_speed: N,
+    // Tells agents apart when nothing else does, as when two spawn on the
+    // same spot. Every agent needs a different one, its index will do.
+    pub id: usize,
+}
+
+// Boundary of a half-plane of allowed velocities, those on the left of
+// `direction` going through `point`.
+#[derive(Clone, Copy, Debug)]
+struct Line<N> {
+    point: Vec2<N>,
+    direction: Vec2<N>,
+}
+
+const EPSILON: f64 = 1e-5;
+
+// New velocities for all agents at once, each worked out from where the
+// others are now. Neighbours are looked up through a `SpatialGrid`.
+pub fn orca_velocities<N: Real>(
+    agents: &[OrcaAgent<N>],
+    params: &OrcaParams<N>,
+    delta_t: N,
+) -> Vec<Vec2<N>> {
+    let grid = SpatialGrid::from_points(
+        params.neighbor_distance,
+        agents.iter().map(|agent| agent.position),
+    );
+    agents
+        .iter()
+        .enumerate()
+        .map(|(index, agent)| {
+            let mut neighbors: Vec<&OrcaAgent<N>> = grid
+                .within(agent.position, params.neighbor_distance)
+                .into_iter()
+                .filter(|&other| other != index)
+                .map(|other| &agents[other])
+                .collect();
+            // Stable, so equally close neighbours stay in index order.
+            neighbors.sort_by(|a, b| {
+                let a = agent.position.distance_squared(a.position);
+                let b = agent.position.distance_squared(b.position);
+                a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+            });
+            neighbors.truncate(params.max_neighbors);
+            orca_velocity(agent, &neighbors, params, delta_t)
+        })
+        .collect()
+}
+
+// The velocity closest to `agent.preferred_velocity` that avoids all of
+// `neighbors` for `params.time_horizon` seconds, or if there's none, the one
+// that intrudes on them least.
+pub fn orca_velocity<N: Real>(
+    agent: &OrcaAgent<N>,
+    neighbors: &[&OrcaAgent<N>],
+    params: &OrcaParams<N>,
+    delta_t: N,
+) -> Vec2<N> {
+    let lines: Vec<Line<N>> = neighbors
+        .iter()
+        .map(|other| orca_line(agent, other, params.time_horizon, delta_t))
+        .collect();
+    let mut result = Vec2::zero();
+    let failed = linear_program_2(
+        &lines,
+        agent.max_speed,
+        agent.preferred_velocity,
+        false,
+        &mut result,
+    );
+    if failed < lines.len() {
+        linear_program_3(&lines, failed, agent.max_speed, &mut result);
+    }
+    result
+}
+
+// Heads for the actor's next waypoint at its speed, slowing down on the last
+// step so as not to overshoot. Standing still for a step of no time.
+pub fn preferred_velocity<N: Real>(actor: &Actor<N>, delta_t: N) -> Vec2<N> {
+    if delta_t <= N::zero() {
+        return Vec2::zero();
+    }
+    match actor.waypoints.first() {
+        Some(waypoint) => {
+            let to_waypoint = waypoint.position - actor.position;
+            let speed = actor.speed.min(to_waypoint.magnitude() / delta_t);
+            to_waypoint.with_magnitude(speed)
+        }
+        None => Vec2::zero(),
+    }
+}
+
+// Moves every actor towards its next waypoint like
+// `move_towards_next_waypoint`, but steering round each other. `velocities`
+// holds each actor's velocity from the step before and is updated, and all
+// actors are `radius` in size. A step of no time changes nothing.
+pub fn move_crowd<N: Real>(
+    actors: &mut [Actor<N>],
+    velocities: &mut [Vec2<N>],
+    radius: N,
+    params: &OrcaParams<N>,
+    delta_t: N,
+) {
+    assert_eq!(actors.len(), velocities.len(), "need a velocity for every actor");
+    if delta_t <= N::zero() {
+        return;
+    }
+    let agents: Vec<OrcaAgent<N>> = actors
+        .iter()
+        .zip(velocities.iter())
+        .enumerate()
+        .map(|(id, (actor, &velocity))| OrcaAgent {
+            position: actor.position,
+            velocity,
+            preferred_velocity: preferred_velocity(actor, delta_t),
+            radius,
+            max_speed: actor.speed,
+            id,
+        })
+        .collect();
+    let new_velocities = orca_velocities(&agents, params, delta_t);
+    for ((actor, velocity), new_velocity) in actors
+        .iter_mut()
+        .zip(velocities.iter_mut())
+        .zip(new_velocities)
+    {
+        *velocity = new_velocity;
+        actor.position += new_velocity * delta_t;
+    }
+}
+
+// The half-plane of velocities that keep `agent` clear of `other`, taking
+// half of the change needed to get out of their velocity obstacle.
+fn orca_line<N: Real>(
+    agent: &OrcaAgent<N>,
+    other: &OrcaAgent<N>,
+    time_horizon: N,
+    delta_t: N,
+) -> Line<N> {
+    let relative_position = other.position - agent.position;
+    let relative_velocity = agent.velocity - other.velocity;
+    let distance_squared = relative_position.magnitude_squared();
+    let combined_radius = agent.radius + other.radius;
+    let combined_radius_squared = combined_radius * combined_radius;
+
+    let (direction, u) = if distance_squared > combined_radius_squared {
+        // Not touching yet. The velocity obstacle is a cone truncated by a
+        // circle, project onto whichever part is nearest.
+        let w = relative_velocity - relative_position / time_horizon;
+        let w_length_squared = w.magnitude_squared();
+        let dot = w.dot(relative_position);
+        if dot < N::zero() && dot * dot > combined_radius_squared * w_length_squared {
+            // The circular cut-off.
+            let w_length = w_length_squared.sqrt();
+            let unit_w = w / w_length;
+            let direction = Vec2::new(unit_w.y, -unit_w.x);
+            (direction, unit_w * (combined_radius / time_horizon - w_length))
+        } else {
+            // One of the legs of the cone.
+            let leg = (distance_squared - combined_radius_squared).sqrt();
+            let (x, y) = (relative_position.x, relative_position.y);
+            let direction = if relative_position.perp_dot(w) > N::zero() {
+                Vec2::new(x * leg - y * combined_radius, x * combined_radius + y * leg)
+                    / distance_squared
+            } else {
+                -Vec2::new(x * leg + y * combined_radius, -x * combined_radius + y * leg)
+                    / distance_squared
+            };
+            let u = direction * relative_velocity.dot(direction) - relative_velocity;
+            (direction, u)
+        }
+    } else {
+        // Already overlapping, so get apart within this step.
+        let w = relative_velocity - relative_position / delta_t;
+        let w_length = w.magnitude();
+        let unit_w = if w_length > N::from_f64(EPSILON) {
+            w / w_length
+        } else {
+            apart(agent, other)
+        };
+        let direction = Vec2::new(unit_w.y, -unit_w.x);
+        (direction, unit_w * (combined_radius / delta_t - w_length))
+    };
+
+    Line {
+        point: agent.velocity + u * N::from_f64(0.5),
+        direction,
+    }
+}
+
+// Which way `agent` should leave `other` when their motion doesn't say, as
+// when they spawn on the same spot. Agents split along x in order of their ids,
+// so each pair goes opposite ways whichever of them is asking.
+fn apart<N: Real>(agent: &OrcaAgent<N>, other: &OrcaAgent<N>) -> Vec2<N> {
+    let away = agent.position - other.position;
+    if away.magnitude_squared() > N::from_f64(EPSILON) {
+        away.normalize()
+    } else if agent.id < other.id {
+        Vec2::new(-N::one(), N::zero())
+    } else {
+        Vec2::new(N::one(), N::zero())
+    }
+}
+
+// Best velocity on line `index` within `radius` of the origin that satisfies
+// the lines before it. False when there is none.
+fn linear_program_1<N: Real>(
+    lines: &[Line<N>],
+    index: usize,
+    radius: N,
+    optimal: Vec2<N>,
+    optimize_direction: bool,
+    result: &mut Vec2<N>,
+) -> bool {
+    let line = lines[index];
+    let dot = line.point.dot(line.direction);
+    let discriminant = dot * dot + radius * radius - line.point.magnitude_squared();
+    if discriminant < N::zero() {
+        // The speed circle misses the line altogether.
+        return false;
+    }
+    let root = discriminant.sqrt();
+    let mut left = -dot - root;
+    let mut right = -dot + root;
+
+    for other in &lines[..index] {
+        let denominator = line.direction.perp_dot(other.direction);
+        let numerator = other.direction.perp_dot(line.point - other.point);
+        if denominator.abs() <= N::from_f64(EPSILON) {
+            // Parallel, either all of this line is allowed by the other or
+            // none of it is.
+            if numerator < N::zero() {
+                return false;
+            }
+            continue;
+        }
+        let t = numerator / denominator;
+        if denominator >= N::zero() {
+            right = right.min(t);
+        } else {
+            left = left.max(t);
+        }
+        if left > right {
+            return false;
+        }
+    }
+
+    let t = if optimize_direction {
+        if optimal.dot(line.direction) > N::zero() {
+            right
+        } else {
+            left
+        }
+    } else {
+        line.direction
+            .dot(optimal - line.point)
+            .max(left)
+            .min(right)
+    };
+    *result = line.point + line.direction * t;
+    true
+}
+
+// Best velocity within `radius` of the origin satisfying every line, either
+// closest to `optimal` or furthest along it when `optimize_direction` is set.
+// Returns the index of the first line that can't be satisfied, or the number
+// of lines when all were.
+fn linear_program_2<N: Real>(
+    lines: &[Line<N>],
+    radius: N,
+    optimal: Vec2<N>,
+    optimize_direction: bool,
+    result: &mut Vec2<N>,
+) -> usize {
+    *result = if optimize_direction {
+        optimal * radius
+    } else {
+        optimal.limit(radius)
+    };
+    for (index, line) in lines.iter().enumerate() {
+        if line.direction.perp_dot(line.point - *result) > N::zero() {
+            let previous = *result;
+            if !linear_program_1(lines, index, radius, optimal, optimize_direction, result) {
+                *result = previous;
+                return index;
+            }
+        }
+    }
+    lines.len()
+}
+
+// When the lines leave no room at all, finds the velocity that breaks the
+// worst of them by the least.
+fn linear_program_3<N: Real>(lines: &[Line<N>], begin: usize, radius: N, result: &mut Vec2<N>) {
+    let mut distance = N::zero();
+    for index in begin..lines.len() {
+        let line = lines[index];
+        if line.direction.perp_dot(line.point - *result) <= distance {
+            continue;
+        }
+        let mut projected = Vec::with_capacity(index);
+        for other in &lines[..index] {
+            let determinant = line.direction.perp_dot(other.direction);
+            let point = if determinant.abs() <= N::from_f64(EPSILON) {
+                if line.direction.dot(other.direction) > N::zero() {
+                    // Same way round, so this line already covers it.
+                    continue;
+                }
+                (line.point + other.point) * N::from_f64(0.5)
+            } else {
+                let along = other.direction.perp_dot(line.point - other.point) / determinant;
+                line.point + line.direction * along
+            };
+            projected.push(Line {
+                point,
+                direction: (other.direction - line.direction).normalize(),
+            });
+        }
+
+        let previous = *result;
+        let outward = Vec2::new(-line.direction.y, line.direction.x);
+        if linear_program_2(&projected, radius, outward, true, result) < projected.len() {
+            // Can only fail through rounding, the result was already as good
+            // as it gets.
+            *result = previous;
+        }
+        distance = line.direction.perp_dot(line.point - *result);
+    }
+}
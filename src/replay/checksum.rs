@@ -0,0 +1,145 @@
+use boids::{Flock, Vehicle};
+use ecosystem::Ecosystem;
+use math::Real;
+use simulation::{SimActor, Simulation};
+use {Actor, Waypoint};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+// FNV-1a over the exact bit patterns of the state, so any drift at all,
+// however small, changes the checksum.
+pub struct StateHasher {
+    hash: u64,
+}
+
+pub trait StateHash {
+    fn hash_state(&self, hasher: &mut StateHasher);
+
+    fn checksum(&self) -> u64 {
+        let mut hasher = StateHasher::new();
+        self.hash_state(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl StateHasher {
+    pub fn new() -> StateHasher {
+        StateHasher {
+            hash: FNV_OFFSET_BASIS,
+        }
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        for byte in 0..8 {
+            self.hash ^= (value >> (byte * 8)) & 0xff;
+            self.hash = self.hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    pub fn write_f64(&mut self, value: f64) {
+        self.write_u64(value.to_bits());
+    }
+
+    pub fn write_f32(&mut self, value: f32) {
+        self.write_u64(u64::from(value.to_bits()));
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.hash
+    }
+}
+
+impl Default for StateHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StateHash for f32 {
+    fn hash_state(&self, hasher: &mut StateHasher) {
+        hasher.write_f32(*self);
+    }
+}
+
+impl StateHash for f64 {
+    fn hash_state(&self, hasher: &mut StateHasher) {
+        hasher.write_f64(*self);
+    }
+}
+
+impl<N: Real + StateHash> StateHash for Waypoint<N> {
+    fn hash_state(&self, hasher: &mut StateHasher) {
+        self.position.x.hash_state(hasher);
+        self.position.y.hash_state(hasher);
+    }
+}
+
+impl<N: Real + StateHash> StateHash for Actor<N> {
+    fn hash_state(&self, hasher: &mut StateHasher) {
+        self.position.x.hash_state(hasher);
+        self.position.y.hash_state(hasher);
+        self.speed.hash_state(hasher);
+        hasher.write_u64(self.waypoints.len() as u64);
+        for waypoint in &self.waypoints {
+            waypoint.hash_state(hasher);
+        }
+    }
+}
+
+impl<N: Real + StateHash> StateHash for Vehicle<N> {
+    fn hash_state(&self, hasher: &mut StateHasher) {
+        self.location.x.hash_state(hasher);
+        self.location.y.hash_state(hasher);
+        self.wander_angle.hash_state(hasher);
+        self.velocity.x.hash_state(hasher);
+        self.velocity.y.hash_state(hasher);
+        self.acceleration.x.hash_state(hasher);
+        self.acceleration.y.hash_state(hasher);
+        self.max_force.hash_state(hasher);
+        self.max_speed.hash_state(hasher);
+        hasher.write_u64(self.limit_mode as u64);
+    }
+}
+
+impl<N: Real + StateHash> StateHash for Flock<N> {
+    fn hash_state(&self, hasher: &mut StateHasher) {
+        hasher.write_u64(self.vehicles.len() as u64);
+        for vehicle in &self.vehicles {
+            vehicle.borrow().hash_state(hasher);
+        }
+    }
+}
+
+impl<N: Real + StateHash> StateHash for Ecosystem<N> {
+    fn hash_state(&self, hasher: &mut StateHasher) {
+        hasher.write_u64(self.flocks.len() as u64);
+        for flock in &self.flocks {
+            flock.hash_state(hasher);
+        }
+    }
+}
+
+impl StateHash for SimActor {
+    fn hash_state(&self, hasher: &mut StateHasher) {
+        self.actor.hash_state(hasher);
+        hasher.write_u64(self.route_index as u64);
+        // ORCA works from the last velocity, so it's part of the state.
+        self.velocity.x.hash_state(hasher);
+        self.velocity.y.hash_state(hasher);
+    }
+}
+
+impl StateHash for Simulation {
+    fn hash_state(&self, hasher: &mut StateHasher) {
+        hasher.write_u64(self.tick);
+        hasher.write_f64(self.time);
+        for sim_actor in &self.actors {
+            sim_actor.hash_state(hasher);
+        }
+        if let Some(ref flock) = self.flock {
+            flock.hash_state(hasher);
+        }
+        self.rng.hash_state(hasher);
+    }
+}
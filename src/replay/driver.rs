@@ -0,0 +1,198 @@
+use ggez::event::{EventHandler, Keycode, Mod, MouseButton, MouseState};
+use ggez::{Context, GameError, GameResult};
+
+use serde_json::Value;
+
+use super::{InputEvent, InputLog, Recorder, Replayer};
+
+// A handler whose `update` is a pure function of its seed, its config and its
+// input, so it can be recorded and played back.
+pub trait Replayable: EventHandler {
+    fn seed(&self) -> u64;
+    fn checksum(&self) -> u64;
+
+    // Settings the handler was started with that a replay has to share.
+    fn config(&self) -> Value {
+        Value::Null
+    }
+}
+
+// Passes live input through to `handler` while logging it.
+pub struct Recording<H> {
+    pub handler: H,
+    recorder: Recorder,
+}
+
+// Drives `handler` from a log, ignoring live input apart from Escape, and
+// fails the event loop as soon as a tick's checksum differs from the log.
+pub struct Replaying<H> {
+    pub handler: H,
+    replayer: Replayer,
+}
+
+impl<H: Replayable> Recording<H> {
+    pub fn new(handler: H) -> Recording<H> {
+        let recorder = Recorder::new(handler.seed(), handler.config());
+        Recording { handler, recorder }
+    }
+
+    pub fn log(&self) -> &InputLog {
+        self.recorder.log()
+    }
+}
+
+impl<H: Replayable> Replaying<H> {
+    // Fails if `handler` wasn't started the way the log was recorded.
+    pub fn new(handler: H, log: InputLog) -> GameResult<Replaying<H>> {
+        log.check_start(handler.seed(), &handler.config())
+            .map_err(|mismatch| GameError::UnknownError(mismatch.to_string()))?;
+        Ok(Replaying {
+            handler,
+            replayer: Replayer::new(log),
+        })
+    }
+
+    // Ticks played back so far.
+    pub fn tick(&self) -> usize {
+        self.replayer.tick()
+    }
+
+    // Once the log runs out updates do nothing, leaving the last state up
+    // until the window is closed.
+    pub fn is_finished(&self) -> bool {
+        self.replayer.is_finished()
+    }
+}
+
+impl<H: Replayable> EventHandler for Recording<H> {
+    fn update(&mut self, ctx: &mut Context) -> GameResult<()> {
+        self.handler.update(ctx)?;
+        self.recorder.end_tick(self.handler.checksum());
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
+        self.handler.draw(ctx)
+    }
+
+    fn mouse_button_down_event(&mut self, ctx: &mut Context, button: MouseButton, x: i32, y: i32) {
+        self.recorder.record(InputEvent::MouseButtonDown {
+            button: button as u8,
+            x,
+            y,
+        });
+        self.handler.mouse_button_down_event(ctx, button, x, y);
+    }
+
+    fn mouse_button_up_event(&mut self, ctx: &mut Context, button: MouseButton, x: i32, y: i32) {
+        self.recorder.record(InputEvent::MouseButtonUp {
+            button: button as u8,
+            x,
+            y,
+        });
+        self.handler.mouse_button_up_event(ctx, button, x, y);
+    }
+
+    fn mouse_motion_event(
+        &mut self,
+        ctx: &mut Context,
+        state: MouseState,
+        x: i32,
+        y: i32,
+        xrel: i32,
+        yrel: i32,
+    ) {
+        self.recorder.record(InputEvent::MouseMotion {
+            x,
+            y,
+            xrel,
+            yrel,
+            buttons: state.to_sdl_state(),
+        });
+        self.handler.mouse_motion_event(ctx, state, x, y, xrel, yrel);
+    }
+
+    fn key_down_event(&mut self, ctx: &mut Context, keycode: Keycode, keymod: Mod, repeat: bool) {
+        self.recorder.record(InputEvent::KeyDown {
+            keycode: keycode as i32,
+            keymod: keymod.bits(),
+            repeat,
+        });
+        self.handler.key_down_event(ctx, keycode, keymod, repeat);
+    }
+
+    fn key_up_event(&mut self, ctx: &mut Context, keycode: Keycode, keymod: Mod, repeat: bool) {
+        self.recorder.record(InputEvent::KeyUp {
+            keycode: keycode as i32,
+            keymod: keymod.bits(),
+            repeat,
+        });
+        self.handler.key_up_event(ctx, keycode, keymod, repeat);
+    }
+
+    fn quit_event(&mut self, ctx: &mut Context) -> bool {
+        self.handler.quit_event(ctx)
+    }
+}
+
+impl<H: Replayable> EventHandler for Replaying<H> {
+    fn update(&mut self, ctx: &mut Context) -> GameResult<()> {
+        if self.replayer.is_finished() {
+            return Ok(());
+        }
+
+        for input in self.replayer.inputs().to_vec() {
+            dispatch(&mut self.handler, ctx, input);
+        }
+        self.handler.update(ctx)?;
+        self.replayer
+            .end_tick(self.handler.checksum())
+            .map_err(|divergence| GameError::UnknownError(divergence.to_string()))
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
+        self.handler.draw(ctx)
+    }
+
+    fn key_down_event(&mut self, ctx: &mut Context, keycode: Keycode, _keymod: Mod, _repeat: bool) {
+        if keycode == Keycode::Escape {
+            ctx.quit().expect("Should never fail");
+        }
+    }
+
+    fn quit_event(&mut self, ctx: &mut Context) -> bool {
+        self.handler.quit_event(ctx)
+    }
+}
+
+fn dispatch<H: EventHandler>(handler: &mut H, ctx: &mut Context, input: InputEvent) {
+    match input {
+        InputEvent::MouseMotion {
+            x,
+            y,
+            xrel,
+            yrel,
+            buttons,
+        } => handler.mouse_motion_event(ctx, MouseState::from_sdl_state(buttons), x, y, xrel, yrel),
+        InputEvent::MouseButtonDown { button, x, y } => {
+            handler.mouse_button_down_event(ctx, MouseButton::from_ll(button), x, y)
+        }
+        InputEvent::MouseButtonUp { button, x, y } => {
+            handler.mouse_button_up_event(ctx, MouseButton::from_ll(button), x, y)
+        }
+        InputEvent::KeyDown {
+            keycode,
+            keymod,
+            repeat,
+        } => if let Some(keycode) = Keycode::from_i32(keycode) {
+            handler.key_down_event(ctx, keycode, Mod::from_bits_truncate(keymod), repeat)
+        },
+        InputEvent::KeyUp {
+            keycode,
+            keymod,
+            repeat,
+        } => if let Some(keycode) = Keycode::from_i32(keycode) {
+            handler.key_up_event(ctx, keycode, Mod::from_bits_truncate(keymod), repeat)
+        },
+    }
+}
@@ -0,0 +1,233 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use serde_json::{self, Value};
+
+use error::{LoadError, LoadResult};
+
+mod checksum;
+#[cfg(feature = "render")]
+mod driver;
+
+pub use self::checksum::{StateHash, StateHasher};
+#[cfg(feature = "render")]
+pub use self::driver::{Recording, Replayable, Replaying};
+
+// Input as delivered to an `EventHandler`, reduced to plain numbers so logs
+// can be written and read without a window. Buttons, keycodes and modifiers
+// keep their SDL values.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InputEvent {
+    MouseMotion {
+        x: i32,
+        y: i32,
+        xrel: i32,
+        yrel: i32,
+        buttons: u32,
+    },
+    MouseButtonDown { button: u8, x: i32, y: i32 },
+    MouseButtonUp { button: u8, x: i32, y: i32 },
+    KeyDown { keycode: i32, keymod: u16, repeat: bool },
+    KeyUp { keycode: i32, keymod: u16, repeat: bool },
+}
+
+// Everything that arrived before a tick's update, and the state checksum
+// once the update had run.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct TickRecord {
+    pub inputs: Vec<InputEvent>,
+    pub checksum: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct InputLog {
+    pub seed: u64,
+    // Whatever else the run was started with that changes how it plays out,
+    // such as the boids' boundary mode. Null when there's nothing.
+    #[serde(default)]
+    pub config: Value,
+    pub ticks: Vec<TickRecord>,
+}
+
+pub struct Recorder {
+    log: InputLog,
+    pending: Vec<InputEvent>,
+}
+
+pub struct Replayer {
+    log: InputLog,
+    tick: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Divergence {
+    pub tick: usize,
+    pub expected: u64,
+    pub actual: u64,
+}
+
+// A log that can't be played back from where a replay starts, since it was
+// recorded from a different seed or configuration.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Mismatch {
+    Seed { expected: u64, actual: u64 },
+    Config { expected: Value, actual: Value },
+}
+
+impl InputLog {
+    pub fn new(seed: u64, config: Value) -> InputLog {
+        InputLog {
+            seed,
+            config,
+            ticks: Vec::new(),
+        }
+    }
+
+    // Checks a replay is starting from the same seed and configuration as
+    // the recording did, so it has a chance of matching.
+    pub fn check_start(&self, seed: u64, config: &Value) -> Result<(), Mismatch> {
+        if seed != self.seed {
+            return Err(Mismatch::Seed {
+                expected: self.seed,
+                actual: seed,
+            });
+        }
+        if *config != self.config {
+            return Err(Mismatch::Config {
+                expected: self.config.clone(),
+                actual: config.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn from_json<R: Read>(reader: R) -> LoadResult<InputLog> {
+        serde_json::from_reader(reader)
+            .map_err(|e| LoadError::invalid(format!("Invalid input log: {}", e)))
+    }
+
+    pub fn to_json<W: Write>(&self, writer: W) -> io::Result<()> {
+        serde_json::to_writer(writer, self)?;
+        Ok(())
+    }
+}
+
+impl Recorder {
+    pub fn new(seed: u64, config: Value) -> Recorder {
+        Recorder {
+            log: InputLog::new(seed, config),
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, input: InputEvent) {
+        self.pending.push(input);
+    }
+
+    // Closes the current tick, attaching everything recorded since the last
+    // call to it.
+    pub fn end_tick(&mut self, checksum: u64) {
+        self.log.ticks.push(TickRecord {
+            inputs: self.pending.drain(..).collect(),
+            checksum,
+        });
+    }
+
+    pub fn log(&self) -> &InputLog {
+        &self.log
+    }
+
+    pub fn into_log(self) -> InputLog {
+        self.log
+    }
+}
+
+impl Replayer {
+    pub fn new(log: InputLog) -> Replayer {
+        Replayer { log, tick: 0 }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.log.seed
+    }
+
+    pub fn tick(&self) -> usize {
+        self.tick
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.tick >= self.log.ticks.len()
+    }
+
+    // Inputs to feed before running the current tick.
+    pub fn inputs(&self) -> &[InputEvent] {
+        self.log
+            .ticks
+            .get(self.tick)
+            .map_or(&[], |record| &record.inputs)
+    }
+
+    // Moves on to the next tick, checking the state reached against the
+    // recording.
+    pub fn end_tick(&mut self, checksum: u64) -> Result<(), Divergence> {
+        let tick = self.tick;
+        let expected = match self.log.ticks.get(tick) {
+            Some(record) => record.checksum,
+            None => return Ok(()),
+        };
+        self.tick += 1;
+        if checksum == expected {
+            Ok(())
+        } else {
+            Err(Divergence {
+                tick,
+                expected,
+                actual: checksum,
+            })
+        }
+    }
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Replay diverged at tick {}: expected checksum {:016x}, got {:016x}",
+            self.tick, self.expected, self.actual
+        )
+    }
+}
+
+impl Error for Divergence {
+    fn description(&self) -> &str {
+        "replay diverged from the recording"
+    }
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Mismatch::Seed { expected, actual } => write!(
+                f,
+                "Input log was recorded with seed {}, the replay starts from seed {}",
+                expected, actual
+            ),
+            Mismatch::Config {
+                ref expected,
+                ref actual,
+            } => write!(
+                f,
+                "Input log was recorded with config {}, the replay starts from {}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+impl Error for Mismatch {
+    fn description(&self) -> &str {
+        "replay started differently from the recording"
+    }
+}
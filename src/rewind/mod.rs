@@ -0,0 +1,163 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+use boids::{Flock, Vehicle};
+use ecosystem::Ecosystem;
+use math::Real;
+use rng::SimRng;
+use simulation::Simulation;
+use Actor;
+
+// State that can be captured and later put back exactly as it was.
+pub trait Snapshot {
+    type State: Clone;
+
+    fn snapshot(&self) -> Self::State;
+    fn restore(&mut self, state: &Self::State);
+}
+
+// Bounded history of snapshots with a cursor for scrubbing. Once full the
+// oldest snapshot is dropped, and pushing while scrubbed back discards
+// everything ahead of the cursor.
+pub struct History<T> {
+    snapshots: VecDeque<T>,
+    capacity: usize,
+    cursor: usize,
+}
+
+impl<T> History<T> {
+    pub fn new(capacity: usize) -> History<T> {
+        let capacity = capacity.max(1);
+        History {
+            snapshots: VecDeque::with_capacity(capacity),
+            capacity,
+            cursor: 0,
+        }
+    }
+
+    pub fn push(&mut self, snapshot: T) {
+        if !self.snapshots.is_empty() {
+            self.snapshots.truncate(self.cursor + 1);
+        }
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(snapshot);
+        self.cursor = self.snapshots.len() - 1;
+    }
+
+    pub fn current(&self) -> Option<&T> {
+        self.snapshots.get(self.cursor)
+    }
+
+    // Moves the cursor one snapshot back, or returns None at the oldest one.
+    pub fn step_back(&mut self) -> Option<&T> {
+        if self.cursor == 0 {
+            return None;
+        }
+        self.cursor -= 1;
+        self.snapshots.get(self.cursor)
+    }
+
+    // Moves the cursor one snapshot forward, or returns None at the latest one.
+    pub fn step_forward(&mut self) -> Option<&T> {
+        if self.cursor + 1 >= self.snapshots.len() {
+            return None;
+        }
+        self.cursor += 1;
+        self.snapshots.get(self.cursor)
+    }
+
+    pub fn is_at_latest(&self) -> bool {
+        self.cursor + 1 >= self.snapshots.len()
+    }
+
+    // How many snapshots the cursor is behind the latest one.
+    pub fn steps_behind(&self) -> usize {
+        self.snapshots.len().saturating_sub(self.cursor + 1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+        self.cursor = 0;
+    }
+}
+
+impl Snapshot for SimRng {
+    type State = SimRng;
+
+    fn snapshot(&self) -> SimRng {
+        *self
+    }
+
+    fn restore(&mut self, state: &SimRng) {
+        *self = *state;
+    }
+}
+
+impl<N: Real> Snapshot for Actor<N> {
+    type State = Actor<N>;
+
+    fn snapshot(&self) -> Actor<N> {
+        self.clone()
+    }
+
+    fn restore(&mut self, state: &Actor<N>) {
+        *self = state.clone();
+    }
+}
+
+// Only the vehicles change from tick to tick, the parameters are left alone.
+impl<N: Real> Snapshot for Flock<N> {
+    type State = Vec<Vehicle<N>>;
+
+    fn snapshot(&self) -> Vec<Vehicle<N>> {
+        self.vehicles
+            .iter()
+            .map(|vehicle| vehicle.borrow().clone())
+            .collect()
+    }
+
+    fn restore(&mut self, state: &Vec<Vehicle<N>>) {
+        self.vehicles = state.iter().cloned().map(RefCell::new).collect();
+    }
+}
+
+// One list of vehicles per species, in the same order as the species.
+impl<N: Real> Snapshot for Ecosystem<N> {
+    type State = Vec<Vec<Vehicle<N>>>;
+
+    fn snapshot(&self) -> Vec<Vec<Vehicle<N>>> {
+        self.flocks.iter().map(Snapshot::snapshot).collect()
+    }
+
+    fn restore(&mut self, state: &Vec<Vec<Vehicle<N>>>) {
+        for (flock, vehicles) in self.flocks.iter_mut().zip(state) {
+            flock.restore(vehicles);
+        }
+    }
+}
+
+impl Snapshot for Simulation {
+    type State = Simulation;
+
+    fn snapshot(&self) -> Simulation {
+        self.clone()
+    }
+
+    fn restore(&mut self, state: &Simulation) {
+        *self = state.clone();
+    }
+}
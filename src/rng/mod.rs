@@ -0,0 +1,81 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use replay::{StateHash, StateHasher};
+
+const MULTIPLIER: u64 = 6_364_136_223_846_793_005;
+const DEFAULT_STREAM: u64 = 1_442_695_040_888_963_407;
+
+// PCG32 (XSH RR). Small, fast and fully specified here, so a seed reproduces
+// the same run on every platform and build.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct SimRng {
+    seed: u64,
+    state: u64,
+    increment: u64,
+}
+
+impl SimRng {
+    pub fn new(seed: u64) -> SimRng {
+        SimRng::seeded(seed, DEFAULT_STREAM | 1)
+    }
+
+    // Picks one of 2^63 independent sequences for the seed, numbered as in
+    // the reference implementation's `pcg32_srandom_r(seed, stream)`.
+    pub fn with_stream(seed: u64, stream: u64) -> SimRng {
+        SimRng::seeded(seed, (stream << 1) | 1)
+    }
+
+    fn seeded(seed: u64, increment: u64) -> SimRng {
+        let mut rng = SimRng {
+            seed,
+            state: 0,
+            increment,
+        };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+
+    // Seeds from the clock, for runs that don't ask for a particular seed.
+    // Print `seed()` so the run can be reproduced later.
+    pub fn from_time() -> SimRng {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        SimRng::new(now.as_secs() ^ (u64::from(now.subsec_nanos()) << 32))
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old.wrapping_mul(MULTIPLIER).wrapping_add(self.increment);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        let rotation = (old >> 59) as u32;
+        xorshifted.rotate_right(rotation)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        (u64::from(self.next_u32()) << 32) | u64::from(self.next_u32())
+    }
+
+    // Uniform in [0, 1).
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    // Uniform in [low, high).
+    pub fn gen_range(&mut self, low: f64, high: f64) -> f64 {
+        low + (high - low) * self.next_f64()
+    }
+}
+
+impl StateHash for SimRng {
+    fn hash_state(&self, hasher: &mut StateHasher) {
+        hasher.write_u64(self.state);
+        hasher.write_u64(self.increment);
+    }
+}
@@ -0,0 +1,222 @@
+use std::io::Read;
+
+use math::{Point2, Vec2};
+
+use serde_json;
+
+use boids::{Flock, FlockParams};
+use error::{LoadError, LoadResult};
+use orca::{orca_velocities, preferred_velocity, OrcaAgent, OrcaParams};
+use rng::SimRng;
+use {actor_at_waypoint, move_towards_next_waypoint, Actor, Waypoint};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Scenario {
+    #[serde(default = "default_ticks")]
+    pub ticks: u64,
+    #[serde(default = "default_delta_t")]
+    pub delta_t: f64,
+    // Fixed seed for reproducible runs, a fresh one is picked when missing.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub actors: Vec<ActorSpec>,
+    #[serde(default)]
+    pub boids: Option<BoidSpec>,
+    // When set, actors steer round each other with ORCA instead of walking
+    // straight through.
+    #[serde(default)]
+    pub orca: Option<OrcaParams>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ActorSpec {
+    #[serde(default)]
+    pub name: String,
+    pub position: [f32; 2],
+    pub speed: f32,
+    #[serde(default)]
+    pub waypoints: Vec<[f32; 2]>,
+    // Looping actors cycle through their waypoints like the waypoint example,
+    // the others drop each waypoint once reached like mouse-control.
+    #[serde(default = "default_looping")]
+    pub looping: bool,
+    // Only used for avoiding other actors.
+    #[serde(default = "default_radius")]
+    pub radius: f32,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BoidSpec {
+    pub count: u32,
+    #[serde(default)]
+    pub params: FlockParams,
+}
+
+#[derive(Clone)]
+pub struct SimActor {
+    pub name: String,
+    pub actor: Actor,
+    pub looping: bool,
+    pub velocity: Vec2<f32>,
+    pub radius: f32,
+    // Index into the scenario's waypoint list of the waypoint being headed to.
+    pub route_index: usize,
+    route_length: usize,
+}
+
+#[derive(Clone)]
+pub struct Simulation {
+    pub tick: u64,
+    pub time: f64,
+    pub delta_t: f64,
+    pub actors: Vec<SimActor>,
+    pub flock: Option<Flock>,
+    pub orca: Option<OrcaParams>,
+    // Every random decision in the simulation is drawn from here, so the same
+    // seed and scenario always produce the same run.
+    pub rng: SimRng,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SimEvent {
+    WaypointReached { actor: usize, waypoint: usize },
+    RouteFinished { actor: usize },
+}
+
+impl Scenario {
+    pub fn from_json<R: Read>(reader: R) -> LoadResult<Scenario> {
+        serde_json::from_reader(reader)
+            .map_err(|e| LoadError::invalid(format!("Invalid scenario: {}", e)))
+    }
+}
+
+impl Simulation {
+    pub fn new(scenario: &Scenario, seed: u64) -> Simulation {
+        let mut rng = SimRng::new(seed);
+        let actors = scenario
+            .actors
+            .iter()
+            .map(|spec| SimActor {
+                name: spec.name.clone(),
+                actor: Actor {
+                    position: Point2::new(spec.position[0], spec.position[1]),
+                    speed: spec.speed,
+                    waypoints: spec
+                        .waypoints
+                        .iter()
+                        .map(|waypoint| Waypoint::new(waypoint[0], waypoint[1]))
+                        .collect(),
+                },
+                looping: spec.looping,
+                velocity: Vec2::zero(),
+                radius: spec.radius,
+                route_index: 0,
+                route_length: spec.waypoints.len(),
+            })
+            .collect();
+
+        let flock = scenario
+            .boids
+            .as_ref()
+            .map(|boids| Flock::new_random(boids.count, boids.params.clone(), &mut rng));
+
+        Simulation {
+            tick: 0,
+            time: 0.0,
+            delta_t: scenario.delta_t,
+            actors,
+            flock,
+            orca: scenario.orca.clone(),
+            rng,
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    // Advances the simulation by one fixed `delta_t` step.
+    pub fn step(&mut self) -> Vec<SimEvent> {
+        let mut events = Vec::new();
+        // A step of no time changes nothing, and there's no velocity to
+        // measure over it.
+        if self.delta_t <= 0.0 {
+            return events;
+        }
+        let delta_t = self.delta_t as f32;
+
+        for (index, sim_actor) in self.actors.iter_mut().enumerate() {
+            // Advance before moving so an actor standing on its next waypoint
+            // never has to normalize a zero-length vector.
+            if actor_at_waypoint(&sim_actor.actor) {
+                events.push(SimEvent::WaypointReached {
+                    actor: index,
+                    waypoint: sim_actor.route_index,
+                });
+                let reached = sim_actor.actor.waypoints.remove(0);
+                if sim_actor.looping {
+                    sim_actor.actor.waypoints.push(reached);
+                    sim_actor.route_index = (sim_actor.route_index + 1) % sim_actor.route_length;
+                } else {
+                    sim_actor.route_index += 1;
+                    if sim_actor.actor.waypoints.is_empty() {
+                        events.push(SimEvent::RouteFinished { actor: index });
+                    }
+                }
+            }
+        }
+
+        match self.orca {
+            Some(ref params) => {
+                let agents: Vec<OrcaAgent> = self
+                    .actors
+                    .iter()
+                    .enumerate()
+                    .map(|(id, sim_actor)| OrcaAgent {
+                        position: sim_actor.actor.position,
+                        velocity: sim_actor.velocity,
+                        preferred_velocity: preferred_velocity(&sim_actor.actor, delta_t),
+                        radius: sim_actor.radius,
+                        max_speed: sim_actor.actor.speed,
+                        id,
+                    })
+                    .collect();
+                let velocities = orca_velocities(&agents, params, delta_t);
+                for (sim_actor, velocity) in self.actors.iter_mut().zip(velocities) {
+                    sim_actor.velocity = velocity;
+                    sim_actor.actor.position += velocity * delta_t;
+                }
+            }
+            None => for sim_actor in &mut self.actors {
+                let before = sim_actor.actor.position;
+                move_towards_next_waypoint(&mut sim_actor.actor, &delta_t);
+                sim_actor.velocity = (sim_actor.actor.position - before) / delta_t;
+            },
+        }
+
+        if let Some(ref mut flock) = self.flock {
+            flock.update(&mut self.rng);
+        }
+
+        self.tick += 1;
+        self.time += self.delta_t;
+        events
+    }
+}
+
+fn default_ticks() -> u64 {
+    600
+}
+
+fn default_delta_t() -> f64 {
+    1.0 / 60.0
+}
+
+fn default_looping() -> bool {
+    true
+}
+
+fn default_radius() -> f32 {
+    10.0
+}
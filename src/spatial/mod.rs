@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+use math::{Point2, Real};
+
+// Buckets points into square cells so the ones near a position can be found
+// without checking every point. Cells should be about as big as the radius
+// usually queried.
+#[derive(Clone, Debug)]
+pub struct SpatialGrid<N = f32> {
+    cell_size: N,
+    cells: HashMap<(i64, i64), Cell<N>>,
+}
+
+// Index and position of each point in a cell.
+type Cell<N> = Vec<(usize, Point2<N>)>;
+
+impl<N: Real> SpatialGrid<N> {
+    pub fn new(cell_size: N) -> SpatialGrid<N> {
+        assert!(cell_size > N::zero(), "cell size must be positive");
+        SpatialGrid {
+            cell_size,
+            cells: HashMap::new(),
+        }
+    }
+
+    // Each point is known by its index in `points`.
+    pub fn from_points<I>(cell_size: N, points: I) -> SpatialGrid<N>
+    where
+        I: IntoIterator<Item = Point2<N>>,
+    {
+        let mut grid = SpatialGrid::new(cell_size);
+        for (index, point) in points.into_iter().enumerate() {
+            grid.insert(index, point);
+        }
+        grid
+    }
+
+    pub fn insert(&mut self, index: usize, position: Point2<N>) {
+        let cell = self.cell(position);
+        self.cells
+            .entry(cell)
+            .or_insert_with(Vec::new)
+            .push((index, position));
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    // Indices of the points no further than `radius` from `center`. Cells are
+    // visited row by row and points in the order they were inserted, so the
+    // order is the same from run to run.
+    pub fn within(&self, center: Point2<N>, radius: N) -> Vec<usize> {
+        let (min_x, min_y) = self.cell(Point2::new(center.x - radius, center.y - radius));
+        let (max_x, max_y) = self.cell(Point2::new(center.x + radius, center.y + radius));
+        let radius_squared = radius * radius;
+        let mut found = Vec::new();
+        for y in min_y..max_y + 1 {
+            for x in min_x..max_x + 1 {
+                if let Some(points) = self.cells.get(&(x, y)) {
+                    for &(index, position) in points {
+                        if center.distance_squared(position) <= radius_squared {
+                            found.push(index);
+                        }
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    fn cell(&self, position: Point2<N>) -> (i64, i64) {
+        let x = (position.x / self.cell_size).floor().as_f64() as i64;
+        let y = (position.y / self.cell_size).floor().as_f64() as i64;
+        (x, y)
+    }
+}
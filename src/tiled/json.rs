@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+use std::io::Read;
+
+use error::{LoadError, LoadResult};
+
+use math::{Point2, Vec2};
+
+use serde_json::{self, Map, Value};
+
+use super::{check_layer_width, check_tile_size, decode_base64_gids, default_columns, MapObject,
+            ObjectLayer, ObjectShape, Properties, TileLayer, TiledMap, Tileset};
+
+pub fn parse<R: Read>(reader: R) -> LoadResult<TiledMap> {
+    let root: Value = serde_json::from_reader(reader)
+        .map_err(|e| LoadError::invalid(format!("Invalid Tiled JSON document: {}", e)))?;
+    let root = as_object(&root, "map")?;
+
+    let mut tilesets = Vec::new();
+    for tileset in array_field(root, "tilesets")? {
+        tilesets.push(parse_tileset(as_object(tileset, "tileset")?)?);
+    }
+
+    let mut tile_layers = Vec::new();
+    let mut object_layers = Vec::new();
+    parse_layers(
+        array_field(root, "layers")?,
+        &mut tile_layers,
+        &mut object_layers,
+    )?;
+
+    Ok(TiledMap {
+        width: u32_field(root, "width")?,
+        height: u32_field(root, "height")?,
+        tile_width: u32_field(root, "tilewidth")?,
+        tile_height: u32_field(root, "tileheight")?,
+        tilesets,
+        tile_layers,
+        object_layers,
+        properties: parse_properties(root),
+    })
+}
+
+fn parse_layers(
+    layers: &[Value],
+    tile_layers: &mut Vec<TileLayer>,
+    object_layers: &mut Vec<ObjectLayer>,
+) -> LoadResult<()> {
+    for layer in layers {
+        let layer = as_object(layer, "layer")?;
+        match layer.get("type").and_then(Value::as_str) {
+            Some("tilelayer") => tile_layers.push(parse_tile_layer(layer)?),
+            Some("objectgroup") => object_layers.push(parse_object_layer(layer)?),
+            Some("group") => parse_layers(array_field(layer, "layers")?, tile_layers, object_layers)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn as_object<'a>(value: &'a Value, what: &str) -> LoadResult<&'a Map<String, Value>> {
+    value
+        .as_object()
+        .ok_or_else(|| LoadError::invalid(format!("Expected {} to be a JSON object", what)))
+}
+
+fn array_field<'a>(object: &'a Map<String, Value>, key: &str) -> LoadResult<&'a [Value]> {
+    match object.get(key) {
+        Some(&Value::Array(ref values)) => Ok(values),
+        None => Ok(&[]),
+        Some(_) => Err(LoadError::invalid(format!("Expected \"{}\" to be an array", key))),
+    }
+}
+
+fn u32_field(object: &Map<String, Value>, key: &str) -> LoadResult<u32> {
+    object
+        .get(key)
+        .and_then(Value::as_u64)
+        .map(|value| value as u32)
+        .ok_or_else(|| LoadError::invalid(format!("Missing or invalid \"{}\"", key)))
+}
+
+fn u32_field_or(object: &Map<String, Value>, key: &str, default: u32) -> u32 {
+    object
+        .get(key)
+        .and_then(Value::as_u64)
+        .map_or(default, |value| value as u32)
+}
+
+fn f32_field_or(object: &Map<String, Value>, key: &str, default: f32) -> f32 {
+    object
+        .get(key)
+        .and_then(Value::as_f64)
+        .map_or(default, |value| value as f32)
+}
+
+fn str_field(object: &Map<String, Value>, key: &str) -> String {
+    object
+        .get(key)
+        .and_then(Value::as_str)
+        .unwrap_or("")
+        .to_string()
+}
+
+fn property_value(value: &Value) -> String {
+    match *value {
+        Value::String(ref string) => string.clone(),
+        ref other => other.to_string(),
+    }
+}
+
+// Tiled 1.2 writes properties as a list of {name, type, value} objects,
+// older versions write a plain name -> value object.
+fn parse_properties(object: &Map<String, Value>) -> Properties {
+    let mut properties = Properties::new();
+    match object.get("properties") {
+        Some(&Value::Array(ref list)) => for property in list {
+            if let (Some(name), Some(value)) = (
+                property.get("name").and_then(Value::as_str),
+                property.get("value"),
+            ) {
+                properties.insert(name.to_string(), property_value(value));
+            }
+        },
+        Some(&Value::Object(ref map)) => for (name, value) in map {
+            properties.insert(name.clone(), property_value(value));
+        },
+        _ => {}
+    }
+    properties
+}
+
+fn parse_tileset(object: &Map<String, Value>) -> LoadResult<Tileset> {
+    if object.contains_key("source") {
+        return Err(LoadError::invalid(
+            "External tilesets are not supported, embed the tileset in the map",
+        ));
+    }
+
+    let mut tile_properties = HashMap::new();
+    if let Some(&Value::Object(ref legacy)) = object.get("tileproperties") {
+        for (id, properties) in legacy {
+            if let (Ok(id), Some(properties)) = (id.parse(), properties.as_object()) {
+                let properties = properties
+                    .iter()
+                    .map(|(name, value)| (name.clone(), property_value(value)))
+                    .collect();
+                tile_properties.insert(id, properties);
+            }
+        }
+    }
+    for tile in array_field(object, "tiles")? {
+        let tile = as_object(tile, "tile")?;
+        let properties = parse_properties(tile);
+        if !properties.is_empty() {
+            tile_properties.insert(u32_field(tile, "id")?, properties);
+        }
+    }
+
+    let tile_width = u32_field(object, "tilewidth")?;
+    let tile_height = u32_field(object, "tileheight")?;
+    check_tile_size(tile_width, tile_height)?;
+    let spacing = u32_field_or(object, "spacing", 0);
+    let margin = u32_field_or(object, "margin", 0);
+    let image_width = u32_field(object, "imagewidth")?;
+    let columns = default_columns(image_width, tile_width, margin, spacing);
+
+    Ok(Tileset {
+        first_gid: u32_field(object, "firstgid")?,
+        name: str_field(object, "name"),
+        tile_width,
+        tile_height,
+        spacing,
+        margin,
+        tile_count: u32_field_or(object, "tilecount", 0),
+        columns: u32_field_or(object, "columns", columns),
+        image: str_field(object, "image"),
+        image_width,
+        image_height: u32_field(object, "imageheight")?,
+        tile_properties,
+    })
+}
+
+fn parse_tile_layer(object: &Map<String, Value>) -> LoadResult<TileLayer> {
+    if object.contains_key("compression") {
+        return Err(LoadError::invalid(
+            "Compressed tile data is not supported, save the map as CSV or uncompressed Base64",
+        ));
+    }
+
+    let data = match object.get("data") {
+        Some(&Value::Array(ref gids)) => gids.iter()
+            .map(|gid| {
+                gid.as_u64()
+                    .map(|gid| gid as u32)
+                    .ok_or_else(|| LoadError::invalid(format!("Invalid gid in tile data: {}", gid)))
+            })
+            .collect::<LoadResult<Vec<u32>>>()?,
+        Some(&Value::String(ref encoded)) => decode_base64_gids(encoded)?,
+        _ => return Err(LoadError::invalid("Tile layer has no data")),
+    };
+
+    let name = str_field(object, "name");
+    let width = u32_field(object, "width")?;
+    check_layer_width(&name, width)?;
+
+    Ok(TileLayer {
+        name,
+        width,
+        height: u32_field(object, "height")?,
+        visible: object.get("visible").and_then(Value::as_bool).unwrap_or(true),
+        opacity: f32_field_or(object, "opacity", 1.0),
+        data,
+        properties: parse_properties(object),
+    })
+}
+
+fn parse_object_layer(object: &Map<String, Value>) -> LoadResult<ObjectLayer> {
+    let mut objects = Vec::new();
+    for map_object in array_field(object, "objects")? {
+        objects.push(parse_object(as_object(map_object, "object")?)?);
+    }
+
+    Ok(ObjectLayer {
+        name: str_field(object, "name"),
+        objects,
+        properties: parse_properties(object),
+    })
+}
+
+fn parse_object(object: &Map<String, Value>) -> LoadResult<MapObject> {
+    let width = f32_field_or(object, "width", 0.0);
+    let height = f32_field_or(object, "height", 0.0);
+    let is_set = |key: &str| object.get(key).and_then(Value::as_bool).unwrap_or(false);
+
+    let shape = if is_set("point") {
+        ObjectShape::Point
+    } else if is_set("ellipse") {
+        ObjectShape::Ellipse { width, height }
+    } else if object.contains_key("polyline") {
+        ObjectShape::Polyline(parse_points(array_field(object, "polyline")?)?)
+    } else if object.contains_key("polygon") {
+        ObjectShape::Polygon(parse_points(array_field(object, "polygon")?)?)
+    } else {
+        ObjectShape::Rectangle { width, height }
+    };
+
+    Ok(MapObject {
+        id: u32_field_or(object, "id", 0),
+        name: str_field(object, "name"),
+        kind: str_field(object, "type"),
+        position: Point2::new(f32_field_or(object, "x", 0.0), f32_field_or(object, "y", 0.0)),
+        shape,
+        properties: parse_properties(object),
+    })
+}
+
+fn parse_points(points: &[Value]) -> LoadResult<Vec<Vec2<f32>>> {
+    points
+        .iter()
+        .map(|point| {
+            let x = point.get("x").and_then(Value::as_f64);
+            let y = point.get("y").and_then(Value::as_f64);
+            match (x, y) {
+                (Some(x), Some(y)) => Ok(Vec2::new(x as f32, y as f32)),
+                _ => Err(LoadError::invalid(format!("Invalid point: {}", point))),
+            }
+        })
+        .collect()
+}
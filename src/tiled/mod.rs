@@ -0,0 +1,353 @@
+use std::collections::HashMap;
+use std::io::Read;
+
+#[cfg(feature = "render")]
+use ggez::{Context, GameResult};
+
+use math::{Point2, Vec2};
+
+use error::{LoadError, LoadResult};
+use {Actor, Waypoint};
+
+mod json;
+#[cfg(feature = "render")]
+mod render;
+mod tmx;
+
+#[cfg(feature = "render")]
+pub use self::render::MapRenderer;
+
+// Tiled stores flip flags in the top three bits of every gid.
+const GID_MASK: u32 = 0x1FFF_FFFF;
+
+pub type Properties = HashMap<String, String>;
+
+pub struct TiledMap {
+    pub width: u32,
+    pub height: u32,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub tilesets: Vec<Tileset>,
+    pub tile_layers: Vec<TileLayer>,
+    pub object_layers: Vec<ObjectLayer>,
+    pub properties: Properties,
+}
+
+pub struct Tileset {
+    pub first_gid: u32,
+    pub name: String,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub spacing: u32,
+    pub margin: u32,
+    pub tile_count: u32,
+    pub columns: u32,
+    pub image: String,
+    pub image_width: u32,
+    pub image_height: u32,
+    pub tile_properties: HashMap<u32, Properties>,
+}
+
+pub struct TileLayer {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub visible: bool,
+    pub opacity: f32,
+    pub data: Vec<u32>,
+    pub properties: Properties,
+}
+
+pub struct ObjectLayer {
+    pub name: String,
+    pub objects: Vec<MapObject>,
+    pub properties: Properties,
+}
+
+pub struct MapObject {
+    pub id: u32,
+    pub name: String,
+    pub kind: String,
+    pub position: Point2<f32>,
+    pub shape: ObjectShape,
+    pub properties: Properties,
+}
+
+pub enum ObjectShape {
+    Rectangle { width: f32, height: f32 },
+    Ellipse { width: f32, height: f32 },
+    Point,
+    Polyline(Vec<Vec2<f32>>),
+    Polygon(Vec<Vec2<f32>>),
+}
+
+pub struct Route {
+    pub name: String,
+    pub closed: bool,
+    pub waypoints: Vec<Waypoint>,
+}
+
+pub struct Spawn {
+    pub name: String,
+    pub kind: String,
+    pub position: Point2<f32>,
+    pub properties: Properties,
+}
+
+pub struct WalkabilityGrid {
+    pub width: u32,
+    pub height: u32,
+    pub cell_width: f32,
+    pub cell_height: f32,
+    blocked: Vec<bool>,
+}
+
+impl TiledMap {
+    pub fn from_tmx<R: Read>(reader: R) -> LoadResult<TiledMap> {
+        tmx::parse(reader)
+    }
+
+    pub fn from_json<R: Read>(reader: R) -> LoadResult<TiledMap> {
+        json::parse(reader)
+    }
+
+    pub fn tileset_for_gid(&self, gid: u32) -> Option<&Tileset> {
+        let gid = gid & GID_MASK;
+        if gid == 0 {
+            return None;
+        }
+        self.tilesets
+            .iter()
+            .filter(|tileset| tileset.first_gid <= gid)
+            .max_by_key(|tileset| tileset.first_gid)
+    }
+
+    pub fn tile_properties(&self, gid: u32) -> Option<&Properties> {
+        self.tileset_for_gid(gid).and_then(|tileset| {
+            let local_id = (gid & GID_MASK) - tileset.first_gid;
+            tileset.tile_properties.get(&local_id)
+        })
+    }
+
+    pub fn walkability_grid(&self) -> WalkabilityGrid {
+        let mut blocked = vec![false; (self.width * self.height) as usize];
+
+        for layer in self.tile_layers.iter().filter(|layer| layer.width > 0) {
+            let layer_blocked = property_is_true(&layer.properties, "blocked");
+            // Layers may be smaller or larger than the map, so cells are
+            // placed by the layer's own size and anything outside is dropped.
+            for (index, &gid) in layer.data.iter().enumerate() {
+                let column = index as u32 % layer.width;
+                let row = index as u32 / layer.width;
+                let outside = row >= layer.height || column >= self.width || row >= self.height;
+                if gid & GID_MASK == 0 || outside {
+                    continue;
+                }
+                let tile_blocked = self.tile_properties(gid)
+                    .map_or(false, |properties| property_is_true(properties, "blocked"));
+                if layer_blocked || tile_blocked {
+                    blocked[(row * self.width + column) as usize] = true;
+                }
+            }
+        }
+
+        WalkabilityGrid {
+            width: self.width,
+            height: self.height,
+            cell_width: self.tile_width as f32,
+            cell_height: self.tile_height as f32,
+            blocked,
+        }
+    }
+
+    pub fn routes(&self) -> Vec<Route> {
+        let mut routes = Vec::new();
+        for layer in &self.object_layers {
+            for object in &layer.objects {
+                let (points, closed) = match object.shape {
+                    ObjectShape::Polyline(ref points) => (points, false),
+                    ObjectShape::Polygon(ref points) => (points, true),
+                    _ => continue,
+                };
+                let waypoints = points
+                    .iter()
+                    .map(|&point| Waypoint {
+                        position: object.position + point,
+                    })
+                    .collect();
+                routes.push(Route {
+                    name: object.name.clone(),
+                    closed,
+                    waypoints,
+                });
+            }
+        }
+        routes
+    }
+
+    pub fn spawns(&self) -> Vec<Spawn> {
+        let mut spawns = Vec::new();
+        for layer in &self.object_layers {
+            for object in &layer.objects {
+                if let ObjectShape::Point = object.shape {
+                    spawns.push(Spawn {
+                        name: object.name.clone(),
+                        kind: object.kind.clone(),
+                        position: object.position,
+                        properties: object.properties.clone(),
+                    });
+                }
+            }
+        }
+        spawns
+    }
+
+    // Spawns may name a route with a "route" property and override the
+    // default speed with a "speed" property. Actors get the route's patrol
+    // waypoints so they can cycle through them indefinitely.
+    pub fn spawn_actors(&self, default_speed: f32) -> Vec<Actor> {
+        let routes = self.routes();
+        self.spawns()
+            .into_iter()
+            .map(|spawn| {
+                let speed = spawn
+                    .properties
+                    .get("speed")
+                    .and_then(|speed| speed.parse().ok())
+                    .unwrap_or(default_speed);
+                let waypoints = spawn
+                    .properties
+                    .get("route")
+                    .and_then(|name| routes.iter().find(|route| &route.name == name))
+                    .map_or_else(Vec::new, Route::patrol_waypoints);
+                Actor {
+                    position: spawn.position,
+                    speed,
+                    waypoints,
+                }
+            })
+            .collect()
+    }
+}
+
+impl Route {
+    // Closed routes loop back to their start, open ones are walked there and
+    // back again.
+    pub fn patrol_waypoints(&self) -> Vec<Waypoint> {
+        let mut waypoints = self.waypoints.clone();
+        if !self.closed && waypoints.len() > 2 {
+            let interior = &self.waypoints[1..self.waypoints.len() - 1];
+            waypoints.extend(interior.iter().rev().cloned());
+        }
+        waypoints
+    }
+}
+
+impl WalkabilityGrid {
+    pub fn is_blocked(&self, column: u32, row: u32) -> bool {
+        if column >= self.width || row >= self.height {
+            return true;
+        }
+        self.blocked[(row * self.width + column) as usize]
+    }
+
+    pub fn is_walkable(&self, column: u32, row: u32) -> bool {
+        !self.is_blocked(column, row)
+    }
+
+    pub fn cell_at(&self, position: Point2<f32>) -> Option<(u32, u32)> {
+        if position.x < 0.0 || position.y < 0.0 {
+            return None;
+        }
+        let column = (position.x / self.cell_width) as u32;
+        let row = (position.y / self.cell_height) as u32;
+        if column < self.width && row < self.height {
+            Some((column, row))
+        } else {
+            None
+        }
+    }
+
+    pub fn is_walkable_at(&self, position: Point2<f32>) -> bool {
+        self.cell_at(position)
+            .map_or(false, |(column, row)| self.is_walkable(column, row))
+    }
+
+    pub fn cell_center(&self, column: u32, row: u32) -> Point2<f32> {
+        Point2::new(
+            (column as f32 + 0.5) * self.cell_width,
+            (row as f32 + 0.5) * self.cell_height,
+        )
+    }
+}
+
+// Loads a `.tmx` or `.json` map through the ggez filesystem.
+#[cfg(feature = "render")]
+pub fn load_map(ctx: &mut Context, path: &str) -> GameResult<TiledMap> {
+    let file = ctx.filesystem.open(path)?;
+    if path.ends_with(".json") {
+        Ok(TiledMap::from_json(file)?)
+    } else {
+        Ok(TiledMap::from_tmx(file)?)
+    }
+}
+
+fn property_is_true(properties: &Properties, key: &str) -> bool {
+    properties.get(key).map_or(false, |value| value == "true")
+}
+
+fn check_tile_size(tile_width: u32, tile_height: u32) -> LoadResult<()> {
+    if tile_width == 0 || tile_height == 0 {
+        return Err(LoadError::invalid("Tileset tile width and height must be positive"));
+    }
+    Ok(())
+}
+
+fn check_layer_width(name: &str, width: u32) -> LoadResult<()> {
+    if width == 0 {
+        return Err(LoadError::invalid(format!("Tile layer \"{}\" has no width", name)));
+    }
+    Ok(())
+}
+
+// Tiles that fit across a tileset image when the file doesn't say, allowing
+// for the margin round the edge and the spacing between tiles.
+fn default_columns(image_width: u32, tile_width: u32, margin: u32, spacing: u32) -> u32 {
+    (image_width + spacing).saturating_sub(2 * margin) / (tile_width + spacing)
+}
+
+fn decode_base64_gids(encoded: &str) -> LoadResult<Vec<u32>> {
+    let mut bytes = Vec::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in encoded.bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            b' ' | b'\n' | b'\r' | b'\t' => continue,
+            _ => return Err(LoadError::invalid("Invalid character in base64 tile data")),
+        };
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    if bytes.len() % 4 != 0 {
+        return Err(LoadError::invalid("Base64 tile data is not a whole number of gids"));
+    }
+    Ok(bytes
+        .chunks(4)
+        .map(|gid| {
+            u32::from(gid[0]) | u32::from(gid[1]) << 8 | u32::from(gid[2]) << 16
+                | u32::from(gid[3]) << 24
+        })
+        .collect())
+}
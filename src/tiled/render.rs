@@ -0,0 +1,104 @@
+use ggez::graphics::{self, spritebatch::SpriteBatch, Color, DrawParam, FilterMode, Image, Point2,
+                    Rect};
+use ggez::{Context, GameResult};
+
+use super::{TiledMap, Tileset, GID_MASK};
+
+struct LayerBatch {
+    opacity: f32,
+    batch: SpriteBatch,
+}
+
+// Builds one sprite batch per tile layer and tileset up front, so drawing a
+// level costs a handful of draw calls no matter how many tiles it has.
+pub struct MapRenderer {
+    layers: Vec<LayerBatch>,
+}
+
+impl MapRenderer {
+    // `directory` is the ggez filesystem directory the map was loaded from,
+    // tileset image paths are resolved relative to it.
+    pub fn new(ctx: &mut Context, map: &TiledMap, directory: &str) -> GameResult<MapRenderer> {
+        let mut images = Vec::with_capacity(map.tilesets.len());
+        for tileset in &map.tilesets {
+            let mut image = Image::new(ctx, resource_path(directory, &tileset.image))?;
+            image.set_filter(FilterMode::Nearest);
+            images.push(image);
+        }
+
+        let mut layers = Vec::new();
+        for layer in map.tile_layers.iter().filter(|layer| layer.visible && layer.width > 0) {
+            let mut batches: Vec<Option<SpriteBatch>> = map.tilesets.iter().map(|_| None).collect();
+
+            for (index, &gid) in layer.data.iter().enumerate() {
+                let tileset_index = match tileset_index_for_gid(map, gid) {
+                    Some(tileset_index) => tileset_index,
+                    None => continue,
+                };
+                let tileset = &map.tilesets[tileset_index];
+                let column = index as u32 % layer.width;
+                let row = index as u32 / layer.width;
+
+                let batch = batches[tileset_index]
+                    .get_or_insert_with(|| SpriteBatch::new(images[tileset_index].clone()));
+                batch.add(DrawParam {
+                    src: source_rect(tileset, (gid & GID_MASK) - tileset.first_gid),
+                    dest: Point2::new(
+                        (column * map.tile_width) as f32,
+                        ((row + 1) * map.tile_height) as f32 - tileset.tile_height as f32,
+                    ),
+                    ..Default::default()
+                });
+            }
+
+            for batch in batches.into_iter().filter_map(|batch| batch) {
+                layers.push(LayerBatch {
+                    opacity: layer.opacity,
+                    batch,
+                });
+            }
+        }
+
+        Ok(MapRenderer { layers })
+    }
+
+    pub fn draw(&self, ctx: &mut Context) -> GameResult<()> {
+        for layer in &self.layers {
+            graphics::set_color(ctx, Color::new(1.0, 1.0, 1.0, layer.opacity))?;
+            graphics::draw(ctx, &layer.batch, Point2::new(0.0, 0.0), 0.0)?;
+        }
+        Ok(())
+    }
+}
+
+fn tileset_index_for_gid(map: &TiledMap, gid: u32) -> Option<usize> {
+    let tileset = map.tileset_for_gid(gid)?;
+    map.tilesets
+        .iter()
+        .position(|candidate| candidate.first_gid == tileset.first_gid)
+}
+
+fn source_rect(tileset: &Tileset, local_id: u32) -> Rect {
+    let columns = tileset.columns.max(1);
+    let x = tileset.margin + (local_id % columns) * (tileset.tile_width + tileset.spacing);
+    let y = tileset.margin + (local_id / columns) * (tileset.tile_height + tileset.spacing);
+    let image_width = tileset.image_width as f32;
+    let image_height = tileset.image_height as f32;
+
+    Rect::new(
+        x as f32 / image_width,
+        y as f32 / image_height,
+        tileset.tile_width as f32 / image_width,
+        tileset.tile_height as f32 / image_height,
+    )
+}
+
+fn resource_path(directory: &str, file: &str) -> String {
+    if file.starts_with('/') {
+        file.to_string()
+    } else if directory.ends_with('/') {
+        format!("{}{}", directory, file)
+    } else {
+        format!("{}/{}", directory, file)
+    }
+}
@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+use std::io::Read;
+
+use error::{LoadError, LoadResult};
+
+use math::{Point2, Vec2};
+
+use xml_tree::{read_tree, Element};
+
+use super::{check_layer_width, check_tile_size, decode_base64_gids, default_columns, MapObject,
+            ObjectLayer, ObjectShape, Properties, TileLayer, TiledMap, Tileset};
+
+pub fn parse<R: Read>(reader: R) -> LoadResult<TiledMap> {
+    let root = read_tree(reader)?;
+    if root.name != "map" {
+        return Err(LoadError::invalid("TMX document does not start with <map>"));
+    }
+
+    let mut tilesets = Vec::new();
+    for tileset in root.children_named("tileset") {
+        tilesets.push(parse_tileset(tileset)?);
+    }
+
+    let mut tile_layers = Vec::new();
+    let mut object_layers = Vec::new();
+    parse_layers(&root, &mut tile_layers, &mut object_layers)?;
+
+    Ok(TiledMap {
+        width: root.parse_attribute("width")?,
+        height: root.parse_attribute("height")?,
+        tile_width: root.parse_attribute("tilewidth")?,
+        tile_height: root.parse_attribute("tileheight")?,
+        tilesets,
+        tile_layers,
+        object_layers,
+        properties: parse_properties(&root),
+    })
+}
+
+fn parse_layers(
+    parent: &Element,
+    tile_layers: &mut Vec<TileLayer>,
+    object_layers: &mut Vec<ObjectLayer>,
+) -> LoadResult<()> {
+    for layer in &parent.children {
+        match layer.name.as_str() {
+            "layer" => tile_layers.push(parse_tile_layer(layer)?),
+            "objectgroup" => object_layers.push(parse_object_layer(layer)?),
+            "group" => parse_layers(layer, tile_layers, object_layers)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn parse_properties(element: &Element) -> Properties {
+    let mut properties = Properties::new();
+    if let Some(list) = element.child("properties") {
+        for property in list.children_named("property") {
+            if let Some(name) = property.attribute("name") {
+                let value = property
+                    .attribute("value")
+                    .map_or_else(|| property.text.trim().to_string(), |v| v.to_string());
+                properties.insert(name.to_string(), value);
+            }
+        }
+    }
+    properties
+}
+
+fn parse_tileset(element: &Element) -> LoadResult<Tileset> {
+    if element.attribute("source").is_some() {
+        return Err(LoadError::invalid(
+            "External tilesets (.tsx) are not supported, embed the tileset in the map",
+        ));
+    }
+
+    let image = element
+        .child("image")
+        .ok_or_else(|| LoadError::invalid("Only single-image tilesets are supported"))?;
+
+    let mut tile_properties = HashMap::new();
+    for tile in element.children_named("tile") {
+        let properties = parse_properties(tile);
+        if !properties.is_empty() {
+            tile_properties.insert(tile.parse_attribute("id")?, properties);
+        }
+    }
+
+    let tile_width = element.parse_attribute("tilewidth")?;
+    let tile_height = element.parse_attribute("tileheight")?;
+    check_tile_size(tile_width, tile_height)?;
+    let spacing = element.parse_attribute_or("spacing", 0)?;
+    let margin = element.parse_attribute_or("margin", 0)?;
+    let image_width = image.parse_attribute("width")?;
+    let columns = element.parse_attribute_or(
+        "columns",
+        default_columns(image_width, tile_width, margin, spacing),
+    )?;
+
+    Ok(Tileset {
+        first_gid: element.parse_attribute("firstgid")?,
+        name: element.attribute("name").unwrap_or("").to_string(),
+        tile_width,
+        tile_height,
+        spacing,
+        margin,
+        tile_count: element.parse_attribute_or("tilecount", 0)?,
+        columns,
+        image: image.parse_attribute("source")?,
+        image_width,
+        image_height: image.parse_attribute("height")?,
+        tile_properties,
+    })
+}
+
+fn parse_tile_layer(element: &Element) -> LoadResult<TileLayer> {
+    let data = element
+        .child("data")
+        .ok_or_else(|| LoadError::invalid("Tile layer has no <data>"))?;
+
+    if data.attribute("compression").is_some() {
+        return Err(LoadError::invalid(
+            "Compressed tile data is not supported, save the map as CSV or uncompressed Base64",
+        ));
+    }
+
+    let gids = match data.attribute("encoding") {
+        Some("csv") => data.text
+            .split(',')
+            .map(|gid| gid.trim())
+            .filter(|gid| !gid.is_empty())
+            .map(|gid| {
+                gid.parse()
+                    .map_err(|_| LoadError::invalid(format!("Invalid gid in CSV tile data: {}", gid)))
+            })
+            .collect::<LoadResult<Vec<u32>>>()?,
+        Some("base64") => decode_base64_gids(&data.text)?,
+        Some(encoding) => {
+            return Err(LoadError::invalid(format!(
+                "Unknown tile data encoding: {}",
+                encoding
+            )))
+        }
+        None => data.children_named("tile")
+            .map(|tile| tile.parse_attribute_or("gid", 0))
+            .collect::<LoadResult<Vec<u32>>>()?,
+    };
+
+    let name = element.attribute("name").unwrap_or("").to_string();
+    let width = element.parse_attribute("width")?;
+    check_layer_width(&name, width)?;
+
+    Ok(TileLayer {
+        name,
+        width,
+        height: element.parse_attribute("height")?,
+        visible: element.parse_attribute_or("visible", 1u8)? != 0,
+        opacity: element.parse_attribute_or("opacity", 1.0)?,
+        data: gids,
+        properties: parse_properties(element),
+    })
+}
+
+fn parse_object_layer(element: &Element) -> LoadResult<ObjectLayer> {
+    let mut objects = Vec::new();
+    for object in element.children_named("object") {
+        objects.push(parse_object(object)?);
+    }
+
+    Ok(ObjectLayer {
+        name: element.attribute("name").unwrap_or("").to_string(),
+        objects,
+        properties: parse_properties(element),
+    })
+}
+
+fn parse_object(element: &Element) -> LoadResult<MapObject> {
+    let width = element.parse_attribute_or("width", 0.0)?;
+    let height = element.parse_attribute_or("height", 0.0)?;
+
+    let shape = if element.child("point").is_some() {
+        ObjectShape::Point
+    } else if element.child("ellipse").is_some() {
+        ObjectShape::Ellipse { width, height }
+    } else if let Some(polyline) = element.child("polyline") {
+        ObjectShape::Polyline(parse_points(polyline)?)
+    } else if let Some(polygon) = element.child("polygon") {
+        ObjectShape::Polygon(parse_points(polygon)?)
+    } else {
+        ObjectShape::Rectangle { width, height }
+    };
+
+    Ok(MapObject {
+        id: element.parse_attribute_or("id", 0)?,
+        name: element.attribute("name").unwrap_or("").to_string(),
+        kind: element.attribute("type").unwrap_or("").to_string(),
+        position: Point2::new(element.parse_attribute("x")?, element.parse_attribute("y")?),
+        shape,
+        properties: parse_properties(element),
+    })
+}
+
+fn parse_points(element: &Element) -> LoadResult<Vec<Vec2<f32>>> {
+    let points: &str = element
+        .attribute("points")
+        .ok_or_else(|| LoadError::invalid(format!("<{}> is missing its points", element.name)))?;
+
+    points
+        .split_whitespace()
+        .map(|pair| {
+            let mut coordinates = pair.split(',').map(|c| c.parse::<f32>());
+            match (coordinates.next(), coordinates.next()) {
+                (Some(Ok(x)), Some(Ok(y))) => Ok(Vec2::new(x, y)),
+                _ => Err(LoadError::invalid(format!("Invalid point: {}", pair))),
+            }
+        })
+        .collect()
+}
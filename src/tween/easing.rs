@@ -0,0 +1,104 @@
+use std::f64::consts::PI;
+
+// Robert Penner's easing curves. Each maps progress from 0 to 1 onto 0 to 1,
+// though elastic and back overshoot on the way.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Easing {
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    ElasticIn,
+    ElasticOut,
+    ElasticInOut,
+    BounceIn,
+    BounceOut,
+    BounceInOut,
+    BackIn,
+    BackOut,
+    BackInOut,
+}
+
+// How far back the back curves pull before setting off, about 10%.
+const BACK_OVERSHOOT: f64 = 1.701_58;
+
+impl Easing {
+    // Progress outside 0 to 1 is clamped first.
+    pub fn apply(self, progress: f64) -> f64 {
+        let t = progress.max(0.0).min(1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => t * (2.0 - t),
+            Easing::QuadInOut => in_out(t, |t| t * t),
+            Easing::CubicIn => t * t * t,
+            Easing::CubicOut => out(t, |t| t * t * t),
+            Easing::CubicInOut => in_out(t, |t| t * t * t),
+            Easing::ElasticIn => elastic_in(t, 0.3),
+            Easing::ElasticOut => out(t, |t| elastic_in(t, 0.3)),
+            Easing::ElasticInOut => in_out(t, |t| elastic_in(t, 0.45)),
+            Easing::BounceIn => out(t, bounce_out),
+            Easing::BounceOut => bounce_out(t),
+            Easing::BounceInOut => in_out(t, |t| out(t, bounce_out)),
+            Easing::BackIn => back_in(t, BACK_OVERSHOOT),
+            Easing::BackOut => out(t, |t| back_in(t, BACK_OVERSHOOT)),
+            Easing::BackInOut => in_out(t, |t| back_in(t, BACK_OVERSHOOT * 1.525)),
+        }
+    }
+}
+
+impl Default for Easing {
+    fn default() -> Self {
+        Easing::Linear
+    }
+}
+
+// Plays an ease-in curve backwards, so it starts fast and settles.
+fn out<F: Fn(f64) -> f64>(t: f64, ease_in: F) -> f64 {
+    1.0 - ease_in(1.0 - t)
+}
+
+// The ease-in curve over the first half and its mirror over the second.
+fn in_out<F: Fn(f64) -> f64>(t: f64, ease_in: F) -> f64 {
+    if t < 0.5 {
+        ease_in(t * 2.0) / 2.0
+    } else {
+        1.0 - ease_in((1.0 - t) * 2.0) / 2.0
+    }
+}
+
+// A sine wave growing exponentially into the end. `period` is the length of
+// one wobble as a fraction of the whole curve.
+fn elastic_in(t: f64, period: f64) -> f64 {
+    if t <= 0.0 || t >= 1.0 {
+        return t;
+    }
+    let shift = period / 4.0;
+    -(2f64.powf(10.0 * (t - 1.0))) * ((t - 1.0 - shift) * 2.0 * PI / period).sin()
+}
+
+fn back_in(t: f64, overshoot: f64) -> f64 {
+    t * t * ((overshoot + 1.0) * t - overshoot)
+}
+
+// Four parabolic hops, each lower than the one before.
+fn bounce_out(t: f64) -> f64 {
+    const SCALE: f64 = 7.5625;
+    const SPAN: f64 = 2.75;
+    if t < 1.0 / SPAN {
+        SCALE * t * t
+    } else if t < 2.0 / SPAN {
+        let t = t - 1.5 / SPAN;
+        SCALE * t * t + 0.75
+    } else if t < 2.5 / SPAN {
+        let t = t - 2.25 / SPAN;
+        SCALE * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / SPAN;
+        SCALE * t * t + 0.984_375
+    }
+}
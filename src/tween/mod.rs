@@ -0,0 +1,321 @@
+use std::f64::INFINITY;
+
+use draw::Color;
+use game_timer::GameTimer;
+use math::{Point2, Real, Vec2};
+use rewind::Snapshot;
+
+mod easing;
+
+pub use self::easing::Easing;
+
+// Values that can be animated. `amount` runs from 0 at `self` to 1 at `to`
+// and goes past either end when an easing overshoots.
+pub trait Tweenable: Copy {
+    fn interpolate(self, to: Self, amount: f64) -> Self;
+}
+
+impl Tweenable for f32 {
+    fn interpolate(self, to: f32, amount: f64) -> f32 {
+        f64::from(self).interpolate(f64::from(to), amount) as f32
+    }
+}
+
+impl Tweenable for f64 {
+    fn interpolate(self, to: f64, amount: f64) -> f64 {
+        self + (to - self) * amount
+    }
+}
+
+impl<N: Real> Tweenable for Vec2<N> {
+    fn interpolate(self, to: Vec2<N>, amount: f64) -> Vec2<N> {
+        self.lerp(to, N::from_f64(amount))
+    }
+}
+
+impl<N: Real> Tweenable for Point2<N> {
+    fn interpolate(self, to: Point2<N>, amount: f64) -> Point2<N> {
+        self.lerp(to, N::from_f64(amount))
+    }
+}
+
+impl Tweenable for Color {
+    fn interpolate(self, to: Color, amount: f64) -> Color {
+        Color {
+            r: self.r.interpolate(to.r, amount),
+            g: self.g.interpolate(to.g, amount),
+            b: self.b.interpolate(to.b, amount),
+            a: self.a.interpolate(to.a, amount),
+        }
+    }
+}
+
+// `affine_transform` with an easing curve: maps `value` between `from_min`
+// and `from_max` onto `to_min` to `to_max`. With `Easing::Linear` it matches
+// `affine_transform` inside the range, outside it holds at the ends.
+pub fn ease_transform<T: Tweenable>(
+    easing: Easing,
+    value: f64,
+    from_min: f64,
+    from_max: f64,
+    to_min: T,
+    to_max: T,
+) -> T {
+    let progress = (value - from_min) / (from_max - from_min);
+    to_min.interpolate(to_max, easing.apply(progress))
+}
+
+// Anything that can be sampled over time. Tracks hold no playback state, so
+// the same track gives the same value for the same time however it was
+// reached, which keeps rewinding and replays exact.
+pub trait Track {
+    type Value;
+
+    // Seconds until the track comes to rest, infinite for endless repeats.
+    fn duration(&self) -> f64;
+    // Times before 0 sample the start, times past the end sample the end.
+    fn value_at(&self, time: f64) -> Self::Value;
+
+    fn repeat(self, mode: RepeatMode, times: Option<u32>) -> Repeat<Self>
+    where
+        Self: Sized,
+    {
+        Repeat {
+            track: self,
+            mode,
+            times,
+        }
+    }
+
+    fn looped(self) -> Repeat<Self>
+    where
+        Self: Sized,
+    {
+        self.repeat(RepeatMode::Loop, None)
+    }
+
+    fn yoyo(self) -> Repeat<Self>
+    where
+        Self: Sized,
+    {
+        self.repeat(RepeatMode::Yoyo, None)
+    }
+}
+
+// One value easing from `from` to `to` over `duration` seconds.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tween<T> {
+    pub from: T,
+    pub to: T,
+    pub duration: f64,
+    pub easing: Easing,
+}
+
+impl<T: Tweenable> Tween<T> {
+    pub fn new(from: T, to: T, duration: f64, easing: Easing) -> Tween<T> {
+        Tween {
+            from,
+            to,
+            duration,
+            easing,
+        }
+    }
+
+    // Stays at `value` for `duration` seconds, for pauses in a sequence.
+    pub fn hold(value: T, duration: f64) -> Tween<T> {
+        Tween::new(value, value, duration, Easing::Linear)
+    }
+}
+
+impl<T: Tweenable> Track for Tween<T> {
+    type Value = T;
+
+    fn duration(&self) -> f64 {
+        self.duration
+    }
+
+    fn value_at(&self, time: f64) -> T {
+        if self.duration <= 0.0 {
+            return if time < 0.0 { self.from } else { self.to };
+        }
+        let progress = self.easing.apply(time / self.duration);
+        self.from.interpolate(self.to, progress)
+    }
+}
+
+// Tracks played one after another. Always holds at least one step, so
+// there's a value to show before the first and after the last.
+#[derive(Clone, Debug)]
+pub struct Sequence<A> {
+    steps: Vec<A>,
+}
+
+impl<A: Track> Sequence<A> {
+    pub fn new(first: A) -> Sequence<A> {
+        Sequence { steps: vec![first] }
+    }
+
+    pub fn then(mut self, next: A) -> Sequence<A> {
+        self.steps.push(next);
+        self
+    }
+
+    pub fn steps(&self) -> &[A] {
+        &self.steps
+    }
+}
+
+impl<T: Tweenable> Sequence<Tween<T>> {
+    // Starts the next tween from wherever the last one ended.
+    pub fn then_to(self, to: T, duration: f64, easing: Easing) -> Sequence<Tween<T>> {
+        let from = self.steps[self.steps.len() - 1].to;
+        self.then(Tween::new(from, to, duration, easing))
+    }
+}
+
+impl<A: Track> Track for Sequence<A> {
+    type Value = A::Value;
+
+    fn duration(&self) -> f64 {
+        self.steps.iter().map(Track::duration).sum()
+    }
+
+    fn value_at(&self, time: f64) -> A::Value {
+        let mut start = 0.0;
+        let last = self.steps.len() - 1;
+        for step in &self.steps[..last] {
+            let end = start + step.duration();
+            if time < end {
+                return step.value_at(time - start);
+            }
+            start = end;
+        }
+        self.steps[last].value_at(time - start)
+    }
+}
+
+// Tuples of tracks play in parallel, their values coming out as a tuple of
+// the same shape, and the group lasts as long as its longest member.
+macro_rules! impl_parallel_track {
+    ($($track:ident : $index:tt),*) => {
+        impl<$($track: Track),*> Track for ($($track,)*) {
+            type Value = ($($track::Value,)*);
+
+            fn duration(&self) -> f64 {
+                let mut duration = 0.0f64;
+                $(duration = duration.max(self.$index.duration());)*
+                duration
+            }
+
+            fn value_at(&self, time: f64) -> Self::Value {
+                ($(self.$index.value_at(time),)*)
+            }
+        }
+    }
+}
+
+impl_parallel_track!(A: 0, B: 1);
+impl_parallel_track!(A: 0, B: 1, C: 2);
+impl_parallel_track!(A: 0, B: 1, C: 2, D: 3);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RepeatMode {
+    // Jumps back to the start each time round.
+    Loop,
+    // Plays forwards then backwards, each pass counting as one time round.
+    Yoyo,
+}
+
+// Plays `track` `times` times over, or forever when `times` is None.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Repeat<A> {
+    pub track: A,
+    pub mode: RepeatMode,
+    pub times: Option<u32>,
+}
+
+impl<A: Track> Track for Repeat<A> {
+    type Value = A::Value;
+
+    fn duration(&self) -> f64 {
+        match self.times {
+            Some(times) => self.track.duration() * f64::from(times),
+            None => INFINITY,
+        }
+    }
+
+    fn value_at(&self, time: f64) -> A::Value {
+        let length = self.track.duration();
+        if length <= 0.0 || time <= 0.0 {
+            return self.track.value_at(time);
+        }
+        let mut pass = (time / length).floor();
+        let mut offset = time - pass * length;
+        if let Some(times) = self.times {
+            // Finishing lands on the end of the last pass, not the start of
+            // the one after it.
+            if pass >= f64::from(times) {
+                pass = f64::from(times.max(1) - 1);
+                offset = length;
+            }
+        }
+        let backwards = self.mode == RepeatMode::Yoyo && pass % 2.0 == 1.0;
+        if backwards {
+            self.track.value_at(length - offset)
+        } else {
+            self.track.value_at(offset)
+        }
+    }
+}
+
+// Plays a track back, advanced by the frame time each update.
+#[derive(Clone, Debug)]
+pub struct Animator<A> {
+    pub track: A,
+    time: f64,
+}
+
+impl<A: Track> Animator<A> {
+    pub fn new(track: A) -> Animator<A> {
+        Animator { track, time: 0.0 }
+    }
+
+    pub fn advance(&mut self, delta_t: f64) {
+        self.time += delta_t;
+    }
+
+    // Advances by the time the timer measured for the last frame.
+    pub fn tick(&mut self, timer: &GameTimer) {
+        self.advance(timer.get_frame_time());
+    }
+
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    pub fn value(&self) -> A::Value {
+        self.track.value_at(self.time)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.time >= self.track.duration()
+    }
+
+    pub fn restart(&mut self) {
+        self.time = 0.0;
+    }
+}
+
+// The track never changes, so the playhead is all there is to save.
+impl<A> Snapshot for Animator<A> {
+    type State = f64;
+
+    fn snapshot(&self) -> f64 {
+        self.time
+    }
+
+    fn restore(&mut self, time: &f64) {
+        self.time = *time;
+    }
+}
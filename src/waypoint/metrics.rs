@@ -0,0 +1,119 @@
+use math::{Point2, Real};
+
+use super::{distance, path_length, Waypoint};
+use Actor;
+
+#[derive(Clone, Copy, Debug)]
+pub struct RouteProjection<N = f32> {
+    // Nearest point on the route.
+    pub point: Point2<N>,
+    // Index of the leg the point lies on, leg `i` runs from waypoint `i` to
+    // waypoint `i + 1`.
+    pub segment: usize,
+    // How far along that leg the point lies, from 0 to 1.
+    pub segment_fraction: N,
+    // Distance from the queried position to `point`.
+    pub distance: N,
+    // Route length from the first waypoint up to `point`.
+    pub distance_along: N,
+}
+
+pub fn leg_lengths<N: Real>(waypoints: &[Waypoint<N>]) -> Vec<N> {
+    waypoints
+        .windows(2)
+        .map(|leg| distance(leg[0].position, leg[1].position))
+        .collect()
+}
+
+pub fn route_length<N: Real>(waypoints: &[Waypoint<N>]) -> N {
+    path_length(waypoints, None, false)
+}
+
+// Projects `position` onto the polyline through `waypoints`. A single
+// waypoint projects onto itself, an empty route has no projection.
+pub fn closest_point<N: Real>(
+    waypoints: &[Waypoint<N>],
+    position: Point2<N>,
+) -> Option<RouteProjection<N>> {
+    if waypoints.len() == 1 {
+        let point = waypoints[0].position;
+        return Some(RouteProjection {
+            point,
+            segment: 0,
+            segment_fraction: N::zero(),
+            distance: distance(position, point),
+            distance_along: N::zero(),
+        });
+    }
+
+    let mut best: Option<RouteProjection<N>> = None;
+    let mut travelled = N::zero();
+    for (segment, leg) in waypoints.windows(2).enumerate() {
+        let start = leg[0].position;
+        let direction = leg[1].position - start;
+        let length_squared = direction.dot(direction);
+        let fraction = if length_squared > N::zero() {
+            ((position - start).dot(direction) / length_squared)
+                .max(N::zero())
+                .min(N::one())
+        } else {
+            N::zero()
+        };
+        let point = start + direction * fraction;
+        let length = length_squared.sqrt();
+        let candidate = RouteProjection {
+            point,
+            segment,
+            segment_fraction: fraction,
+            distance: distance(position, point),
+            distance_along: travelled + length * fraction,
+        };
+        if best.map_or(true, |best| candidate.distance < best.distance) {
+            best = Some(candidate);
+        }
+        travelled += length;
+    }
+    best
+}
+
+// Fraction of the route already covered by someone at `position`, from 0 at
+// the first waypoint to 1 at the last.
+pub fn progress<N: Real>(waypoints: &[Waypoint<N>], position: Point2<N>) -> N {
+    let total = route_length(waypoints);
+    match closest_point(waypoints, position) {
+        Some(projection) if total > N::zero() => projection.distance_along / total,
+        Some(_) => N::one(),
+        None => N::zero(),
+    }
+}
+
+pub fn remaining_length<N: Real>(waypoints: &[Waypoint<N>], position: Point2<N>) -> N {
+    closest_point(waypoints, position).map_or(N::zero(), |projection| {
+        route_length(waypoints) - projection.distance_along
+    })
+}
+
+// Seconds until someone moving at `speed` from `position` finishes the route.
+pub fn eta<N: Real>(waypoints: &[Waypoint<N>], position: Point2<N>, speed: N) -> Option<N> {
+    time_for(remaining_length(waypoints, position), speed)
+}
+
+// An actor's waypoints are the part of its route it has yet to visit, so its
+// remaining distance runs from its position through all of them.
+pub fn actor_remaining_length<N: Real>(actor: &Actor<N>) -> N {
+    path_length(&actor.waypoints, Some(actor.position), false)
+}
+
+pub fn actor_eta<N: Real>(actor: &Actor<N>) -> Option<N> {
+    time_for(actor_remaining_length(actor), actor.speed)
+}
+
+fn time_for<N: Real>(length: N, speed: N) -> Option<N> {
+    if length <= N::zero() {
+        Some(N::zero())
+    } else if speed > N::zero() {
+        Some(length / speed)
+    } else {
+        None
+    }
+}
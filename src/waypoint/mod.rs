@@ -0,0 +1,48 @@
+use math::{Point2, Real};
+
+mod metrics;
+mod optimize;
+mod simplify;
+
+pub use self::metrics::{actor_eta, actor_remaining_length, closest_point, eta, leg_lengths,
+                        progress, remaining_length, route_length, RouteProjection};
+pub use self::optimize::{optimize_route, RouteOptions, RouteReport};
+pub use self::simplify::{max_deviation, resample, round_corners, simplify_rdp,
+                         simplify_visvalingam};
+
+#[derive(Clone)]
+pub struct Waypoint<N = f32> {
+    pub position: Point2<N>,
+}
+
+impl<N> Waypoint<N> {
+    pub fn new(x: N, y: N) -> Waypoint<N> {
+        Waypoint {
+            position: Point2::new(x, y),
+        }
+    }
+}
+
+// Length of the polyline through `waypoints`, starting from `origin` when one
+// is given and returning to the first waypoint when `closed` is set.
+pub fn path_length<N: Real>(
+    waypoints: &[Waypoint<N>],
+    origin: Option<Point2<N>>,
+    closed: bool,
+) -> N {
+    let mut length = N::zero();
+    if let (Some(origin), Some(first)) = (origin, waypoints.first()) {
+        length += distance(origin, first.position);
+    }
+    for leg in waypoints.windows(2) {
+        length += distance(leg[0].position, leg[1].position);
+    }
+    if closed && waypoints.len() > 1 {
+        length += distance(waypoints[waypoints.len() - 1].position, waypoints[0].position);
+    }
+    length
+}
+
+fn distance<N: Real>(a: Point2<N>, b: Point2<N>) -> N {
+    a.distance(b)
+}
@@ -0,0 +1,270 @@
+use std::cmp::Ordering;
+
+use math::Point2;
+
+use super::{distance, path_length, Waypoint};
+
+// Improvements smaller than this are treated as noise, which keeps the local
+// search from cycling on floating point error.
+const IMPROVEMENT_EPSILON: f64 = 1e-4;
+const MAX_OR_OPT_SEGMENT: usize = 3;
+const MAX_PASSES: usize = 100;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RouteOptions {
+    // Where the traveller currently is, the route is measured from here.
+    pub origin: Option<Point2<f32>>,
+    // Keep the first waypoint first.
+    pub fixed_start: bool,
+    // Keep the last waypoint last.
+    pub fixed_end: bool,
+    // The route loops back to its first waypoint.
+    pub closed: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RouteReport {
+    pub length_before: f32,
+    pub length_after: f32,
+}
+
+impl RouteReport {
+    pub fn saved(&self) -> f32 {
+        self.length_before - self.length_after
+    }
+}
+
+// Reorders `waypoints` to shorten the route, using a nearest-neighbour tour
+// improved with 2-opt and Or-opt moves until neither finds anything. Leaves
+// them alone when that comes out no shorter than the order they were in.
+pub fn optimize_route(waypoints: &mut Vec<Waypoint>, options: &RouteOptions) -> RouteReport {
+    let length_before = path_length(waypoints, options.origin, options.closed);
+    if waypoints.len() < 2 {
+        return RouteReport {
+            length_before,
+            length_after: length_before,
+        };
+    }
+
+    let points: Vec<Point2<f32>> = waypoints.iter().map(|waypoint| waypoint.position).collect();
+    let mut tour = Tour::nearest_neighbour(&points, options);
+    for _ in 0..MAX_PASSES {
+        let improved_2opt = tour.two_opt();
+        let improved_or_opt = tour.or_opt();
+        if !improved_2opt && !improved_or_opt {
+            break;
+        }
+    }
+
+    let reordered: Vec<Waypoint> = tour
+        .order
+        .iter()
+        .map(|&index| waypoints[index].clone())
+        .collect();
+    let length_after = path_length(&reordered, options.origin, options.closed);
+    if length_after >= length_before {
+        return RouteReport {
+            length_before,
+            length_after: length_before,
+        };
+    }
+    *waypoints = reordered;
+    RouteReport {
+        length_before,
+        length_after,
+    }
+}
+
+struct Tour<'a> {
+    points: &'a [Point2<f32>],
+    origin: Option<Point2<f32>>,
+    closed: bool,
+    order: Vec<usize>,
+    // Positions in `order` that local search may rearrange.
+    movable: (usize, usize),
+}
+
+impl<'a> Tour<'a> {
+    fn nearest_neighbour(points: &'a [Point2<f32>], options: &RouteOptions) -> Tour<'a> {
+        let count = points.len();
+        let mut visited = vec![false; count];
+        let mut order = Vec::with_capacity(count);
+
+        // Taken out before the start is picked, so a closed tour can't join
+        // the loop at the end and lose it.
+        let end = if options.fixed_end && count > 1 {
+            Some(count - 1)
+        } else {
+            None
+        };
+        if let Some(end) = end {
+            visited[end] = true;
+        }
+        // A closed tour looks the same from every waypoint, so pinning the
+        // first one only decides where the traveller joins the loop.
+        let start = if options.fixed_start {
+            Some(0)
+        } else if options.closed {
+            Some(options.origin.map_or(0, |origin| nearest(points, &visited, origin)))
+        } else {
+            None
+        };
+
+        let mut current = options.origin.unwrap_or(points[0]);
+        if let Some(start) = start {
+            visited[start] = true;
+            order.push(start);
+            current = points[start];
+        } else if options.origin.is_none() {
+            visited[0] = true;
+            order.push(0);
+        }
+
+        while order.len() + end.map_or(0, |_| 1) < count {
+            let next = nearest(points, &visited, current);
+            visited[next] = true;
+            order.push(next);
+            current = points[next];
+        }
+        if let Some(end) = end {
+            order.push(end);
+        }
+
+        let first_movable = if start.is_some() { 1 } else { 0 };
+        let last_movable = if end.is_some() { count - 1 } else { count };
+        Tour {
+            points,
+            origin: options.origin,
+            closed: options.closed,
+            order,
+            movable: (first_movable, last_movable),
+        }
+    }
+
+    fn point(&self, position: usize) -> Point2<f32> {
+        self.points[self.order[position]]
+    }
+
+    fn previous(&self, position: usize) -> Option<Point2<f32>> {
+        if position > 0 {
+            Some(self.point(position - 1))
+        } else {
+            self.origin
+        }
+    }
+
+    fn next(&self, position: usize) -> Option<Point2<f32>> {
+        if position + 1 < self.order.len() {
+            Some(self.point(position + 1))
+        } else if self.closed {
+            Some(self.point(0))
+        } else {
+            None
+        }
+    }
+
+    fn two_opt(&mut self) -> bool {
+        let (first, last) = self.movable;
+        let mut improved = false;
+        for i in first..last {
+            for j in i + 1..last {
+                let before = leg(self.previous(i), Some(self.point(i)))
+                    + leg(Some(self.point(j)), self.next(j));
+                let after = leg(self.previous(i), Some(self.point(j)))
+                    + leg(Some(self.point(i)), self.next(j));
+                if after + IMPROVEMENT_EPSILON < before {
+                    self.order[i..j + 1].reverse();
+                    improved = true;
+                }
+            }
+        }
+        improved
+    }
+
+    fn or_opt(&mut self) -> bool {
+        let mut improved = false;
+        for length in 1..MAX_OR_OPT_SEGMENT + 1 {
+            let mut i = self.movable.0;
+            while i + length <= self.movable.1 {
+                if self.relocate_segment(i, length) {
+                    improved = true;
+                } else {
+                    i += 1;
+                }
+            }
+        }
+        improved
+    }
+
+    // Tries every gap in the movable part of the tour for the segment
+    // starting at `start`, moving it to the best one if that shortens the tour.
+    fn relocate_segment(&mut self, start: usize, length: usize) -> bool {
+        let end = start + length - 1;
+        let head = Some(self.point(start));
+        let tail = Some(self.point(end));
+        let removal_gain = leg(self.previous(start), head) + leg(tail, self.next(end))
+            - leg(self.previous(start), self.next(end));
+
+        let (first, last) = self.movable;
+        let mut best: Option<(usize, bool, f64)> = None;
+        // Gap `gap` sits between positions `gap - 1` and `gap`.
+        for gap in first..last + 1 {
+            if gap >= start && gap <= end + 1 {
+                continue;
+            }
+            let before = self.previous(gap);
+            let after = if gap < self.order.len() {
+                Some(self.point(gap))
+            } else if self.closed {
+                Some(self.point(0))
+            } else {
+                None
+            };
+            let existing = leg(before, after);
+            let forward = leg(before, head) + leg(tail, after) - existing;
+            let reversed = leg(before, tail) + leg(head, after) - existing;
+            let (cost, reverse) = if reversed < forward {
+                (reversed, true)
+            } else {
+                (forward, false)
+            };
+            if cost + IMPROVEMENT_EPSILON < removal_gain
+                && best.map_or(true, |(_, _, best_cost)| cost < best_cost)
+            {
+                best = Some((gap, reverse, cost));
+            }
+        }
+
+        match best {
+            Some((gap, reverse, _)) => {
+                let mut segment: Vec<usize> = self.order.drain(start..end + 1).collect();
+                if reverse {
+                    segment.reverse();
+                }
+                let insert_at = if gap > end { gap - length } else { gap };
+                for (offset, index) in segment.into_iter().enumerate() {
+                    self.order.insert(insert_at + offset, index);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+fn leg(from: Option<Point2<f32>>, to: Option<Point2<f32>>) -> f64 {
+    match (from, to) {
+        (Some(from), Some(to)) => f64::from(distance(from, to)),
+        _ => 0.0,
+    }
+}
+
+fn nearest(points: &[Point2<f32>], visited: &[bool], from: Point2<f32>) -> usize {
+    // NaN distances count as ties rather than stopping the tour.
+    (0..points.len())
+        .filter(|&index| !visited[index])
+        .map(|index| (index, distance(from, points[index])))
+        .min_by(|&(_, a), &(_, b)| a.partial_cmp(&b).unwrap_or(Ordering::Equal))
+        .map(|(index, _)| index)
+        .expect("nearest called with every point visited")
+}
@@ -0,0 +1,258 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::f32::consts::PI;
+
+use math::{segment_distance, Point2, Vec2};
+
+use super::{closest_point, distance, Waypoint};
+
+// Ramer-Douglas-Peucker: keeps the endpoints and recursively the point
+// furthest from the chord while it is further than `tolerance`. Every dropped
+// waypoint stays within `tolerance` of the simplified route.
+pub fn simplify_rdp(waypoints: &[Waypoint], tolerance: f32) -> Vec<Waypoint> {
+    if waypoints.len() < 3 {
+        return waypoints.to_vec();
+    }
+
+    let mut keep = vec![false; waypoints.len()];
+    keep[0] = true;
+    keep[waypoints.len() - 1] = true;
+
+    let mut stack = vec![(0, waypoints.len() - 1)];
+    while let Some((first, last)) = stack.pop() {
+        let mut furthest = None;
+        let mut furthest_distance = tolerance;
+        for index in first + 1..last {
+            let d = segment_distance(
+                waypoints[index].position,
+                waypoints[first].position,
+                waypoints[last].position,
+            );
+            if d > furthest_distance {
+                furthest_distance = d;
+                furthest = Some(index);
+            }
+        }
+        if let Some(index) = furthest {
+            keep[index] = true;
+            stack.push((first, index));
+            stack.push((index, last));
+        }
+    }
+
+    waypoints
+        .iter()
+        .zip(keep)
+        .filter(|&(_, keep)| keep)
+        .map(|(waypoint, _)| waypoint.clone())
+        .collect()
+}
+
+// Visvalingam-Whyatt: repeatedly drops the waypoint spanning the smallest
+// triangle with its neighbours. A waypoint is only dropped while every
+// original waypoint between its neighbours stays within `tolerance` of the
+// shortcut, so the result carries the same guarantee as `simplify_rdp`.
+pub fn simplify_visvalingam(waypoints: &[Waypoint], tolerance: f32) -> Vec<Waypoint> {
+    let count = waypoints.len();
+    if count < 3 {
+        return waypoints.to_vec();
+    }
+
+    let position = |index: usize| waypoints[index].position;
+    let mut previous: Vec<usize> = (0..count).map(|index| index.saturating_sub(1)).collect();
+    let mut next: Vec<usize> = (0..count).map(|index| (index + 1).min(count - 1)).collect();
+    let mut removed = vec![false; count];
+    // Bumped whenever a waypoint's neighbours change, so stale heap entries
+    // can be recognised and skipped.
+    let mut generation = vec![0usize; count];
+
+    let mut heap = BinaryHeap::new();
+    for index in 1..count - 1 {
+        heap.push(Candidate {
+            area: triangle_area(position(index - 1), position(index), position(index + 1)),
+            index,
+            generation: 0,
+        });
+    }
+
+    while let Some(candidate) = heap.pop() {
+        let index = candidate.index;
+        if removed[index] || candidate.generation != generation[index] {
+            continue;
+        }
+
+        let before = previous[index];
+        let after = next[index];
+        let within_tolerance = (before + 1..after).all(|between| {
+            segment_distance(position(between), position(before), position(after)) <= tolerance
+        });
+        if !within_tolerance {
+            continue;
+        }
+
+        removed[index] = true;
+        next[before] = after;
+        previous[after] = before;
+        for &neighbour in &[before, after] {
+            if neighbour == 0 || neighbour == count - 1 {
+                continue;
+            }
+            generation[neighbour] += 1;
+            heap.push(Candidate {
+                area: triangle_area(
+                    position(previous[neighbour]),
+                    position(neighbour),
+                    position(next[neighbour]),
+                ),
+                index: neighbour,
+                generation: generation[neighbour],
+            });
+        }
+    }
+
+    waypoints
+        .iter()
+        .zip(removed)
+        .filter(|&(_, removed)| !removed)
+        .map(|(waypoint, _)| waypoint.clone())
+        .collect()
+}
+
+// Places waypoints every `spacing` units along the route, always keeping the
+// first and last waypoint.
+pub fn resample(waypoints: &[Waypoint], spacing: f32) -> Vec<Waypoint> {
+    if waypoints.len() < 2 || spacing <= 0.0 {
+        return waypoints.to_vec();
+    }
+
+    let mut resampled = vec![waypoints[0].clone()];
+    // Distance still to travel before the next waypoint is due.
+    let mut until_next = spacing;
+    for leg in waypoints.windows(2) {
+        let start = leg[0].position;
+        let length = distance(start, leg[1].position);
+        if length <= 0.0 {
+            continue;
+        }
+        let direction = (leg[1].position - start) / length;
+        let mut along = until_next;
+        while along < length {
+            resampled.push(Waypoint {
+                position: start + direction * along,
+            });
+            along += spacing;
+        }
+        until_next = along - length;
+    }
+
+    let last = &waypoints[waypoints.len() - 1];
+    let ends_on_last = resampled
+        .last()
+        .map_or(false, |waypoint| distance(waypoint.position, last.position) < spacing * 1e-3);
+    if ends_on_last {
+        resampled.pop();
+    }
+    resampled.push(last.clone());
+    resampled
+}
+
+// Replaces every interior corner with a circular arc of up to `radius`,
+// approximated by `arc_segments` straight pieces. The radius shrinks where
+// legs are too short to fit the full arc.
+pub fn round_corners(waypoints: &[Waypoint], radius: f32, arc_segments: usize) -> Vec<Waypoint> {
+    if waypoints.len() < 3 || radius <= 0.0 || arc_segments == 0 {
+        return waypoints.to_vec();
+    }
+
+    let mut rounded = vec![waypoints[0].clone()];
+    for corner in waypoints.windows(3) {
+        let (a, b, c) = (corner[0].position, corner[1].position, corner[2].position);
+        let incoming = distance(a, b);
+        let outgoing = distance(b, c);
+        if incoming <= 0.0 || outgoing <= 0.0 {
+            rounded.push(corner[1].clone());
+            continue;
+        }
+
+        let to_a = (a - b) / incoming;
+        let to_c = (c - b) / outgoing;
+        let angle = to_a.dot(to_c).max(-1.0).min(1.0).acos();
+        // Nearly straight or doubling straight back, there is no arc to fit.
+        if angle > PI - 1e-3 || angle < 1e-3 {
+            rounded.push(corner[1].clone());
+            continue;
+        }
+
+        let half_angle = angle / 2.0;
+        let tangent_length = (radius / half_angle.tan())
+            .min(incoming / 2.0)
+            .min(outgoing / 2.0);
+        let arc_radius = tangent_length * half_angle.tan();
+        let bisector = (to_a + to_c).normalize();
+        let center = b + bisector * (arc_radius / half_angle.sin());
+
+        let start = b + to_a * tangent_length - center;
+        let end = b + to_c * tangent_length - center;
+        let start_angle = start.y.atan2(start.x);
+        let mut sweep = end.y.atan2(end.x) - start_angle;
+        if sweep > PI {
+            sweep -= 2.0 * PI;
+        } else if sweep < -PI {
+            sweep += 2.0 * PI;
+        }
+
+        for step in 0..arc_segments + 1 {
+            let theta = start_angle + sweep * step as f32 / arc_segments as f32;
+            rounded.push(Waypoint {
+                position: center + Vec2::new(theta.cos(), theta.sin()) * arc_radius,
+            });
+        }
+    }
+    rounded.push(waypoints[waypoints.len() - 1].clone());
+    rounded
+}
+
+// Largest distance from any original waypoint to the `simplified` route,
+// handy for checking a simplification against its tolerance.
+pub fn max_deviation(original: &[Waypoint], simplified: &[Waypoint]) -> f32 {
+    original
+        .iter()
+        .filter_map(|waypoint| closest_point(simplified, waypoint.position))
+        .map(|projection| projection.distance)
+        .fold(0.0, f32::max)
+}
+
+struct Candidate {
+    area: f32,
+    index: usize,
+    generation: usize,
+}
+
+// Reversed so the max-heap pops the smallest area first.
+impl Ord for Candidate {
+    fn cmp(&self, other: &Candidate) -> Ordering {
+        other
+            .area
+            .partial_cmp(&self.area)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Candidate) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Candidate) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+fn triangle_area(a: Point2<f32>, b: Point2<f32>, c: Point2<f32>) -> f32 {
+    ((b.x - a.x) * (c.y - a.y) - (c.x - a.x) * (b.y - a.y)).abs() / 2.0
+}
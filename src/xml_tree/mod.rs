@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::str::FromStr;
+
+use error::{LoadError, LoadResult};
+
+use xml::reader::{EventReader, XmlEvent};
+
+// A minimal in-memory XML tree, enough for walking the small documents the
+// map and route importers read.
+pub struct Element {
+    pub name: String,
+    pub attributes: HashMap<String, String>,
+    pub children: Vec<Element>,
+    pub text: String,
+}
+
+impl Element {
+    pub fn children_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> + 'a {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(|value| value.as_str())
+    }
+
+    pub fn parse_attribute<T: FromStr>(&self, name: &str) -> LoadResult<T> {
+        let value = self.attribute(name).ok_or_else(|| {
+            LoadError::invalid(format!("<{}> is missing the \"{}\" attribute", self.name, name))
+        })?;
+        value.parse().map_err(|_| {
+            LoadError::invalid(format!(
+                "<{}> has an invalid \"{}\" attribute: {}",
+                self.name, name, value
+            ))
+        })
+    }
+
+    pub fn parse_attribute_or<T: FromStr>(&self, name: &str, default: T) -> LoadResult<T> {
+        if self.attribute(name).is_some() {
+            self.parse_attribute(name)
+        } else {
+            Ok(default)
+        }
+    }
+}
+
+pub fn read_tree<R: Read>(reader: R) -> LoadResult<Element> {
+    let mut stack: Vec<Element> = Vec::new();
+
+    for event in EventReader::new(reader) {
+        let event = event.map_err(|e| LoadError::invalid(format!("Invalid XML document: {}", e)))?;
+        match event {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => {
+                stack.push(Element {
+                    name: name.local_name,
+                    attributes: attributes
+                        .into_iter()
+                        .map(|attribute| (attribute.name.local_name, attribute.value))
+                        .collect(),
+                    children: Vec::new(),
+                    text: String::new(),
+                });
+            }
+            XmlEvent::Characters(text) | XmlEvent::CData(text) => {
+                if let Some(element) = stack.last_mut() {
+                    element.text.push_str(&text);
+                }
+            }
+            XmlEvent::EndElement { .. } => {
+                let element = stack.pop().expect("xml-rs reported an unbalanced end tag");
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(element),
+                    None => return Ok(element),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Err(LoadError::invalid("XML document ended unexpectedly"))
+}
@@ -0,0 +1,205 @@
+extern crate serde_json;
+extern crate skunkworks;
+
+use std::cell::RefCell;
+
+use skunkworks::boids::{Boundary, Flock, FlockParams, Vehicle};
+use skunkworks::rng::SimRng;
+use skunkworks::{Point2, Vec2};
+
+fn params(boundary: Boundary) -> FlockParams {
+    FlockParams {
+        width: 640.0,
+        height: 480.0,
+        boundary,
+        ..Default::default()
+    }
+}
+
+// Runs a flock for a while and returns the box every vehicle stayed inside.
+fn extent(boundary: Boundary) -> (Point2<f64>, Point2<f64>) {
+    let mut rng = SimRng::new(3);
+    let mut flock = Flock::new_random(60, params(boundary), &mut rng);
+    let mut min = Point2::new(0.0f64, 0.0);
+    let mut max = Point2::new(0.0f64, 0.0);
+    for _ in 0..1200 {
+        flock.update(&mut rng);
+        for vehicle in &flock.vehicles {
+            let location = vehicle.borrow().location;
+            min = Point2::new(min.x.min(location.x), min.y.min(location.y));
+            max = Point2::new(max.x.max(location.x), max.y.max(location.y));
+        }
+    }
+    (min, max)
+}
+
+fn vehicle(x: f64, y: f64, velocity: Vec2<f64>, params: &FlockParams) -> Vehicle {
+    Vehicle::new(velocity, Point2::new(x, y), params)
+}
+
+#[test]
+fn wrapping_sees_across_the_seam() {
+    let params = params(Boundary::Wrap);
+    let near_left = Point2::new(5.0, 100.0);
+    let near_right = Point2::new(635.0, 100.0);
+    // The torus is `constrain_distance` bigger than the area on every side.
+    assert_eq!(params.nearest_image(near_left, near_right), Point2::new(-45.0, 100.0));
+    assert_eq!(params.nearest_image(near_right, near_left), Point2::new(685.0, 100.0));
+    let middle = Point2::new(300.0, 100.0);
+    assert_eq!(params.nearest_image(near_left, middle), middle);
+
+    let bottom = Point2::new(320.0, 470.0);
+    assert_eq!(params.nearest_image(bottom, Point2::new(320.0, 10.0)), Point2::new(320.0, 530.0));
+
+    let bounded = FlockParams {
+        boundary: Boundary::Bounce,
+        ..params.clone()
+    };
+    assert_eq!(bounded.nearest_image(near_left, near_right), near_right);
+}
+
+#[test]
+fn neighbours_across_the_seam_count() {
+    for &(boundary, seen) in [(Boundary::Wrap, true), (Boundary::Bounce, false)].iter() {
+        let params = params(boundary);
+        let mut boid = vehicle(5.0, 100.0, Vec2::new(1.0, 0.0), &params);
+        let others = vec![RefCell::new(vehicle(635.0, 100.0, Vec2::new(0.0, 1.0), &params))];
+        assert_eq!(boid.align(&others, &params) != Vec2::zero(), seen);
+        assert_eq!(boid.separate(&others, &params) != Vec2::zero(), seen);
+    }
+}
+
+#[test]
+fn wrapping_keeps_vehicles_near_the_area() {
+    let (min, max) = extent(Boundary::Wrap);
+    assert!(min.x >= -20.0 && min.y >= -20.0, "{:?}", min);
+    assert!(max.x <= 660.0 && max.y <= 500.0, "{:?}", max);
+}
+
+#[test]
+fn bouncing_reflects_off_the_edges() {
+    let params = params(Boundary::Bounce);
+    let mut boid = vehicle(1.0, 479.0, Vec2::new(-1.25, 1.25), &params);
+    boid.apply_acceleration();
+    boid.bounce_location(0.0, params.width, 0.0, params.height);
+    assert_eq!(boid.location, Point2::new(0.25, 479.75));
+    assert_eq!(boid.velocity, Vec2::new(1.25, -1.25));
+
+    let (min, max) = extent(Boundary::Bounce);
+    assert!(min.x >= 0.0 && min.y >= 0.0, "{:?}", min);
+    assert!(max.x <= 640.0 && max.y <= 480.0, "{:?}", max);
+}
+
+#[test]
+fn steering_away_turns_vehicles_back() {
+    let params = params(Boundary::SteerAway);
+    let middle = vehicle(320.0, 240.0, Vec2::new(-2.0, 0.0), &params);
+    assert_eq!(middle.contain(&params), Vec2::zero());
+
+    let heading_out = vehicle(50.0, 240.0, Vec2::new(-2.0, 0.0), &params);
+    assert!(heading_out.contain(&params).x > 0.0);
+    let corner = vehicle(600.0, 450.0, Vec2::new(1.0, 1.0), &params);
+    let turn = corner.contain(&params);
+    assert!(turn.x < 0.0 && turn.y < 0.0);
+
+    // There's no hard edge, but nothing gets far past one.
+    let margin = params.boundary_margin;
+    let (min, max) = extent(Boundary::SteerAway);
+    assert!(min.x >= -margin && min.y >= -margin, "{:?}", min);
+    assert!(max.x <= 640.0 + margin && max.y <= 480.0 + margin, "{:?}", max);
+}
+
+#[test]
+fn boundaries_load_by_name() {
+    let params: FlockParams = serde_json::from_str(r#"{ "boundary": "steer_away" }"#).unwrap();
+    assert_eq!(params.boundary, Boundary::SteerAway);
+    assert_eq!(FlockParams::<f64>::default().boundary, Boundary::Wrap);
+}
+
+// Ticks until `chaser` comes within `range` of a target moving in a straight
+// line, steering with `steer` each tick.
+fn ticks_to_catch<F>(mut steer: F, range: f64) -> Option<usize>
+where
+    F: FnMut(&mut Vehicle, &Vehicle),
+{
+    let params = params(Boundary::SteerAway);
+    let mut chaser = vehicle(0.0, 0.0, Vec2::new(0.0, 0.0), &params);
+    let mut target = vehicle(200.0, 0.0, Vec2::new(0.0, 1.5), &params);
+    for tick in 0..2000 {
+        if chaser.location.distance(target.location) < range {
+            return Some(tick);
+        }
+        steer(&mut chaser, &target);
+        chaser.apply_acceleration();
+        target.apply_acceleration();
+    }
+    None
+}
+
+#[test]
+fn prediction_looks_further_ahead_for_distant_targets() {
+    let params = params(Boundary::SteerAway);
+    let chaser = vehicle(0.0, 0.0, Vec2::new(0.0, 0.0), &params);
+    // 100 away, closing at 2 + 1 per tick.
+    let near = vehicle(100.0, 0.0, Vec2::new(0.0, 1.0), &params);
+    let predicted = chaser.predict(&near);
+    assert!(predicted.distance(Point2::new(100.0, 100.0 / 3.0)) < 1e-9, "{:?}", predicted);
+    let far = vehicle(300.0, 0.0, Vec2::new(0.0, 1.0), &params);
+    assert!(chaser.predict(&far).y > predicted.y);
+
+    // A chaser that can't move has nothing to predict for a still target.
+    let stuck = Vehicle {
+        max_speed: 0.0,
+        ..chaser.clone()
+    };
+    let still = vehicle(100.0, 0.0, Vec2::new(0.0, 0.0), &params);
+    assert_eq!(stuck.predict(&still), still.location);
+}
+
+#[test]
+fn pursuit_cuts_off_a_moving_target() {
+    let pursued = ticks_to_catch(|chaser, target| chaser.pursue(target), 5.0);
+    let sought = ticks_to_catch(|chaser, target| chaser.seek(target.location), 5.0);
+    assert!(pursued.unwrap() < sought.unwrap(), "{:?} vs {:?}", pursued, sought);
+}
+
+#[test]
+fn evasion_dodges_where_the_threat_is_going() {
+    let params = params(Boundary::SteerAway);
+    let threat = vehicle(-50.0, 0.0, Vec2::new(0.0, 2.0), &params);
+    let mut fleeing = vehicle(0.0, 0.0, Vec2::new(0.0, 0.0), &params);
+    fleeing.flee(threat.location);
+    assert_eq!(fleeing.acceleration.y, 0.0);
+    let mut evading = vehicle(0.0, 0.0, Vec2::new(0.0, 0.0), &params);
+    evading.evade(&threat);
+    assert!(evading.acceleration.x > 0.0 && evading.acceleration.y < 0.0);
+}
+
+#[test]
+fn offset_pursuit_holds_station_beside_the_leader() {
+    let params = params(Boundary::SteerAway);
+    let mut leader = vehicle(100.0, 100.0, Vec2::new(1.0, 0.0), &params);
+    let mut escort = vehicle(0.0, 300.0, Vec2::new(0.0, 0.0), &params);
+    // Behind and to the right, which is further down the screen.
+    let offset = Vec2::new(-30.0, 20.0);
+    for _ in 0..600 {
+        escort.offset_pursuit(&leader, offset);
+        escort.apply_acceleration();
+        leader.apply_acceleration();
+    }
+    let station = leader.location + Vec2::new(-30.0, 20.0);
+    assert!(escort.location.distance(station) < 2.0, "{:?}", escort.location);
+}
+
+#[test]
+fn arriving_settles_on_the_target() {
+    let params = params(Boundary::SteerAway);
+    let mut boid = vehicle(100.0, 100.0, Vec2::new(2.0, 0.0), &params);
+    let target = Point2::new(400.0, 300.0);
+    for _ in 0..2000 {
+        boid.arrive(target);
+        boid.apply_acceleration();
+    }
+    assert!(boid.location.distance(target) < 1.0, "{:?}", boid.location);
+    assert!(boid.velocity.magnitude() < 0.05, "{:?}", boid.velocity);
+}
@@ -0,0 +1,88 @@
+extern crate gif;
+extern crate skunkworks;
+
+use std::env;
+use std::fs::{self, File};
+use std::path::PathBuf;
+
+use gif::SetParameter;
+
+use skunkworks::capture::{CaptureOptions, FrameCapture};
+
+fn scratch_directory(name: &str) -> PathBuf {
+    let directory = env::temp_dir().join(format!("skunkworks-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    directory
+}
+
+// Hundredths of a second each frame of the GIF at `path` is shown for.
+fn gif_delays(path: &PathBuf) -> Vec<u16> {
+    let mut decoder = gif::Decoder::new(File::open(path).unwrap());
+    decoder.set(gif::ColorOutput::RGBA);
+    let mut reader = decoder.read_info().unwrap();
+    let mut delays = Vec::new();
+    while let Some(frame) = reader.read_next_frame().unwrap() {
+        delays.push(frame.delay);
+    }
+    delays
+}
+
+#[test]
+fn writes_every_nth_tick_as_numbered_frames() {
+    let directory = scratch_directory("capture");
+    let gif_path = directory.join("capture.gif");
+    let options = CaptureOptions {
+        directory: directory.clone(),
+        every: 2,
+        step_time: 1.0 / 60.0,
+        gif: Some(gif_path.clone()),
+    };
+    let mut capture = FrameCapture::new(options).unwrap();
+    let pixels = [255u8; 2 * 2 * 4];
+    let ticks = 12;
+    for tick in 0..ticks {
+        assert_eq!(capture.wants_frame(), tick % 2 == 0);
+        capture.add_frame(2, 2, &pixels).unwrap();
+    }
+    assert_eq!(capture.frames_written(), 6);
+    capture.finish();
+
+    let mut written: Vec<String> = fs::read_dir(&directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .filter(|name| name.ends_with(".png"))
+        .collect();
+    written.sort();
+    let expected: Vec<String> = (0..6).map(|frame| format!("frame_{:05}.png", frame)).collect();
+    assert_eq!(written, expected);
+
+    let delays = gif_delays(&gif_path);
+    assert_eq!(delays.len(), 6);
+    let total: u16 = delays.iter().sum();
+    let expected_total = (f64::from(ticks) / 60.0 * 100.0).round() as u16;
+    assert_eq!(total, expected_total);
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn rejects_bad_options_and_frames() {
+    let directory = scratch_directory("capture-invalid");
+    let every_zero = CaptureOptions {
+        directory: directory.clone(),
+        every: 0,
+        ..Default::default()
+    };
+    assert!(FrameCapture::new(every_zero).is_err());
+
+    let mut capture = FrameCapture::new(CaptureOptions {
+        directory: directory.clone(),
+        ..Default::default()
+    }).unwrap();
+    assert!(capture.add_frame(2, 2, &[0; 12]).is_err());
+    // Sizes whose byte count overflows a u32 are refused, not wrapped.
+    assert!(capture.add_frame(40_000, 40_000, &[0; 16]).is_err());
+    assert_eq!(capture.frames_written(), 0);
+
+    fs::remove_dir_all(&directory).unwrap();
+}
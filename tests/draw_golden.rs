@@ -0,0 +1,224 @@
+// Renders the draw functions into a CPU raster and compares the result with
+// the PNGs in tests/golden. Run with UPDATE_GOLDEN=1 to rewrite the goldens
+// after an intended change. On a mismatch the rendered image and a diff,
+// with differing pixels in red, are written to target/golden. The SVG
+// snapshot is compared as text.
+
+extern crate skunkworks;
+
+use std::env;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::PathBuf;
+
+use skunkworks::obstacle::Obstacle;
+use skunkworks::{draw_obstacles, draw_player, draw_route, draw_waypoint, draw_waypoint_labels,
+                 draw_waypoints, Actor, Color, Point2, Raster, Svg, Waypoint};
+
+// Largest per-channel difference that still counts as the same pixel.
+const CHANNEL_TOLERANCE: u8 = 8;
+// Share of pixels allowed to differ by more than that.
+const MISMATCH_TOLERANCE: f64 = 0.001;
+
+const WIDTH: u32 = 160;
+const HEIGHT: u32 = 120;
+
+#[test]
+fn player() {
+    let mut raster = Raster::new(WIDTH, HEIGHT, background());
+    let player = Actor {
+        position: Point2::new(80.0, 60.0),
+        speed: 100.0,
+        waypoints: Vec::new(),
+    };
+    draw_player(&mut raster, &player, 14.0).unwrap();
+    assert_matches_golden("player", &raster);
+}
+
+#[test]
+fn waypoint() {
+    let mut raster = Raster::new(WIDTH, HEIGHT, background());
+    draw_waypoint(&mut raster, 14.0, &Waypoint::new(80.0, 60.0)).unwrap();
+    assert_matches_golden("waypoint", &raster);
+}
+
+#[test]
+fn waypoints() {
+    let mut raster = Raster::new(WIDTH, HEIGHT, background());
+    draw_waypoints(&mut raster, &route(), 4.0).unwrap();
+    assert_matches_golden("waypoints", &raster);
+}
+
+// Labels sit on top of their waypoints, as in the examples.
+#[test]
+fn waypoint_labels() {
+    let mut raster = Raster::new(WIDTH, HEIGHT, background());
+    draw_waypoints(&mut raster, &route(), 14.0).unwrap();
+    draw_waypoint_labels(&mut raster, &route()).unwrap();
+    assert_matches_golden("waypoint_labels", &raster);
+}
+
+#[test]
+fn route_lines() {
+    let mut raster = Raster::new(WIDTH, HEIGHT, background());
+    draw_route(&mut raster, &route(), true, 3.0).unwrap();
+    draw_waypoints(&mut raster, &route(), 4.0).unwrap();
+    assert_matches_golden("route", &raster);
+}
+
+#[test]
+fn scene() {
+    let mut raster = Raster::new(WIDTH, HEIGHT, background());
+    let player = scene_player();
+    draw_waypoints(&mut raster, &player.waypoints, 14.0).unwrap();
+    draw_waypoint_labels(&mut raster, &player.waypoints).unwrap();
+    draw_player(&mut raster, &player, 14.0).unwrap();
+    assert_matches_golden("scene", &raster);
+}
+
+#[test]
+fn obstacles() {
+    let mut raster = Raster::new(WIDTH, HEIGHT, background());
+    let obstacles = vec![
+        Obstacle::Circle {
+            center: Point2::new(40.0, 40.0),
+            radius: 18.0,
+        },
+        Obstacle::Polygon {
+            points: vec![
+                Point2::new(90.0, 20.0),
+                Point2::new(140.0, 30.0),
+                Point2::new(120.0, 70.0),
+            ],
+        },
+        Obstacle::Wall {
+            start: Point2::new(20.0, 100.0),
+            end: Point2::new(140.0, 95.0),
+        },
+    ];
+    draw_obstacles(&mut raster, &obstacles, 3.0).unwrap();
+    assert_matches_golden("obstacles", &raster);
+}
+
+#[test]
+fn scene_svg() {
+    let mut svg = Svg::new(WIDTH, HEIGHT);
+    svg.clear(background());
+    let player = scene_player();
+    draw_route(&mut svg, &player.waypoints, true, 3.0).unwrap();
+    draw_waypoints(&mut svg, &player.waypoints, 14.0).unwrap();
+    draw_waypoint_labels(&mut svg, &player.waypoints).unwrap();
+    draw_player(&mut svg, &player, 14.0).unwrap();
+
+    let golden_path = golden_dir().join("scene.svg");
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(golden_dir()).expect("Could not create tests/golden");
+        svg.write(File::create(&golden_path).unwrap()).unwrap();
+        return;
+    }
+    let mut golden = String::new();
+    File::open(&golden_path)
+        .and_then(|mut file| file.read_to_string(&mut golden))
+        .expect("Could not read the golden SVG");
+    assert_eq!(svg.to_svg_string(), golden);
+}
+
+fn scene_player() -> Actor {
+    Actor {
+        position: Point2::new(20.0, 20.0),
+        speed: 100.0,
+        waypoints: route(),
+    }
+}
+
+fn background() -> Color {
+    Color::new(0.1, 0.1, 0.2, 1.0)
+}
+
+fn route() -> Vec<Waypoint> {
+    vec![
+        Waypoint::new(40.0, 30.0),
+        Waypoint::new(120.0, 35.5),
+        Waypoint::new(100.25, 90.0),
+        Waypoint::new(30.5, 85.0),
+    ]
+}
+
+fn golden_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+}
+
+fn assert_matches_golden(name: &str, actual: &Raster) {
+    let golden_path = golden_dir().join(format!("{}.png", name));
+
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(golden_dir()).expect("Could not create tests/golden");
+        let file = File::create(&golden_path).expect("Could not create the golden image");
+        actual.to_png(file).expect("Could not write the golden image");
+        return;
+    }
+
+    let file = File::open(&golden_path).unwrap_or_else(|e| {
+        panic!(
+            "Could not open {}: {} (run with UPDATE_GOLDEN=1 to create it)",
+            golden_path.display(),
+            e
+        )
+    });
+    let golden = Raster::from_png(file).expect("Could not read the golden image");
+    assert_eq!(
+        (golden.width(), golden.height()),
+        (actual.width(), actual.height()),
+        "{} has a different size than its golden image",
+        name
+    );
+
+    let (diff, mismatched) = diff_image(&golden, actual);
+    let allowed = f64::from(golden.width() * golden.height()) * MISMATCH_TOLERANCE;
+    if mismatched as f64 > allowed {
+        let failure_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("target")
+            .join("golden");
+        fs::create_dir_all(&failure_dir).expect("Could not create target/golden");
+        let actual_path = failure_dir.join(format!("{}.actual.png", name));
+        let diff_path = failure_dir.join(format!("{}.diff.png", name));
+        actual
+            .to_png(File::create(&actual_path).unwrap())
+            .unwrap();
+        diff.to_png(File::create(&diff_path).unwrap()).unwrap();
+        panic!(
+            "{} differs from its golden image in {} pixels, see {} and {}",
+            name,
+            mismatched,
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
+
+// Differing pixels come out red over a dimmed copy of the golden image.
+fn diff_image(golden: &Raster, actual: &Raster) -> (Raster, usize) {
+    let mut diff = Raster::new(golden.width(), golden.height(), Color::new(0.0, 0.0, 0.0, 1.0));
+    let mut mismatched = 0;
+    for y in 0..golden.height() {
+        for x in 0..golden.width() {
+            let expected = golden.pixel(x, y);
+            let found = actual.pixel(x, y);
+            let differs = expected
+                .iter()
+                .zip(found.iter())
+                .any(|(a, b)| (i16::from(*a) - i16::from(*b)).abs() > i16::from(CHANNEL_TOLERANCE));
+            if differs {
+                mismatched += 1;
+                diff.set_pixel(x, y, [255, 0, 0, 255]);
+            } else {
+                let luma = (u16::from(expected[0]) + u16::from(expected[1]) + u16::from(expected[2])) / 3;
+                let dimmed = (luma / 4) as u8;
+                diff.set_pixel(x, y, [dimmed, dimmed, dimmed, 255]);
+            }
+        }
+    }
+    (diff, mismatched)
+}
@@ -0,0 +1,195 @@
+extern crate skunkworks;
+
+use std::cell::RefCell;
+
+use skunkworks::boids::{Boundary, FlockParams, Vehicle};
+use skunkworks::ecosystem::{Ecosystem, Species};
+use skunkworks::replay::{StateHash, StateHasher};
+use skunkworks::rewind::Snapshot;
+use skunkworks::rng::SimRng;
+use skunkworks::{Point2, Vec2};
+
+fn params() -> FlockParams {
+    FlockParams {
+        width: 640.0,
+        height: 480.0,
+        boundary: Boundary::Wrap,
+        ..Default::default()
+    }
+}
+
+fn koi() -> Species {
+    Species {
+        name: "koi".to_string(),
+        params: params(),
+        ..Default::default()
+    }
+}
+
+fn pike() -> Species {
+    Species {
+        name: "pike".to_string(),
+        params: FlockParams {
+            max_speed: 2.5,
+            max_force: 0.05,
+            ..params()
+        },
+        prey: vec!["koi".to_string(), "pike".to_string()],
+        ..Default::default()
+    }
+}
+
+// An ecosystem of the given species with no members yet.
+fn empty(species: Vec<Species>) -> Ecosystem {
+    let mut rng = SimRng::new(1);
+    let mut ecosystem = Ecosystem::new();
+    for species in species {
+        ecosystem.add_species(species, 0, &mut rng);
+    }
+    ecosystem
+}
+
+fn place(ecosystem: &mut Ecosystem, species: usize, x: f64, y: f64, velocity: Vec2<f64>) {
+    let vehicle = Vehicle::new(velocity, Point2::new(x, y), &ecosystem.species[species].params);
+    ecosystem.flocks[species].vehicles.push(RefCell::new(vehicle));
+}
+
+fn location(ecosystem: &Ecosystem, species: usize, index: usize) -> Point2<f64> {
+    ecosystem.flocks[species].vehicles[index].borrow().location
+}
+
+#[test]
+fn predators_and_prey_are_matched_by_name() {
+    let ecosystem = empty(vec![koi(), pike()]);
+    assert_eq!(ecosystem.index_of("pike"), Some(1));
+    assert_eq!(ecosystem.prey_of(0), Vec::<usize>::new());
+    // Pike don't hunt each other even when listed.
+    assert_eq!(ecosystem.prey_of(1), vec![0]);
+    assert_eq!(ecosystem.predators_of(0), vec![1]);
+    assert_eq!(ecosystem.predators_of(1), Vec::<usize>::new());
+}
+
+#[test]
+fn prey_flee_and_predators_give_chase() {
+    let mut ecosystem = empty(vec![koi(), pike()]);
+    place(&mut ecosystem, 0, 300.0, 240.0, Vec2::zero());
+    place(&mut ecosystem, 1, 200.0, 240.0, Vec2::new(0.0, 1.0));
+    let mut rng = SimRng::new(2);
+    for _ in 0..10 {
+        ecosystem.update(&mut rng);
+    }
+    let prey = ecosystem.flocks[0].vehicles[0].borrow().clone();
+    let hunter = ecosystem.flocks[1].vehicles[0].borrow().clone();
+    assert!(prey.velocity.x > 0.0, "{:?}", prey.velocity);
+    assert!(hunter.velocity.x > 0.0, "{:?}", hunter.velocity);
+
+    // Out of range of each other, neither reacts.
+    let mut apart = empty(vec![koi(), pike()]);
+    place(&mut apart, 0, 600.0, 240.0, Vec2::new(0.0, 1.0));
+    place(&mut apart, 1, 100.0, 240.0, Vec2::new(0.0, 1.0));
+    let mut alone = empty(vec![koi()]);
+    place(&mut alone, 0, 600.0, 240.0, Vec2::new(0.0, 1.0));
+    let mut rng = SimRng::new(2);
+    apart.update(&mut rng);
+    let mut rng = SimRng::new(2);
+    alone.update(&mut rng);
+    assert_eq!(location(&apart, 0, 0), location(&alone, 0, 0));
+}
+
+#[test]
+fn species_only_flock_with_their_own_kind() {
+    let carp = Species {
+        name: "carp".to_string(),
+        ..koi()
+    };
+    let mut mixed = empty(vec![koi(), carp]);
+    place(&mut mixed, 0, 300.0, 240.0, Vec2::new(1.0, 0.0));
+    place(&mut mixed, 0, 300.0, 280.0, Vec2::new(1.0, 0.0));
+    place(&mut mixed, 1, 320.0, 260.0, Vec2::new(0.0, -2.0));
+    let mut alone = empty(vec![koi()]);
+    place(&mut alone, 0, 300.0, 240.0, Vec2::new(1.0, 0.0));
+    place(&mut alone, 0, 300.0, 280.0, Vec2::new(1.0, 0.0));
+
+    let (mut rng, mut same_rng) = (SimRng::new(4), SimRng::new(4));
+    for _ in 0..60 {
+        mixed.update(&mut rng);
+        alone.update(&mut same_rng);
+        // Every tick starts from the same random state, and the koi go first
+        // so they draw the same numbers in both.
+        same_rng = rng;
+    }
+    assert_eq!(location(&mixed, 0, 0), location(&alone, 0, 0));
+    assert_eq!(location(&mixed, 0, 1), location(&alone, 0, 1));
+}
+
+#[test]
+fn caught_prey_respawn_or_are_removed() {
+    for &respawn in [true, false].iter() {
+        let mut ecosystem = empty(vec![Species { respawn, ..koi() }, pike()]);
+        place(&mut ecosystem, 0, 300.0, 240.0, Vec2::new(1.0, 0.0));
+        place(&mut ecosystem, 0, 100.0, 100.0, Vec2::new(1.0, 0.0));
+        place(&mut ecosystem, 1, 298.0, 240.0, Vec2::new(1.0, 0.0));
+        let catches = ecosystem.update(&mut SimRng::new(6));
+        assert_eq!(catches.len(), 1);
+        assert_eq!((catches[0].predator, catches[0].prey), (1, 0));
+        assert!(catches[0].location.distance(Point2::new(300.0, 240.0)) < 5.0);
+        if respawn {
+            assert_eq!(ecosystem.flocks[0].vehicles.len(), 2);
+            assert!(location(&ecosystem, 0, 0).distance(Point2::new(300.0, 240.0)) > 5.0);
+        } else {
+            assert_eq!(ecosystem.flocks[0].vehicles.len(), 1);
+        }
+    }
+}
+
+#[test]
+fn predators_catch_one_prey_a_tick_across_species() {
+    let carp = Species {
+        name: "carp".to_string(),
+        respawn: false,
+        ..koi()
+    };
+    let hunter = Species {
+        prey: vec!["koi".to_string(), "carp".to_string()],
+        ..pike()
+    };
+    let mut ecosystem = empty(vec![Species { respawn: false, ..koi() }, carp, hunter]);
+    place(&mut ecosystem, 0, 300.0, 240.0, Vec2::new(1.0, 0.0));
+    place(&mut ecosystem, 1, 300.0, 242.0, Vec2::new(1.0, 0.0));
+    place(&mut ecosystem, 2, 298.0, 241.0, Vec2::new(1.0, 0.0));
+
+    let catches = ecosystem.update(&mut SimRng::new(6));
+    assert_eq!(catches.len(), 1);
+    assert_eq!((catches[0].predator, catches[0].prey), (2, 0));
+    assert_eq!(ecosystem.flocks[1].vehicles.len(), 1);
+    // The carp is left for the next tick.
+    let catches = ecosystem.update(&mut SimRng::new(7));
+    assert_eq!(catches.len(), 1);
+    assert_eq!(catches[0].prey, 1);
+}
+
+#[test]
+fn hunting_runs_the_same_after_a_rewind() {
+    let mut rng = SimRng::new(8);
+    let mut ecosystem = Ecosystem::new();
+    ecosystem.add_species(koi(), 40, &mut rng);
+    ecosystem.add_species(pike(), 2, &mut rng);
+    let saved = (ecosystem.snapshot(), rng);
+
+    let run = |ecosystem: &mut Ecosystem, rng: &mut SimRng| {
+        let mut caught = 0;
+        for _ in 0..1500 {
+            caught += ecosystem.update(rng).len();
+        }
+        let mut hasher = StateHasher::new();
+        ecosystem.hash_state(&mut hasher);
+        (caught, hasher.finish())
+    };
+    let first = run(&mut ecosystem, &mut rng);
+    ecosystem.restore(&saved.0);
+    rng = saved.1;
+    let second = run(&mut ecosystem, &mut rng);
+    assert!(first.0 > 0, "the pike never caught anything");
+    assert_eq!(first, second);
+    assert_eq!(ecosystem.flocks[0].vehicles.len(), 40);
+}
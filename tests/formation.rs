@@ -0,0 +1,143 @@
+extern crate skunkworks;
+
+use skunkworks::formation::{Formation, Squad};
+use skunkworks::orca::preferred_velocity;
+use skunkworks::{Actor, Point2, Vec2, Waypoint};
+
+const DELTA_T: f32 = 1.0 / 60.0;
+
+fn actor(x: f32, y: f32, speed: f32) -> Actor {
+    Actor {
+        position: Point2::new(x, y),
+        speed,
+        waypoints: Vec::new(),
+    }
+}
+
+fn assert_near(actual: Vec2<f32>, expected: Vec2<f32>) {
+    assert!((actual - expected).magnitude() < 1e-4, "{:?} != {:?}", actual, expected);
+}
+
+fn assert_offsets(formation: Formation, expected: &[(f32, f32)]) {
+    let offsets = formation.offsets(expected.len());
+    for (&offset, &(x, y)) in offsets.iter().zip(expected) {
+        assert_near(offset, Vec2::new(x, y));
+    }
+}
+
+// Furthest any follower is from its slot.
+fn worst_gap(squad: &Squad) -> f32 {
+    (0..squad.followers().len())
+        .map(|index| squad.followers()[index].position.distance(squad.slot_position(index)))
+        .fold(0.0, f32::max)
+}
+
+#[test]
+fn formations_lay_out_their_slots() {
+    let line = Formation::Line { spacing: 10.0 };
+    assert_offsets(line, &[(0.0, 10.0), (0.0, -10.0), (0.0, 20.0)]);
+    let column = Formation::Column { spacing: 10.0 };
+    assert_offsets(column, &[(-10.0, 0.0), (-20.0, 0.0), (-30.0, 0.0)]);
+    let wedge = Formation::Wedge { spacing: 10.0 };
+    assert_offsets(wedge, &[(-10.0, 10.0), (-10.0, -10.0), (-20.0, 20.0)]);
+    let circle = Formation::Circle { radius: 10.0 };
+    assert_offsets(circle, &[(10.0, 0.0), (0.0, 10.0), (-10.0, 0.0), (0.0, -10.0)]);
+}
+
+#[test]
+fn custom_formations_queue_extra_followers_behind() {
+    let custom = Formation::Custom {
+        slots: vec![Vec2::new(5.0, 0.0), Vec2::new(0.0, 20.0)],
+    };
+    assert_offsets(custom, &[(5.0, 0.0), (0.0, 20.0), (-20.0, 20.0), (-40.0, 20.0)]);
+    let empty = Formation::Custom { slots: Vec::new() };
+    assert_offsets(empty, &[(0.0, 0.0)]);
+}
+
+#[test]
+fn slots_turn_with_the_leader() {
+    let mut leader = actor(0.0, 0.0, 100.0);
+    leader.waypoints.push(Waypoint::new(0.0, 200.0));
+    let mut squad = Squad::new(leader, Formation::Column { spacing: 20.0 });
+    squad.add(actor(-20.0, 0.0, 100.0));
+    squad.update(DELTA_T);
+    // Heading down the screen, so behind is up.
+    let slot = squad.slot_position(0) - squad.leader.position;
+    assert_near(slot, Vec2::new(0.0, -20.0));
+}
+
+#[test]
+fn followers_keep_formation_along_the_route() {
+    let mut leader = actor(0.0, 0.0, 60.0);
+    for &(x, y) in [(300.0, 0.0), (300.0, 300.0), (0.0, 300.0)].iter() {
+        leader.waypoints.push(Waypoint::new(x, y));
+    }
+    let mut squad = Squad::new(leader, Formation::Wedge { spacing: 25.0 });
+    for &(x, y) in [(-80.0, 60.0), (-60.0, -90.0), (-120.0, 10.0), (-10.0, -150.0)].iter() {
+        squad.add(actor(x, y, 90.0));
+    }
+
+    for _ in 0..60 * 20 {
+        squad.update(DELTA_T);
+    }
+    assert!(squad.leader.waypoints.is_empty());
+    assert!(worst_gap(&squad) < 1.0, "{}", worst_gap(&squad));
+    let mut slots: Vec<usize> = (0..4).map(|index| squad.slot(index)).collect();
+    slots.sort();
+    assert_eq!(slots, vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn adding_and_removing_followers_reshuffles_the_slots() {
+    let mut squad = Squad::new(actor(0.0, 0.0, 50.0), Formation::Line { spacing: 10.0 });
+    // Each stands nearest a different slot than the order it joined in.
+    assert_eq!(squad.add(actor(0.0, -10.0, 50.0)), 0);
+    assert_eq!(squad.add(actor(0.0, 10.0, 50.0)), 1);
+    assert_eq!(squad.add(actor(0.0, 21.0, 50.0)), 2);
+    assert_eq!((squad.slot(0), squad.slot(1), squad.slot(2)), (1, 0, 2));
+
+    // With the right-hand follower gone, whoever is nearest takes its slot.
+    let removed = squad.remove(1);
+    assert_eq!(removed.position, Point2::new(0.0, 10.0));
+    assert_eq!(squad.followers().len(), 2);
+    assert_eq!((squad.slot(0), squad.slot(1)), (1, 0));
+
+    for _ in 0..60 * 5 {
+        squad.update(DELTA_T);
+    }
+    assert!(worst_gap(&squad) < 1.0, "{}", worst_gap(&squad));
+    assert!(squad.followers()[1].position.distance(Point2::new(0.0, 10.0)) < 1.0);
+}
+
+#[test]
+fn looping_squads_keep_walking_the_route() {
+    let mut leader = actor(0.0, 0.0, 100.0);
+    leader.waypoints.push(Waypoint::new(100.0, 0.0));
+    leader.waypoints.push(Waypoint::new(0.0, 0.0));
+    let mut squad = Squad::new(leader, Formation::Circle { radius: 30.0 });
+    squad.looping = true;
+    squad.add(actor(0.0, 30.0, 150.0));
+
+    let mut far_end = 0;
+    for _ in 0..60 * 10 {
+        squad.update(DELTA_T);
+        if squad.leader.position.distance(Point2::new(100.0, 0.0)) < 1.0 {
+            far_end += 1;
+        }
+    }
+    assert_eq!(squad.leader.waypoints.len(), 2);
+    assert!(far_end >= 4, "reached the far end {} times", far_end);
+}
+
+#[test]
+fn paused_updates_leave_the_squad_alone() {
+    let mut leader = actor(0.0, 0.0, 100.0);
+    leader.waypoints.push(Waypoint::new(100.0, 0.0));
+    let mut squad = Squad::new(leader, Formation::Column { spacing: 20.0 });
+    // Already on its slot, the case that used to divide nothing by nothing.
+    squad.add(actor(-20.0, 0.0, 100.0));
+    squad.update(0.0);
+    assert_eq!(squad.leader.position, Point2::new(0.0, 0.0));
+    assert_eq!(squad.followers()[0].position, Point2::new(-20.0, 0.0));
+    assert_eq!(preferred_velocity(&squad.leader, 0.0), Vec2::zero());
+}
@@ -0,0 +1,208 @@
+extern crate skunkworks;
+
+use std::f64::consts::PI;
+use std::fs::File;
+use std::path::PathBuf;
+
+use skunkworks::geo::{project_tracks, GeoPoint, GeoTrack, GeoTrackPoint, Projection,
+                      ScreenTransform};
+
+const EARTH_RADIUS: f64 = 6_378_137.0;
+
+fn fixture(name: &str) -> File {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name);
+    File::open(&path).expect("Could not open the fixture")
+}
+
+fn assert_near(actual: f64, expected: f64, tolerance: f64) {
+    assert!(
+        (actual - expected).abs() < tolerance,
+        "{} is not close to {}",
+        actual,
+        expected
+    );
+}
+
+fn locations(track: &GeoTrack) -> Vec<(f64, f64)> {
+    track
+        .points
+        .iter()
+        .map(|point| (point.location.latitude, point.location.longitude))
+        .collect()
+}
+
+fn track(points: &[(f64, f64)]) -> GeoTrack {
+    GeoTrack {
+        name: String::new(),
+        points: points
+            .iter()
+            .map(|&(latitude, longitude)| GeoTrackPoint {
+                location: GeoPoint::new(latitude, longitude),
+                elevation: None,
+                timestamp: None,
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn gpx_tracks_and_routes_load() {
+    let tracks = GeoTrack::from_gpx(fixture("walk.gpx")).unwrap();
+    assert_eq!(tracks.len(), 2);
+
+    // Both segments of the track are joined into one.
+    let walk = &tracks[0];
+    assert_eq!(walk.name, "Morning walk");
+    assert_eq!(
+        locations(walk),
+        vec![(51.5, -0.12), (51.501, -0.119), (51.502, -0.118)]
+    );
+    let elevations: Vec<Option<f64>> = walk.points.iter().map(|point| point.elevation).collect();
+    assert_eq!(elevations, vec![Some(11.5), Some(12.0), None]);
+    let start = walk.points[0].timestamp.unwrap();
+    assert_eq!(start.to_rfc3339(), "2018-06-01T08:00:00+00:00");
+    // Offsets are converted to UTC.
+    let last = walk.points[2].timestamp.unwrap();
+    assert_eq!(last.to_rfc3339(), "2018-06-01T08:01:30+00:00");
+
+    let route = &tracks[1];
+    assert_eq!(route.name, "Back home");
+    assert_eq!(locations(route), vec![(51.502, -0.118), (51.5, -0.12)]);
+    assert!(route.points.iter().all(|point| point.timestamp.is_none()));
+}
+
+#[test]
+fn geojson_lines_load() {
+    let tracks = GeoTrack::from_geojson(fixture("walk.geojson")).unwrap();
+    // The Point feature isn't a track, each line of the MultiLineString is.
+    assert_eq!(tracks.len(), 3);
+
+    let walk = &tracks[0];
+    assert_eq!(walk.name, "Morning walk");
+    assert_eq!(locations(walk), vec![(51.5, -0.12), (51.501, -0.119)]);
+    assert_eq!(walk.points[1].elevation, Some(12.0));
+    let times: Vec<String> = walk
+        .points
+        .iter()
+        .map(|point| point.timestamp.unwrap().to_rfc3339())
+        .collect();
+    assert_eq!(times, vec!["2018-06-01T08:00:00+00:00", "2018-06-01T08:00:30+00:00"]);
+
+    assert_eq!((tracks[1].name.as_str(), tracks[1].points.len()), ("Detours", 2));
+    assert_eq!((tracks[2].name.as_str(), locations(&tracks[2])), ("Detours", vec![(0.0, 0.0)]));
+    assert!(tracks[1].points[0].elevation.is_none());
+}
+
+#[test]
+fn malformed_documents_are_rejected() {
+    let gpx = [
+        r#"<kml></kml>"#,
+        r#"<gpx><trk><trkseg><trkpt lat="north" lon="0"/></trkseg></trk></gpx>"#,
+        r#"<gpx><trk><trkseg><trkpt lat="0"/></trkseg></trk></gpx>"#,
+        r#"<gpx><rte><rtept lat="0" lon="0"><ele>high</ele></rtept></rte></gpx>"#,
+        r#"<gpx><rte><rtept lat="0" lon="0"><time>yesterday</time></rtept></rte></gpx>"#,
+        r#"<gpx><rte>"#,
+    ];
+    for document in gpx.iter() {
+        assert!(GeoTrack::from_gpx(document.as_bytes()).is_err(), "{}", document);
+    }
+
+    let geojson = [
+        r#"{"type": "LineString", "coordinates": "#,
+        r#"{"type": "LineString", "coordinates": [[0.0]]}"#,
+        r#"{"type": "LineString", "coordinates": {"lat": 0, "lon": 0}}"#,
+        r#"{"type": "MultiLineString", "coordinates": [[0.0, 0.0]]}"#,
+        r#"{"type": "FeatureCollection"}"#,
+        r#"{"type": "Feature", "properties": {"times": ["noon"]},
+            "geometry": {"type": "LineString", "coordinates": [[0, 0]]}}"#,
+    ];
+    for document in geojson.iter() {
+        assert!(GeoTrack::from_geojson(document.as_bytes()).is_err(), "{}", document);
+    }
+}
+
+#[test]
+fn web_mercator_matches_known_values() {
+    let mercator = Projection::WebMercator;
+    let (x, y) = mercator.project(GeoPoint::new(0.0, 0.0));
+    assert_near(x, 0.0, 1e-9);
+    assert_near(y, 0.0, 1e-9);
+
+    // The edge of the slippy map world is a square half a circumference wide.
+    let (x, y) = mercator.project(GeoPoint::new(85.051_128_78, 180.0));
+    assert_near(x, PI * EARTH_RADIUS, 1e-6);
+    assert_near(y, PI * EARTH_RADIUS, 1.0);
+
+    // Anything nearer the poles is clamped to that edge.
+    let (_, edge) = mercator.project(GeoPoint::new(85.051_128_78, 0.0));
+    for &latitude in [86.0, 90.0].iter() {
+        let (_, y) = mercator.project(GeoPoint::new(latitude, 0.0));
+        assert_eq!(y, edge);
+        let (_, y) = mercator.project(GeoPoint::new(-latitude, 0.0));
+        assert_near(y, -edge, 1e-6);
+    }
+}
+
+#[test]
+fn equirectangular_is_centred_on_its_origin() {
+    let tracks = vec![track(&[(60.0, 10.0), (61.0, 12.0)])];
+    let local = Projection::local_to(&tracks).unwrap();
+    let (x, y) = local.project(GeoPoint::new(60.0, 10.0));
+    assert_eq!((x, y), (0.0, 0.0));
+
+    // A degree of latitude is the same everywhere, a degree of longitude
+    // shrinks to half at 60 degrees.
+    let degree = EARTH_RADIUS * PI / 180.0;
+    let (x, y) = local.project(GeoPoint::new(61.0, 12.0));
+    assert_near(x, degree, 1e-6);
+    assert_near(y, degree, 1e-6);
+
+    assert!(Projection::local_to(&[]).is_none());
+}
+
+#[test]
+fn fitting_keeps_aspect_and_centres_the_bounds() {
+    let origin = GeoPoint::new(0.0, 0.0);
+    let projection = Projection::Equirectangular { origin };
+    // Twice as wide as it is tall, fitted into a square screen.
+    let tracks = vec![track(&[(0.0, 0.0), (1.0, 2.0)])];
+    let transform = ScreenTransform::fit(&tracks, &projection, 420.0, 420.0, 10.0);
+
+    let projected = project_tracks(&tracks, &projection, &transform);
+    let first = projected[0].points[0].waypoint.position;
+    let last = projected[0].points[1].waypoint.position;
+    // The width fills the 400 usable pixels and the height gets the same scale.
+    assert_near(f64::from(last.x - first.x), 400.0, 1e-3);
+    assert_near(f64::from(first.y - last.y), 200.0, 1e-3);
+    // The middle of the bounds lands in the middle of the screen.
+    assert_near(f64::from(first.x + last.x) / 2.0, 210.0, 1e-3);
+    assert_near(f64::from(first.y + last.y) / 2.0, 210.0, 1e-3);
+
+    // A single point sits in the middle, and so does nothing at all.
+    let point = vec![track(&[(1.0, 1.0)])];
+    let transform = ScreenTransform::fit(&point, &projection, 300.0, 200.0, 10.0);
+    let centre = transform.to_screen(projection.project(GeoPoint::new(1.0, 1.0)));
+    assert_eq!((centre.x, centre.y), (150.0, 100.0));
+    let empty = ScreenTransform::fit(&[], &projection, 300.0, 200.0, 10.0);
+    assert_eq!(empty.screen_origin, (150.0, 100.0));
+}
+
+#[test]
+fn projected_tracks_keep_elevation_and_time() {
+    let tracks = GeoTrack::from_gpx(fixture("walk.gpx")).unwrap();
+    let projection = Projection::local_to(&tracks).unwrap();
+    let transform = ScreenTransform::fit(&tracks, &projection, 800.0, 600.0, 20.0);
+    let projected = project_tracks(&tracks, &projection, &transform);
+
+    let walk = &projected[0];
+    assert_eq!(walk.waypoints().len(), 3);
+    assert_eq!(walk.points[0].elevation, Some(11.5));
+    assert_eq!(walk.elapsed_seconds(1), Some(30.0));
+    assert_eq!(walk.elapsed_seconds(2), Some(90.0));
+    assert_eq!(projected[1].elapsed_seconds(0), None);
+    // North is up on screen.
+    assert!(walk.points[1].waypoint.position.y < walk.points[0].waypoint.position.y);
+}
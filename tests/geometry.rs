@@ -0,0 +1,217 @@
+extern crate skunkworks;
+
+use std::f64::consts::PI;
+
+use skunkworks::math::{angle_difference, point_in_polygon, polygon_area, polygon_centroid,
+                       polygon_signed_area, ray_circle_intersection, segment_distance,
+                       segment_intersection, wrap_angle, Affine2};
+use skunkworks::{rotate_vector2, Point2, Vec2};
+
+const EPSILON: f64 = 1e-9;
+
+fn assert_near(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < EPSILON, "{} is not close to {}", actual, expected);
+}
+
+fn assert_point(actual: Point2<f64>, expected: Point2<f64>) {
+    assert!(
+        actual.distance(expected) < EPSILON,
+        "{:?} is not close to {:?}",
+        actual,
+        expected
+    );
+}
+
+fn square() -> Vec<Point2<f64>> {
+    vec![
+        Point2::new(0.0, 0.0),
+        Point2::new(2.0, 0.0),
+        Point2::new(2.0, 2.0),
+        Point2::new(0.0, 2.0),
+    ]
+}
+
+#[test]
+fn transforms_points_and_vectors() {
+    let point = Point2::new(1.0, 2.0);
+    assert_eq!(Affine2::identity() * point, point);
+
+    let moved = Affine2::translation(Vec2::new(3.0, -1.0));
+    assert_eq!(moved * point, Point2::new(4.0, 1.0));
+    assert_eq!(moved * Vec2::new(1.0, 2.0), Vec2::new(1.0, 2.0));
+
+    assert_eq!(Affine2::scale(2.0, 3.0) * point, Point2::new(2.0, 6.0));
+    assert_point(Affine2::rotation(PI / 2.0) * point, Point2::new(-2.0, 1.0));
+    assert_point(
+        Affine2::rotation_about(Point2::new(1.0, 1.0), PI) * point,
+        Point2::new(1.0, 0.0),
+    );
+}
+
+#[test]
+fn composes_in_order() {
+    let scale = Affine2::scale(2.0, 2.0);
+    let moved = Affine2::translation(Vec2::new(1.0, 0.0));
+    let point = Point2::new(1.0, 1.0);
+
+    assert_eq!(scale.then(moved) * point, Point2::new(3.0, 2.0));
+    assert_eq!(moved.then(scale) * point, Point2::new(4.0, 2.0));
+    assert_eq!(moved * scale, scale.then(moved));
+}
+
+#[test]
+fn inverts_transforms() {
+    let transform = Affine2::rotation(0.7)
+        .then(Affine2::scale(2.0, 0.5))
+        .then(Affine2::translation(Vec2::new(-3.0, 4.0)));
+    let inverse = transform.inverse().unwrap();
+    let point = Point2::new(5.0, -2.0);
+    assert_point(inverse * (transform * point), point);
+    assert_near(transform.determinant(), 1.0);
+    assert!(Affine2::scale(1.0, 0.0).inverse().is_none());
+}
+
+#[test]
+fn rotates_by_relative_angles() {
+    let vector: Vec2<f64> = Vec2::new(2.0, 0.0);
+    let turned = vector.rotate(PI / 2.0).rotate(PI / 2.0);
+    assert!((turned - Vec2::new(-2.0, 0.0)).magnitude() < EPSILON);
+
+    // rotate_vector2 sets the heading instead, so doing it twice changes
+    // nothing.
+    let mut set = vector;
+    rotate_vector2(&mut set, PI / 2.0);
+    rotate_vector2(&mut set, PI / 2.0);
+    assert!((set - Vec2::new(0.0, 2.0)).magnitude() < EPSILON);
+
+    let point = Point2::new(2.0, 1.0);
+    assert_point(point.rotate_about(Point2::new(1.0, 1.0), -PI / 2.0), Point2::new(1.0, 0.0));
+}
+
+#[test]
+fn wraps_angles() {
+    assert_near(wrap_angle(0.5), 0.5);
+    assert_near(wrap_angle(3.0 * PI), -PI);
+    assert_near(wrap_angle(-PI), -PI);
+    assert_near(wrap_angle(PI / 2.0 + 4.0 * PI), PI / 2.0);
+    assert_near(wrap_angle(-7.0 * PI / 4.0), PI / 4.0);
+}
+
+#[test]
+fn takes_the_short_way_round() {
+    assert_near(angle_difference(0.1, 0.4), 0.3);
+    assert_near(angle_difference(0.4, 0.1), -0.3);
+    assert_near(angle_difference(PI - 0.1, -PI + 0.1), 0.2);
+    assert_near(angle_difference(-PI + 0.1, PI - 0.1), -0.2);
+    assert!(angle_difference(0.1f32, 6.2).abs() < 0.2);
+}
+
+#[test]
+fn intersects_segments() {
+    let hit = segment_intersection(
+        Point2::new(0.0, 0.0),
+        Point2::new(2.0, 2.0),
+        Point2::new(0.0, 2.0),
+        Point2::new(2.0, 0.0),
+    );
+    assert_point(hit.unwrap(), Point2::new(1.0, 1.0));
+
+    // Touching at an end counts.
+    let touch = segment_intersection(
+        Point2::new(0.0, 0.0),
+        Point2::new(1.0, 0.0),
+        Point2::new(1.0, -1.0),
+        Point2::new(1.0, 1.0),
+    );
+    assert_point(touch.unwrap(), Point2::new(1.0, 0.0));
+
+    let short = segment_intersection(
+        Point2::new(0.0, 0.0),
+        Point2::new(0.5, 0.0),
+        Point2::new(1.0, -1.0),
+        Point2::new(1.0, 1.0),
+    );
+    assert_eq!(short, None);
+
+    let parallel = segment_intersection(
+        Point2::new(0.0, 0.0),
+        Point2::new(2.0, 0.0),
+        Point2::new(0.0, 1.0),
+        Point2::new(2.0, 1.0),
+    );
+    assert_eq!(parallel, None);
+}
+
+#[test]
+fn measures_distance_to_segments() {
+    let (start, end) = (Point2::new(0.0, 0.0), Point2::new(10.0, 0.0));
+    assert_near(segment_distance(Point2::new(5.0, 3.0), start, end), 3.0);
+    // Past either end it's the distance to that end.
+    assert_near(segment_distance(Point2::new(-3.0, 4.0), start, end), 5.0);
+    assert_near(segment_distance(Point2::new(13.0, -4.0), start, end), 5.0);
+    assert_near(segment_distance(Point2::new(3.0, 4.0), start, start), 5.0);
+}
+
+#[test]
+fn intersects_rays_with_circles() {
+    let center = Point2::new(10.0, 0.0);
+    let origin = Point2::origin();
+    assert_near(ray_circle_intersection(origin, Vec2::new(1.0, 0.0), center, 2.0).unwrap(), 8.0);
+
+    // The distance is in world units however long the direction is.
+    assert_near(ray_circle_intersection(origin, Vec2::new(5.0, 0.0), center, 2.0).unwrap(), 8.0);
+
+    assert_eq!(ray_circle_intersection(origin, Vec2::new(-1.0, 0.0), center, 2.0), None);
+    assert_eq!(ray_circle_intersection(origin, Vec2::new(0.0, 1.0), center, 2.0), None);
+    assert_eq!(ray_circle_intersection(origin, Vec2::zero(), center, 2.0), None);
+
+    let inside = Point2::new(9.0, 0.0);
+    assert_eq!(ray_circle_intersection(inside, Vec2::new(1.0, 0.0), center, 2.0), Some(0.0));
+
+    let grazing = ray_circle_intersection(Point2::new(0.0, 2.0), Vec2::new(1.0, 0.0), center, 2.0);
+    assert_near(grazing.unwrap(), 10.0);
+}
+
+#[test]
+fn finds_points_in_polygons() {
+    let square = square();
+    assert!(point_in_polygon(Point2::new(1.0, 1.0), &square));
+    assert!(!point_in_polygon(Point2::new(3.0, 1.0), &square));
+    assert!(!point_in_polygon(Point2::new(1.0, -0.5), &square));
+    assert!(!point_in_polygon(Point2::new(1.0, 1.0), &[]));
+
+    // A concave U shape with the notch open at the top.
+    let u = vec![
+        Point2::new(0.0, 0.0),
+        Point2::new(3.0, 0.0),
+        Point2::new(3.0, 3.0),
+        Point2::new(2.0, 3.0),
+        Point2::new(2.0, 1.0),
+        Point2::new(1.0, 1.0),
+        Point2::new(1.0, 3.0),
+        Point2::new(0.0, 3.0),
+    ];
+    assert!(point_in_polygon(Point2::new(0.5, 2.0), &u));
+    assert!(!point_in_polygon(Point2::new(1.5, 2.0), &u));
+    assert!(point_in_polygon(Point2::new(1.5, 0.5), &u));
+}
+
+#[test]
+fn measures_polygons() {
+    let square = square();
+    assert_near(polygon_signed_area(&square), 4.0);
+    let reversed: Vec<_> = square.iter().rev().cloned().collect();
+    assert_near(polygon_signed_area(&reversed), -4.0);
+    assert_near(polygon_area(&reversed), 4.0);
+
+    assert_point(polygon_centroid(&square).unwrap(), Point2::new(1.0, 1.0));
+    assert_point(polygon_centroid(&reversed).unwrap(), Point2::new(1.0, 1.0));
+
+    let triangle = vec![Point2::new(0.0, 0.0), Point2::new(3.0, 0.0), Point2::new(0.0, 3.0)];
+    assert_near(polygon_area(&triangle), 4.5);
+    assert_point(polygon_centroid(&triangle).unwrap(), Point2::new(1.0, 1.0));
+
+    let line = vec![Point2::new(0.0, 0.0), Point2::new(1.0, 1.0), Point2::new(2.0, 2.0)];
+    assert_eq!(polygon_centroid(&line), None);
+    assert_eq!(polygon_area::<f64>(&[]), 0.0);
+}
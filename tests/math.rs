@@ -0,0 +1,188 @@
+extern crate cgmath;
+extern crate nalgebra;
+extern crate skunkworks;
+
+use std::f64::consts::PI;
+
+use skunkworks::boids::{Flock, FlockParams};
+use skunkworks::rng::SimRng;
+use skunkworks::{bearing_to_target, limit_vector2, rotate_vector2, LimitMode, Point2, Vec2};
+
+const EPSILON: f64 = 1e-9;
+
+fn assert_close(actual: Vec2<f64>, expected: Vec2<f64>) {
+    assert!(
+        (actual - expected).magnitude() < EPSILON,
+        "{:?} is not close to {:?}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn vector_arithmetic() {
+    let a = Vec2::new(1.0, 2.0);
+    let b = Vec2::new(3.0, -4.0);
+    assert_eq!(a + b, Vec2::new(4.0, -2.0));
+    assert_eq!(a - b, Vec2::new(-2.0, 6.0));
+    assert_eq!(-a, Vec2::new(-1.0, -2.0));
+    assert_eq!(a * 2.0, Vec2::new(2.0, 4.0));
+    assert_eq!(2.0 * a, Vec2::new(2.0, 4.0));
+    assert_eq!(b / 2.0, Vec2::new(1.5, -2.0));
+
+    let mut c = a;
+    c += b;
+    assert_eq!(c, Vec2::new(4.0, -2.0));
+    c -= a;
+    assert_eq!(c, b);
+    c *= 3.0;
+    assert_eq!(c, Vec2::new(9.0, -12.0));
+    c /= 3.0;
+    assert_eq!(c, b);
+}
+
+#[test]
+fn integer_vectors() {
+    let a: Vec2<i32> = Vec2::new(3, 4);
+    assert_eq!(a + Vec2::new(1, 1), Vec2::new(4, 5));
+    assert_eq!(a.magnitude_squared(), 25);
+    assert_eq!(Vec2::<i32>::zero(), Vec2::new(0, 0));
+}
+
+#[test]
+fn point_arithmetic() {
+    let p = Point2::new(1.0, 1.0);
+    let q = Point2::new(4.0, 5.0);
+    assert_eq!(q - p, Vec2::new(3.0, 4.0));
+    assert_eq!(p + Vec2::new(3.0, 4.0), q);
+    assert_eq!(q - Vec2::new(3.0, 4.0), p);
+    assert_eq!(p.distance(q), 5.0);
+    assert_eq!(p.distance_squared(q), 25.0);
+    assert_eq!(p.lerp(q, 0.5), Point2::new(2.5, 3.0));
+
+    let mut r = p;
+    r += Vec2::new(1.0, 2.0);
+    assert_eq!(r, Point2::new(2.0, 3.0));
+    r -= Vec2::new(2.0, 3.0);
+    assert_eq!(r, Point2::origin());
+    assert_eq!(q.to_vec(), Vec2::new(4.0, 5.0));
+    assert_eq!(Point2::from_vec(Vec2::new(4.0, 5.0)), q);
+}
+
+#[test]
+fn products_and_lengths() {
+    let a = Vec2::new(3.0, 4.0);
+    let b = Vec2::new(-4.0, 3.0);
+    assert_eq!(a.dot(b), 0.0);
+    assert_eq!(a.perp_dot(b), 25.0);
+    assert_eq!(b.perp_dot(a), -25.0);
+    assert_eq!(a.magnitude(), 5.0);
+    assert_eq!(a.magnitude_squared(), 25.0);
+    assert_close(a.normalize(), Vec2::new(0.6, 0.8));
+    assert!(Vec2::<f64>::zero().normalize().x.is_nan());
+    assert_close(a.lerp(b, 0.5), Vec2::new(-0.5, 3.5));
+}
+
+#[test]
+fn angles() {
+    assert_close(Vec2::from_angle(0.0), Vec2::new(1.0, 0.0));
+    assert_close(Vec2::from_angle(PI / 2.0), Vec2::new(0.0, 1.0));
+    assert!((Vec2::new(-1.0, 0.0).angle() - PI).abs() < EPSILON);
+    assert!((Vec2::new(0.0, -2.0).angle() + PI / 2.0).abs() < EPSILON);
+}
+
+#[test]
+fn nalgebra_conversions() {
+    let vector: nalgebra::Vector2<f32> = Vec2::new(1.0, 2.0).into();
+    assert_eq!(vector, nalgebra::Vector2::new(1.0, 2.0));
+    assert_eq!(Vec2::from(vector), Vec2::new(1.0, 2.0));
+
+    let point: nalgebra::Point2<f32> = Point2::new(3.0, 4.0).into();
+    assert_eq!(point, nalgebra::Point2::new(3.0, 4.0));
+    assert_eq!(Point2::from(point), Point2::new(3.0, 4.0));
+}
+
+#[test]
+fn cgmath_conversions() {
+    let vector: cgmath::Vector2<f64> = Vec2::new(1.0, 2.0).into();
+    assert_eq!(vector, cgmath::Vector2::new(1.0, 2.0));
+    assert_eq!(Vec2::from(vector), Vec2::new(1.0, 2.0));
+
+    let point: cgmath::Point2<f64> = Point2::new(3.0, 4.0).into();
+    assert_eq!(point, cgmath::Point2::new(3.0, 4.0));
+    assert_eq!(Point2::from(point), Point2::new(3.0, 4.0));
+}
+
+#[test]
+fn helpers_take_library_types() {
+    assert_eq!(limit_vector2(1.0, Vec2::new(3.0, -0.5)), Vec2::new(1.0, -0.5));
+
+    // Bearings are clockwise from straight up in screen coordinates.
+    let origin: Point2<f64> = Point2::origin();
+    assert!(bearing_to_target(origin, Point2::new(0.0, -1.0)).abs() < EPSILON);
+    assert!((bearing_to_target(origin, Point2::new(1.0, 0.0)) - PI / 2.0).abs() < EPSILON);
+
+    let mut vector = Vec2::new(0.0, 2.0);
+    rotate_vector2(&mut vector, 0.0);
+    assert_close(vector, Vec2::new(2.0, 0.0));
+}
+
+#[test]
+fn magnitude_limits() {
+    let diagonal: Vec2<f64> = Vec2::new(3.0, 3.0);
+    let limited = diagonal.limit(1.0);
+    assert!((limited.magnitude() - 1.0).abs() < EPSILON);
+    assert_close(limited.normalize(), diagonal.normalize());
+    assert_eq!(Vec2::new(0.3, 0.4).limit(1.0), Vec2::new(0.3, 0.4));
+
+    assert_close(Vec2::new(3.0, 4.0).with_magnitude(10.0), Vec2::new(6.0, 8.0));
+    assert_close(Vec2::new(3.0, 4.0).with_magnitude(-5.0), Vec2::new(-3.0, -4.0));
+    assert_eq!(Vec2::<f64>::zero().with_magnitude(2.0), Vec2::zero());
+
+    let short = Vec2::new(0.0, 0.5);
+    assert_close(short.clamp_magnitude(1.0, 2.0), Vec2::new(0.0, 1.0));
+    assert_close(Vec2::new(0.0, 5.0).clamp_magnitude(1.0, 2.0), Vec2::new(0.0, 2.0));
+    assert_eq!(Vec2::new(0.0, 1.5).clamp_magnitude(1.0, 2.0), Vec2::new(0.0, 1.5));
+    assert_eq!(Vec2::<f64>::zero().clamp_magnitude(1.0, 2.0), Vec2::zero());
+}
+
+// Per-axis clamping lets diagonals through at √2 times the limit.
+#[test]
+fn limit_modes() {
+    let diagonal = Vec2::new(3.0, 3.0);
+    assert_eq!(LimitMode::PerAxis.apply(1.0, diagonal), Vec2::new(1.0, 1.0));
+    assert_close(
+        LimitMode::Magnitude.apply(1.0, diagonal),
+        Vec2::new(0.5f64.sqrt(), 0.5f64.sqrt()),
+    );
+    assert_eq!(LimitMode::default(), LimitMode::Magnitude);
+}
+
+fn fastest_boid(limit_mode: LimitMode) -> (f64, f64) {
+    let mut rng = SimRng::new(7);
+    let params = FlockParams {
+        width: 640.0,
+        height: 480.0,
+        limit_mode,
+        ..Default::default()
+    };
+    let max_speed = params.max_speed;
+    let mut flock = Flock::new_random(40, params, &mut rng);
+    let mut fastest = 0.0f64;
+    for _ in 0..120 {
+        flock.update(&mut rng);
+        for vehicle in &flock.vehicles {
+            fastest = fastest.max(vehicle.borrow().velocity.magnitude());
+        }
+    }
+    (fastest, max_speed)
+}
+
+#[test]
+fn steering_respects_max_speed() {
+    let (fastest, max_speed) = fastest_boid(LimitMode::Magnitude);
+    assert!(fastest <= max_speed + EPSILON, "{} > {}", fastest, max_speed);
+
+    let (fastest, max_speed) = fastest_boid(LimitMode::PerAxis);
+    assert!(fastest <= max_speed * 2f64.sqrt() + EPSILON);
+}
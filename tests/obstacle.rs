@@ -0,0 +1,205 @@
+extern crate serde_json;
+extern crate skunkworks;
+
+use skunkworks::boids::{Flock, FlockParams, Vehicle};
+use skunkworks::obstacle::{avoid_obstacles, avoidance_force, follow_walls, Avoidance, Obstacle};
+use skunkworks::rng::SimRng;
+use skunkworks::{actor_at_waypoint, move_around_obstacles, Actor, Point2, Vec2, Waypoint};
+
+fn circle(x: f64, y: f64, radius: f64) -> Obstacle {
+    Obstacle::Circle {
+        center: Point2::new(x, y),
+        radius,
+    }
+}
+
+fn square(x: f64, y: f64, size: f64) -> Obstacle {
+    Obstacle::Polygon {
+        points: vec![
+            Point2::new(x, y),
+            Point2::new(x + size, y),
+            Point2::new(x + size, y + size),
+            Point2::new(x, y + size),
+        ],
+    }
+}
+
+fn wall(start: (f64, f64), end: (f64, f64)) -> Obstacle {
+    Obstacle::Wall {
+        start: Point2::new(start.0, start.1),
+        end: Point2::new(end.0, end.1),
+    }
+}
+
+// Heading along +x at 2 units per tick, so the box reaches 80 ahead.
+fn force(obstacles: &[Obstacle]) -> Vec2<f64> {
+    avoid_obstacles(Point2::origin(), Vec2::new(2.0, 0.0), obstacles, &Avoidance::default())
+}
+
+#[test]
+fn steers_round_circles_ahead() {
+    let ahead = force(&[circle(50.0, 5.0, 10.0)]);
+    assert!(ahead.y < 0.0, "should turn away from the side it's on: {:?}", ahead);
+    assert!(ahead.x < 0.0, "should brake: {:?}", ahead);
+
+    let mirrored = force(&[circle(50.0, -5.0, 10.0)]);
+    assert!((mirrored.y + ahead.y).abs() < 1e-9);
+
+    // Closer and more squarely in the way pushes harder.
+    assert!(force(&[circle(30.0, 5.0, 10.0)]).y < ahead.y);
+    assert!(force(&[circle(50.0, 1.0, 10.0)]).y.abs() > ahead.y.abs());
+}
+
+#[test]
+fn ignores_circles_out_of_the_box() {
+    assert_eq!(force(&[circle(-50.0, 0.0, 10.0)]), Vec2::zero());
+    assert_eq!(force(&[circle(50.0, 40.0, 10.0)]), Vec2::zero());
+    assert_eq!(force(&[circle(120.0, 0.0, 10.0)]), Vec2::zero());
+
+    // Faster agents look further ahead.
+    let fast = avoid_obstacles(
+        Point2::origin(),
+        Vec2::new(5.0, 0.0),
+        &[circle(120.0, 0.0, 10.0)],
+        &Avoidance::default(),
+    );
+    assert!(fast != Vec2::zero());
+}
+
+#[test]
+fn reacts_to_the_nearest_obstacle() {
+    let near = circle(40.0, 5.0, 10.0);
+    let far = circle(70.0, -5.0, 10.0);
+    assert_eq!(force(&[far.clone(), near.clone()]), force(&[near]));
+}
+
+#[test]
+fn works_in_any_direction() {
+    let obstacles = [circle(5.0, 50.0, 10.0)];
+    let avoidance = Avoidance::default();
+    let down = avoid_obstacles(Point2::origin(), Vec2::new(0.0, 2.0), &obstacles, &avoidance);
+    assert!(down.x < 0.0 && down.y < 0.0, "{:?}", down);
+    let still = avoid_obstacles(Point2::origin(), Vec2::zero(), &obstacles, &avoidance);
+    assert_eq!(still, Vec2::zero());
+}
+
+#[test]
+fn steers_round_polygons() {
+    let ahead = force(&[square(40.0, -2.0, 20.0)]);
+    assert!(ahead.y < 0.0 && ahead.x < 0.0, "{:?}", ahead);
+    // Only a corner pokes into the box.
+    assert!(force(&[square(40.0, -25.0, 20.0)]).y > 0.0);
+    assert_eq!(force(&[square(40.0, 20.0, 20.0)]), Vec2::zero());
+    assert_eq!(force(&[square(-40.0, -10.0, 20.0)]), Vec2::zero());
+}
+
+#[test]
+fn slides_along_walls() {
+    let walls = [wall((-100.0, 20.0), (100.0, 20.0))];
+    let avoidance = Avoidance::default();
+    let heading_in = follow_walls(Point2::origin(), Vec2::new(1.0, 1.0), &walls, &avoidance);
+    assert!(heading_in.y < 0.0, "{:?}", heading_in);
+    assert!(heading_in.x.abs() < 1e-9, "only pushes along the normal: {:?}", heading_in);
+
+    let alongside = follow_walls(Point2::origin(), Vec2::new(1.0, 0.0), &walls, &avoidance);
+    assert_eq!(alongside, Vec2::zero());
+
+    // Walls are only felt for, never swept for.
+    assert_eq!(force(&[wall((30.0, -30.0), (30.0, 30.0))]), Vec2::zero());
+    let obstacles = [wall((30.0, -30.0), (30.0, 30.0))];
+    let total = avoidance_force(Point2::origin(), Vec2::new(2.0, 0.0), &obstacles, &avoidance);
+    assert!(total.x < 0.0, "{:?}", total);
+}
+
+#[test]
+fn actors_walk_round_obstacles() {
+    let rock = circle(200.0, 10.0, 40.0).cast();
+    let mut actor: Actor = Actor {
+        position: Point2::origin(),
+        speed: 100.0,
+        waypoints: vec![Waypoint::new(400.0, 0.0)],
+    };
+    // Left to its defaults, everything is in f32 like the actor.
+    let avoidance: Avoidance = Avoidance {
+        radius: 10.0,
+        ..Avoidance::for_actors()
+    };
+    let obstacles = vec![rock];
+    let mut arrived = false;
+    for _ in 0..600 {
+        move_around_obstacles(&mut actor, &obstacles, &avoidance, &(1.0 / 60.0));
+        assert!(actor.position.distance(Point2::new(200.0, 10.0)) > 40.0, "{:?}", actor.position);
+        if actor_at_waypoint(&actor) {
+            arrived = true;
+            break;
+        }
+    }
+    assert!(arrived, "stuck at {:?}", actor.position);
+}
+
+// How near any boid's centre comes to the middle of the area, once the flock
+// has settled.
+fn closest_boid(obstacles: Vec<Obstacle>) -> f64 {
+    let mut rng = SimRng::new(11);
+    let params = FlockParams {
+        width: 640.0,
+        height: 480.0,
+        ..Default::default()
+    };
+    let mut flock = Flock::new_random(40, params, &mut rng);
+    let center = Point2::new(320.0, 240.0);
+    flock.obstacles = obstacles;
+    let mut closest = std::f64::INFINITY;
+    for tick in 0..1200 {
+        flock.update(&mut rng);
+        if tick >= 120 {
+            for vehicle in &flock.vehicles {
+                closest = closest.min(vehicle.borrow().location.distance(center));
+            }
+        }
+    }
+    closest
+}
+
+#[test]
+fn flocks_swim_round_obstacles() {
+    let free = closest_boid(Vec::new());
+    let avoiding = closest_boid(vec![circle(320.0, 240.0, 80.0)]);
+    assert!(free < 10.0);
+    // Boids may brush the edge, but never get further in than their own size.
+    let size = Avoidance::<f64>::default().radius;
+    assert!(avoiding > 80.0 - size, "came within {} of the middle", avoiding);
+}
+
+#[test]
+fn obstacles_load_from_json() {
+    let json = r#"[
+        { "circle": { "center": { "x": 1.0, "y": 2.0 }, "radius": 3.0 } },
+        { "wall": { "start": { "x": 0.0, "y": 0.0 }, "end": { "x": 5.0, "y": 0.0 } } }
+    ]"#;
+    let obstacles: Vec<Obstacle> = serde_json::from_str(json).unwrap();
+    assert_eq!(obstacles[0], circle(1.0, 2.0, 3.0));
+    assert_eq!(obstacles[1], wall((0.0, 0.0), (5.0, 0.0)));
+    let single: Obstacle<f32> = obstacles[0].cast();
+    assert_eq!(single.cast::<f64>(), obstacles[0]);
+}
+
+#[test]
+fn boids_keep_their_other_forces_while_avoiding() {
+    let params = FlockParams {
+        wander_weight: 0.0,
+        ..Default::default()
+    };
+    // Only just clipping the far end of the detection box, so avoiding it
+    // leaves room for other forces.
+    let obstacles = [circle(84.0, 31.0, 20.0)];
+    let mut rng = SimRng::new(1);
+    let mut plain = Vehicle::new(Vec2::new(2.0, 0.0), Point2::origin(), &params);
+    let mut pushed = plain.clone();
+    // Something else is already pushing this one down the screen.
+    pushed.apply_force(Vec2::new(0.0, params.max_force));
+    plain.update(&[], &obstacles, &params, &mut rng);
+    pushed.update(&[], &obstacles, &params, &mut rng);
+    assert!(plain.velocity.y < 0.0, "{:?}", plain.velocity);
+    assert!((pushed.velocity.y - plain.velocity.y - params.max_force).abs() < 1e-9);
+}
@@ -0,0 +1,223 @@
+extern crate skunkworks;
+
+use std::f64::consts::PI;
+use std::fs::File;
+
+use skunkworks::orca::{move_crowd, orca_velocities, orca_velocity, OrcaAgent, OrcaParams};
+use skunkworks::rng::SimRng;
+use skunkworks::simulation::{Scenario, SimEvent, Simulation};
+use skunkworks::spatial::SpatialGrid;
+use skunkworks::{actor_at_waypoint, Actor, Point2, Vec2, Waypoint};
+
+const DELTA_T: f32 = 1.0 / 60.0;
+const RADIUS: f32 = 10.0;
+
+fn agent(x: f32, y: f32, preferred: Vec2<f32>) -> OrcaAgent {
+    OrcaAgent {
+        position: Point2::new(x, y),
+        velocity: preferred,
+        preferred_velocity: preferred,
+        radius: RADIUS,
+        max_speed: 100.0,
+        id: 0,
+    }
+}
+
+// Closest any two actors came, measured between their edges.
+fn closest_approach(actors: &[Actor]) -> f32 {
+    let mut closest = std::f32::INFINITY;
+    for (index, a) in actors.iter().enumerate() {
+        for b in &actors[index + 1..] {
+            closest = closest.min(a.position.distance(b.position) - RADIUS * 2.0);
+        }
+    }
+    closest
+}
+
+#[test]
+fn grid_finds_the_same_points_as_a_brute_force_search() {
+    let mut rng = SimRng::new(5);
+    let points: Vec<Point2<f32>> = (0..300)
+        .map(|_| {
+            Point2::new(
+                rng.gen_range(-500.0, 500.0) as f32,
+                rng.gen_range(-500.0, 500.0) as f32,
+            )
+        })
+        .collect();
+    let grid = SpatialGrid::from_points(50.0, points.iter().cloned());
+    for &(center, radius) in [
+        (Point2::new(0.0, 0.0), 75.0),
+        (Point2::new(-480.0, 333.0), 120.0),
+        (Point2::new(10.0, -10.0), 5.0),
+    ].iter()
+    {
+        let mut found = grid.within(center, radius);
+        found.sort();
+        let expected: Vec<usize> = points
+            .iter()
+            .enumerate()
+            .filter(|&(_, point)| point.distance(center) <= radius)
+            .map(|(index, _)| index)
+            .collect();
+        assert_eq!(found, expected);
+    }
+}
+
+#[test]
+fn grid_can_be_refilled() {
+    let mut grid = SpatialGrid::new(10.0);
+    grid.insert(7, Point2::new(1.0, 1.0));
+    assert_eq!(grid.within(Point2::origin(), 2.0), vec![7]);
+    grid.clear();
+    assert!(grid.within(Point2::origin(), 2.0).is_empty());
+}
+
+#[test]
+fn unobstructed_agents_keep_their_preferred_velocity() {
+    let params = OrcaParams::default();
+    let alone = agent(0.0, 0.0, Vec2::new(50.0, 0.0));
+    assert_eq!(orca_velocity(&alone, &[], &params, DELTA_T), Vec2::new(50.0, 0.0));
+
+    let too_fast = OrcaAgent {
+        preferred_velocity: Vec2::new(300.0, 400.0),
+        ..alone
+    };
+    let velocity = orca_velocity(&too_fast, &[], &params, DELTA_T);
+    assert!((velocity.magnitude() - 100.0).abs() < 1e-3);
+
+    // Walking side by side in the same direction needs no change.
+    let beside = agent(0.0, 40.0, Vec2::new(50.0, 0.0));
+    assert_eq!(orca_velocity(&alone, &[&beside], &params, DELTA_T), Vec2::new(50.0, 0.0));
+}
+
+#[test]
+fn head_on_agents_both_give_way() {
+    let params = OrcaParams::default();
+    let agents = [
+        agent(0.0, 0.0, Vec2::new(100.0, 0.0)),
+        agent(60.0, 0.0, Vec2::new(-100.0, 0.0)),
+    ];
+    let velocities = orca_velocities(&agents, &params, DELTA_T);
+    // Each turns to its own right, and they share the effort equally.
+    assert!(velocities[0].y != 0.0);
+    assert!((velocities[0].y + velocities[1].y).abs() < 1e-3);
+    assert!((velocities[0].x + velocities[1].x).abs() < 1e-3);
+}
+
+#[test]
+fn coincident_agents_split_apart() {
+    let params = OrcaParams::default();
+    let first = agent(50.0, 50.0, Vec2::zero());
+    let second = OrcaAgent { id: 1, ..first };
+    let velocities = orca_velocities(&[first, second], &params, DELTA_T);
+    assert!(velocities[0].x.is_finite() && velocities[0].y.is_finite());
+    assert!(velocities[0].x < 0.0 && velocities[1].x > 0.0, "{:?}", velocities);
+    // The ids decide who goes which way, not where the agents are stored.
+    let swapped = orca_velocities(&[second, first], &params, DELTA_T);
+    assert_eq!((swapped[0], swapped[1]), (velocities[1], velocities[0]));
+    let alone = orca_velocity(&first, &[&second], &params, DELTA_T);
+    assert_eq!(alone, velocities[0]);
+
+    // Actors spawned on the same spot, all starting from a standstill.
+    let mut actors: Vec<Actor> = (0..3)
+        .map(|_| Actor {
+            position: Point2::new(100.0, 100.0),
+            speed: 100.0,
+            waypoints: vec![Waypoint::new(300.0, 100.0)],
+        })
+        .collect();
+    let mut velocities = vec![Vec2::zero(); 3];
+    for _ in 0..120 {
+        move_crowd(&mut actors, &mut velocities, RADIUS, &params, DELTA_T);
+    }
+    for actor in &actors {
+        assert!(actor.position.x.is_finite() && actor.position.y.is_finite());
+    }
+    // Pushed apart until they no longer overlap.
+    assert!(closest_approach(&actors) > -0.5, "{}", closest_approach(&actors));
+}
+
+#[test]
+fn neighbours_beyond_range_are_ignored() {
+    let params = OrcaParams {
+        neighbor_distance: 50.0,
+        ..Default::default()
+    };
+    let agents = [
+        agent(0.0, 0.0, Vec2::new(100.0, 0.0)),
+        agent(100.0, 0.0, Vec2::new(-100.0, 0.0)),
+    ];
+    let velocities = orca_velocities(&agents, &params, DELTA_T);
+    assert_eq!(velocities[0], Vec2::new(100.0, 0.0));
+}
+
+// Agents spaced round a circle all head for the opposite side, the classic
+// ORCA benchmark. The spacing is a little uneven, as a perfectly symmetric
+// crowd is the worst case for it.
+#[test]
+fn crowds_swap_places_without_touching() {
+    let count = 12;
+    let mut rng = SimRng::new(9);
+    let mut actors: Vec<Actor> = (0..count)
+        .map(|index| {
+            let jitter = rng.gen_range(-0.05, 0.05);
+            let angle = 2.0 * PI * (f64::from(index) + jitter) / f64::from(count);
+            let (x, y) = ((200.0 * angle.cos()) as f32, (200.0 * angle.sin()) as f32);
+            Actor {
+                position: Point2::new(x, y),
+                speed: 100.0,
+                waypoints: vec![Waypoint::new(-x, -y)],
+            }
+        })
+        .collect();
+    let mut velocities = vec![Vec2::zero(); actors.len()];
+    let params = OrcaParams::default();
+
+    for _ in 0..1200 {
+        move_crowd(&mut actors, &mut velocities, RADIUS, &params, DELTA_T);
+        assert!(closest_approach(&actors) > -0.5, "actors overlapped");
+        for actor in &mut actors {
+            if actor_at_waypoint(actor) {
+                actor.waypoints.clear();
+            }
+        }
+    }
+    assert!(actors.iter().all(|actor| actor.waypoints.is_empty()), "not everyone arrived");
+}
+
+fn run_crossing(orca: bool) -> (f32, usize) {
+    let file = File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/scenarios/crossing.json"));
+    let mut scenario = Scenario::from_json(file.unwrap()).unwrap();
+    if !orca {
+        scenario.orca = None;
+    }
+    let mut simulation = Simulation::new(&scenario, 1);
+    let mut closest = std::f32::INFINITY;
+    let mut finished = 0;
+    for _ in 0..scenario.ticks {
+        for event in simulation.step() {
+            if let SimEvent::RouteFinished { .. } = event {
+                finished += 1;
+            }
+        }
+        let actors: Vec<Actor> = simulation
+            .actors
+            .iter()
+            .map(|sim_actor| sim_actor.actor.clone())
+            .collect();
+        closest = closest.min(closest_approach(&actors));
+    }
+    (closest, finished)
+}
+
+#[test]
+fn scenarios_can_turn_avoidance_on() {
+    let (closest, finished) = run_crossing(false);
+    assert!(closest < -RADIUS, "walked straight through each other");
+    assert_eq!(finished, 4);
+
+    let (closest, finished) = run_crossing(true);
+    assert!(closest > -0.5, "overlapped by {}", -closest);
+    assert_eq!(finished, 4);
+}
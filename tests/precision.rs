@@ -0,0 +1,129 @@
+// Runs the same movement and steering on f32 and f64 and checks both paths
+// agree to within f32 precision.
+
+extern crate serde_json;
+extern crate skunkworks;
+
+use skunkworks::boids::{Flock, FlockParams};
+use skunkworks::replay::StateHash;
+use skunkworks::rng::SimRng;
+use skunkworks::{actor_at_waypoint, limit_vector2, move_towards_next_waypoint, Actor, Point2,
+                 Real, Vec2, Waypoint};
+
+const SEED: u64 = 42;
+const DELTA_T: f64 = 1.0 / 60.0;
+
+fn patrol<N: Real>() -> Actor<N> {
+    let waypoint = |x: f64, y: f64| Waypoint::new(N::from_f64(x), N::from_f64(y));
+    Actor {
+        position: Point2::new(N::from_f64(20.0), N::from_f64(20.0)),
+        speed: N::from_f64(100.0),
+        waypoints: vec![
+            waypoint(120.0, 30.0),
+            waypoint(280.0, 250.0),
+            waypoint(230.0, 440.0),
+        ],
+    }
+}
+
+// Walks the route like the waypoint example and returns the position after
+// every tick.
+fn walk<N: Real>(ticks: usize) -> Vec<Point2<f64>> {
+    let mut actor = patrol::<N>();
+    let delta_t = N::from_f64(DELTA_T);
+    let mut trail = Vec::new();
+    for _ in 0..ticks {
+        if actor_at_waypoint(&actor) {
+            let reached = actor.waypoints.remove(0);
+            actor.waypoints.push(reached);
+        }
+        move_towards_next_waypoint(&mut actor, &delta_t);
+        trail.push(actor.position.cast());
+    }
+    trail
+}
+
+fn run_flock<N: Real>(ticks: usize) -> Flock<N> {
+    let mut rng = SimRng::new(SEED);
+    let params = FlockParams {
+        width: N::from_f64(640.0),
+        height: N::from_f64(480.0),
+        ..Default::default()
+    };
+    let mut flock = Flock::new_random(30, params, &mut rng);
+    for _ in 0..ticks {
+        flock.update(&mut rng);
+    }
+    flock
+}
+
+#[test]
+fn actors_move_the_same_in_both_precisions() {
+    let single = walk::<f32>(600);
+    let double = walk::<f64>(600);
+    for (tick, (a, b)) in single.iter().zip(&double).enumerate() {
+        assert!(a.distance(*b) < 0.05, "tick {}: {:?} vs {:?}", tick, a, b);
+    }
+}
+
+#[test]
+fn actors_reach_their_waypoints() {
+    let mut actor = patrol::<f32>();
+    let delta_t = DELTA_T as f32;
+    for _ in 0..600 {
+        if actor_at_waypoint(&actor) {
+            actor.waypoints.remove(0);
+        }
+        if actor.waypoints.is_empty() {
+            break;
+        }
+        move_towards_next_waypoint(&mut actor, &delta_t);
+    }
+    assert!(actor.waypoints.is_empty());
+    assert!(actor.position.distance(Point2::new(230.0, 440.0)) < 1.0);
+}
+
+#[test]
+fn flocks_start_the_same_in_both_precisions() {
+    let single = run_flock::<f32>(0);
+    let double = run_flock::<f64>(0);
+    for (a, b) in single.vehicles.iter().zip(&double.vehicles) {
+        let (a, b) = (a.borrow(), b.borrow());
+        assert!(a.location.cast::<f64>().distance(b.location) < 1e-3);
+        assert!((a.velocity.cast::<f64>() - b.velocity).magnitude() < 1e-5);
+    }
+}
+
+// Flocking is chaotic, so the paths only stay together for a short while.
+#[test]
+fn flocks_steer_the_same_in_both_precisions() {
+    let single = run_flock::<f32>(20);
+    let double = run_flock::<f64>(20);
+    for (a, b) in single.vehicles.iter().zip(&double.vehicles) {
+        let (a, b) = (a.borrow(), b.borrow());
+        assert!(a.location.cast::<f64>().distance(b.location) < 0.01);
+        assert!(a.velocity.magnitude() <= a.max_speed + 1e-4);
+    }
+}
+
+#[test]
+fn runs_are_deterministic_in_both_precisions() {
+    assert_eq!(run_flock::<f32>(60).checksum(), run_flock::<f32>(60).checksum());
+    assert_eq!(run_flock::<f64>(60).checksum(), run_flock::<f64>(60).checksum());
+}
+
+#[test]
+fn limits_in_both_precisions() {
+    assert_eq!(limit_vector2(1.0f32, Vec2::new(2.0, -0.5)), Vec2::new(1.0, -0.5));
+    assert_eq!(limit_vector2(1.0f64, Vec2::new(2.0, -0.5)), Vec2::new(1.0, -0.5));
+}
+
+#[test]
+fn flock_params_load_in_both_precisions() {
+    let json = r#"{ "width": 800.0, "max_speed": 3.0 }"#;
+    let single: FlockParams<f32> = serde_json::from_str(json).unwrap();
+    let double: FlockParams<f64> = serde_json::from_str(json).unwrap();
+    assert_eq!(single.width, 800.0);
+    assert_eq!(double.max_speed, 3.0);
+    assert_eq!(double.height, FlockParams::<f64>::default().height);
+}
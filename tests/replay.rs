@@ -0,0 +1,148 @@
+extern crate serde_json;
+extern crate skunkworks;
+
+use serde_json::Value;
+
+use skunkworks::replay::{Divergence, InputEvent, InputLog, Mismatch, Recorder, Replayer,
+                         StateHasher};
+
+// Stands in for a game: its state is whatever the inputs so far add up to.
+#[derive(Default)]
+struct Counter {
+    total: i64,
+}
+
+impl Counter {
+    fn handle(&mut self, input: &InputEvent) {
+        if let InputEvent::MouseMotion { xrel, yrel, .. } = *input {
+            self.total += i64::from(xrel) * 7 + i64::from(yrel);
+        }
+    }
+
+    fn update(&mut self) {
+        self.total = self.total * 3 + 1;
+    }
+
+    fn checksum(&self) -> u64 {
+        let mut hasher = StateHasher::new();
+        hasher.write_u64(self.total as u64);
+        hasher.finish()
+    }
+}
+
+fn motion(xrel: i32, yrel: i32) -> InputEvent {
+    InputEvent::MouseMotion {
+        x: 0,
+        y: 0,
+        xrel,
+        yrel,
+        buttons: 0,
+    }
+}
+
+fn record(ticks: i32) -> InputLog {
+    let mut recorder = Recorder::new(42, Value::from("bounce"));
+    let mut counter = Counter::default();
+    for tick in 0..ticks {
+        // Some ticks get no input at all.
+        for input in (0..tick % 3).map(|index| motion(tick, index)) {
+            counter.handle(&input);
+            recorder.record(input);
+        }
+        counter.update();
+        recorder.end_tick(counter.checksum());
+    }
+    recorder.into_log()
+}
+
+// Plays `log` back, stopping at the first divergence.
+fn replay(log: InputLog) -> Result<usize, Divergence> {
+    let mut replayer = Replayer::new(log);
+    let mut counter = Counter::default();
+    while !replayer.is_finished() {
+        for input in replayer.inputs() {
+            counter.handle(input);
+        }
+        counter.update();
+        replayer.end_tick(counter.checksum())?;
+    }
+    Ok(replayer.tick())
+}
+
+#[test]
+fn recordings_replay_through_json() {
+    let log = record(20);
+    assert_eq!(log.seed, 42);
+    assert_eq!(log.ticks.len(), 20);
+    assert!(log.ticks[0].inputs.is_empty());
+    assert_eq!(log.ticks[5].inputs, vec![motion(5, 0), motion(5, 1)]);
+
+    let mut json = Vec::new();
+    log.to_json(&mut json).unwrap();
+    let loaded = InputLog::from_json(&json[..]).unwrap();
+    assert_eq!(Replayer::new(loaded.clone()).seed(), 42);
+    assert_eq!(replay(loaded), Ok(20));
+}
+
+#[test]
+fn tampered_checksums_diverge_at_their_tick() {
+    let mut log = record(20);
+    let expected = log.ticks[7].checksum ^ 1;
+    log.ticks[7].checksum = expected;
+    let actual = record(8).ticks[7].checksum;
+    assert_eq!(
+        replay(log),
+        Err(Divergence {
+            tick: 7,
+            expected,
+            actual,
+        })
+    );
+
+    // So does a changed input, from the tick it was fed in.
+    let mut log = record(20);
+    log.ticks[13].inputs[0] = motion(0, 0);
+    assert_eq!(replay(log).map_err(|divergence| divergence.tick), Err(13));
+}
+
+#[test]
+fn replaying_past_the_end_changes_nothing() {
+    let mut replayer = Replayer::new(record(2));
+    replayer.end_tick(0).unwrap_err();
+    replayer.end_tick(0).unwrap_err();
+    assert!(replayer.is_finished());
+    assert!(replayer.inputs().is_empty());
+    assert_eq!(replayer.end_tick(0), Ok(()));
+    assert_eq!(replayer.tick(), 2);
+    assert!(InputLog::from_json(&b"{\"seed\": 1}"[..]).is_err());
+}
+
+#[test]
+fn replays_must_start_like_the_recording() {
+    let mut json = Vec::new();
+    record(3).to_json(&mut json).unwrap();
+    let log = InputLog::from_json(&json[..]).unwrap();
+    assert_eq!(log.config, Value::from("bounce"));
+    assert_eq!(log.check_start(42, &Value::from("bounce")), Ok(()));
+
+    assert_eq!(
+        log.check_start(7, &Value::from("bounce")),
+        Err(Mismatch::Seed {
+            expected: 42,
+            actual: 7,
+        })
+    );
+    let mismatch = log.check_start(42, &Value::from("wrap")).unwrap_err();
+    assert_eq!(
+        mismatch,
+        Mismatch::Config {
+            expected: Value::from("bounce"),
+            actual: Value::from("wrap"),
+        }
+    );
+    assert!(mismatch.to_string().contains("\"bounce\""));
+
+    // Logs from before configs were stored have none.
+    let old = InputLog::from_json(&b"{\"seed\": 1, \"ticks\": []}"[..]).unwrap();
+    assert_eq!(old.check_start(1, &Value::Null), Ok(()));
+}
@@ -0,0 +1,82 @@
+extern crate skunkworks;
+
+use skunkworks::rewind::{History, Snapshot};
+use skunkworks::rng::SimRng;
+
+fn filled(capacity: usize, count: u32) -> History<u32> {
+    let mut history = History::new(capacity);
+    for snapshot in 0..count {
+        history.push(snapshot);
+    }
+    history
+}
+
+#[test]
+fn drops_the_oldest_snapshot_once_full() {
+    let mut history = filled(3, 5);
+    assert_eq!((history.len(), history.capacity()), (3, 3));
+    assert_eq!(history.current(), Some(&4));
+    assert_eq!(history.step_back(), Some(&3));
+    assert_eq!(history.step_back(), Some(&2));
+    assert_eq!(history.step_back(), None);
+
+    // A history always has room for one.
+    let mut tiny = History::new(0);
+    tiny.push(1);
+    tiny.push(2);
+    assert_eq!((tiny.len(), tiny.current()), (1, Some(&2)));
+}
+
+#[test]
+fn steps_stop_at_both_ends() {
+    let mut empty: History<u32> = History::new(4);
+    assert!(empty.is_empty());
+    assert_eq!(empty.current(), None);
+    assert_eq!(empty.step_back(), None);
+    assert_eq!(empty.step_forward(), None);
+
+    let mut history = filled(4, 3);
+    assert!(history.is_at_latest());
+    assert_eq!(history.step_forward(), None);
+    assert_eq!(history.step_back(), Some(&1));
+    assert_eq!(history.step_back(), Some(&0));
+    assert_eq!(history.step_back(), None);
+    assert_eq!(history.current(), Some(&0));
+    assert_eq!(history.steps_behind(), 2);
+    assert_eq!(history.step_forward(), Some(&1));
+    assert_eq!(history.step_forward(), Some(&2));
+    assert_eq!(history.step_forward(), None);
+    assert!(history.is_at_latest());
+}
+
+#[test]
+fn pushing_after_stepping_back_drops_the_redo_tail() {
+    let mut history = filled(5, 4);
+    history.step_back();
+    history.step_back();
+    history.push(10);
+    assert_eq!(history.len(), 3);
+    assert!(history.is_at_latest());
+    assert_eq!(history.step_back(), Some(&1));
+    assert_eq!(history.step_forward(), Some(&10));
+    assert_eq!(history.step_forward(), None);
+
+    history.clear();
+    assert!(history.is_empty());
+    history.push(7);
+    assert_eq!(history.current(), Some(&7));
+}
+
+#[test]
+fn restoring_a_snapshot_rewinds_the_state() {
+    let mut rng = SimRng::new(3);
+    let mut history = History::new(8);
+    let mut draws = Vec::new();
+    for _ in 0..4 {
+        history.push(rng.snapshot());
+        draws.push(rng.next_u32());
+    }
+    history.step_back();
+    rng.restore(history.current().unwrap());
+    assert_eq!(rng.next_u32(), draws[2]);
+}
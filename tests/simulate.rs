@@ -0,0 +1,137 @@
+extern crate serde_json;
+extern crate skunkworks;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use serde_json::Value;
+
+use skunkworks::simulation::{Scenario, SimEvent, Simulation};
+use skunkworks::Point2;
+
+// One actor walks two pixels east at a pixel a tick, the other stands still.
+const SCENARIO: &str = r#"{"ticks": 3, "delta_t": 0.5, "actors": [
+ {"name": "scout, east", "position": [0, 0], "speed": 2, "waypoints": [[2, 0]],
+  "looping": false},
+ {"name": "sentry", "position": [5, 5], "speed": 2}
+]}"#;
+
+fn scenario_file(name: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("skunkworks-{}-{}.json", name, std::process::id()));
+    fs::write(&path, SCENARIO).unwrap();
+    path
+}
+
+fn simulate(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_simulate"))
+        .args(args)
+        .output()
+        .expect("Could not run simulate")
+}
+
+fn stdout(output: &Output) -> String {
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn csv_has_a_row_per_actor_per_tick_and_per_event() {
+    let path = scenario_file("csv");
+    let output = simulate(&[path.to_str().unwrap(), "--seed", "7"]);
+    let csv = stdout(&output);
+    fs::remove_file(&path).unwrap();
+
+    let rows: Vec<&str> = csv.lines().collect();
+    assert_eq!(rows[0], "tick,time,entity,id,name,x,y,vx,vy,event,waypoint");
+    assert_eq!(rows.len(), 1 + 4 * 2 + 2);
+    assert_eq!(rows[1], r#"0,0,actor,0,"scout, east",0,0,0,0,,"#);
+    assert_eq!(rows[2], "0,0,actor,1,sentry,5,5,0,0,,");
+    assert_eq!(rows[3], r#"1,0.5,actor,0,"scout, east",1,0,2,0,,"#);
+    assert_eq!(rows[7], r#"3,1.5,actor,0,"scout, east",2,0,0,0,waypoint_reached,0"#);
+    assert_eq!(rows[8], r#"3,1.5,actor,0,"scout, east",2,0,0,0,route_finished,"#);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Seed: 7"));
+}
+
+#[test]
+fn json_lines_go_to_the_output_file() {
+    let path = scenario_file("jsonl");
+    let records = path.with_extension("jsonl");
+    let output = simulate(&[
+        "--format",
+        "jsonl",
+        path.to_str().unwrap(),
+        "--ticks",
+        "1",
+        "--output",
+        records.to_str().unwrap(),
+    ]);
+    assert_eq!(stdout(&output), "");
+    let lines = fs::read_to_string(&records).unwrap();
+    fs::remove_file(&path).unwrap();
+    fs::remove_file(&records).unwrap();
+
+    let rows: Vec<Value> = lines
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(rows.len(), 4);
+    assert_eq!(rows[0]["name"], "scout, east");
+    assert_eq!(rows[1]["name"], "sentry");
+    assert_eq!((rows[2]["tick"].as_u64(), rows[2]["x"].as_f64()), (Some(1), Some(1.0)));
+    assert_eq!(rows[2]["vx"].as_f64(), Some(2.0));
+    // Only events carry these.
+    assert!(rows[2].get("event").is_none() && rows[2].get("waypoint").is_none());
+}
+
+#[test]
+fn help_and_bad_arguments() {
+    let help = simulate(&["--help"]);
+    assert!(stdout(&help).starts_with("usage: simulate"));
+    assert!(stdout(&simulate(&["-h"])).starts_with("usage: simulate"));
+
+    let bad: [&[&str]; 6] = [
+        &[],
+        &["scenario.json", "--ticks"],
+        &["scenario.json", "--seed", "lucky"],
+        &["scenario.json", "--format", "xml"],
+        &["scenario.json", "--speed", "2"],
+        &["one.json", "two.json"],
+    ];
+    for args in bad.iter() {
+        let output = simulate(args);
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("usage: simulate"));
+    }
+
+    // Arguments are fine, the file isn't there.
+    let missing = simulate(&["no-such-scenario.json"]);
+    assert_eq!(missing.status.code(), Some(1));
+}
+
+#[test]
+fn simulations_report_events_and_ignore_empty_steps() {
+    let scenario = Scenario::from_json(SCENARIO.as_bytes()).unwrap();
+    let mut simulation = Simulation::new(&scenario, 7);
+    assert_eq!(simulation.actors[0].name, "scout, east");
+    assert!(simulation.step().is_empty());
+    assert!(simulation.step().is_empty());
+    assert_eq!(
+        simulation.step(),
+        vec![
+            SimEvent::WaypointReached { actor: 0, waypoint: 0 },
+            SimEvent::RouteFinished { actor: 0 },
+        ]
+    );
+    assert_eq!(simulation.tick, 3);
+
+    let mut paused = Simulation::new(&scenario, 7);
+    paused.delta_t = 0.0;
+    assert!(paused.step().is_empty());
+    assert_eq!(paused.tick, 0);
+    assert_eq!(paused.actors[0].actor.position, Point2::new(0.0, 0.0));
+    assert!(paused.actors[0].velocity.x.is_finite());
+
+    assert!(Scenario::from_json(r#"{"ticks": -1}"#.as_bytes()).is_err());
+}
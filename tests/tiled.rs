@@ -0,0 +1,137 @@
+extern crate skunkworks;
+
+use std::fs::File;
+use std::path::PathBuf;
+
+use skunkworks::tiled::TiledMap;
+use skunkworks::Point2;
+
+fn fixture(name: &str) -> File {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name);
+    File::open(&path).expect("Could not open the fixture")
+}
+
+fn positions(waypoints: &[skunkworks::Waypoint]) -> Vec<(f32, f32)> {
+    waypoints
+        .iter()
+        .map(|waypoint| (waypoint.position.x, waypoint.position.y))
+        .collect()
+}
+
+// Both fixtures describe the same 4x3 level, so they should read the same.
+fn check_level(map: &TiledMap) {
+    assert_eq!((map.width, map.height), (4, 3));
+    // 50px of image with a 1px margin and 1px spacing fits two 16px tiles.
+    assert_eq!(map.tilesets[0].columns, 2);
+
+    let grid = map.walkability_grid();
+    let blocked: Vec<(u32, u32)> = (0..3)
+        .flat_map(|row| (0..4).map(move |column| (column, row)))
+        .filter(|&(column, row)| grid.is_blocked(column, row))
+        .collect();
+    // One from the blocked tile, one from the blocked layer.
+    assert_eq!(blocked, vec![(3, 0), (1, 1)]);
+    assert!(grid.is_walkable_at(Point2::new(8.0, 8.0)));
+    assert!(!grid.is_walkable_at(Point2::new(24.0, 24.0)));
+    assert!(!grid.is_walkable_at(Point2::new(80.0, 8.0)));
+
+    let routes = map.routes();
+    assert_eq!(routes.len(), 2);
+    assert_eq!((routes[0].name.as_str(), routes[0].closed), ("patrol", false));
+    assert_eq!(positions(&routes[0].waypoints), vec![(8.0, 8.0), (40.0, 8.0), (40.0, 24.0)]);
+    assert_eq!((routes[1].name.as_str(), routes[1].closed), ("loop", true));
+    assert_eq!(positions(&routes[1].patrol_waypoints()).len(), 3);
+
+    let spawns = map.spawns();
+    assert_eq!(spawns.len(), 2);
+    assert_eq!((spawns[0].name.as_str(), spawns[0].kind.as_str()), ("guard", "guard"));
+    assert_eq!(spawns[0].position, Point2::new(24.0, 40.0));
+
+    let actors = map.spawn_actors(2.0);
+    assert_eq!(actors[0].speed, 5.0);
+    // Open routes are walked there and back.
+    assert_eq!(
+        positions(&actors[0].waypoints),
+        vec![(8.0, 8.0), (40.0, 8.0), (40.0, 24.0), (40.0, 8.0)]
+    );
+    assert_eq!(actors[1].speed, 2.0);
+    assert!(actors[1].waypoints.is_empty());
+}
+
+#[test]
+fn tmx_maps_load() {
+    check_level(&TiledMap::from_tmx(fixture("small.tmx")).unwrap());
+}
+
+#[test]
+fn json_maps_load() {
+    check_level(&TiledMap::from_json(fixture("small.json")).unwrap());
+}
+
+fn layer_names(map: &TiledMap) -> (Vec<String>, Vec<String>) {
+    (
+        map.tile_layers.iter().map(|layer| layer.name.clone()).collect(),
+        map.object_layers.iter().map(|layer| layer.name.clone()).collect(),
+    )
+}
+
+#[test]
+fn grouped_tmx_layers_load_like_json() {
+    let tmx = TiledMap::from_tmx(fixture("grouped.tmx")).unwrap();
+    let json = TiledMap::from_json(fixture("small.json")).unwrap();
+    check_level(&tmx);
+    assert_eq!(layer_names(&tmx), layer_names(&json));
+    assert_eq!(tmx.tile_layers[1].data, json.tile_layers[1].data);
+}
+
+#[test]
+fn layers_block_cells_by_their_own_width() {
+    // A 2x2 layer over a 4x3 map: its last tile sits at (1, 1), not (3, 0).
+    let tmx = r#"<map width="4" height="3" tilewidth="16" tileheight="16">
+ <layer name="walls" width="2" height="2">
+  <properties><property name="blocked" value="true"/></properties>
+  <data encoding="csv">0,0,0,1</data>
+ </layer>
+ <layer name="overflow" width="5" height="4">
+  <properties><property name="blocked" value="true"/></properties>
+  <data encoding="csv">0,0,0,0,1, 0,0,0,0,0, 0,0,0,0,0, 1,0,0,0,0, 1,1</data>
+ </layer>
+</map>"#;
+    let grid = TiledMap::from_tmx(tmx.as_bytes()).unwrap().walkability_grid();
+    let blocked: Vec<(u32, u32)> = (0..3)
+        .flat_map(|row| (0..4).map(move |column| (column, row)))
+        .filter(|&(column, row)| grid.is_blocked(column, row))
+        .collect();
+    // Tiles past the map's edge or the layer's own height are left out.
+    assert_eq!(blocked, vec![(1, 1)]);
+}
+
+#[test]
+fn zero_sized_tiles_are_rejected() {
+    let tmx = r#"<map width="1" height="1" tilewidth="16" tileheight="16">
+ <tileset firstgid="1" tilewidth="0" tileheight="16">
+  <image source="tiles.png" width="32" height="16"/>
+ </tileset>
+</map>"#;
+    assert!(TiledMap::from_tmx(tmx.as_bytes()).is_err());
+
+    let json = r#"{"width": 1, "height": 1, "tilewidth": 16, "tileheight": 16,
+ "tilesets": [{"firstgid": 1, "tilewidth": 16, "tileheight": 0, "image": "tiles.png",
+               "imagewidth": 32, "imageheight": 16}]}"#;
+    assert!(TiledMap::from_json(json.as_bytes()).is_err());
+}
+
+#[test]
+fn layers_without_a_width_are_rejected() {
+    let tmx = r#"<map width="1" height="1" tilewidth="16" tileheight="16">
+ <layer name="ground" width="0" height="1"><data encoding="csv">1</data></layer>
+</map>"#;
+    assert!(TiledMap::from_tmx(tmx.as_bytes()).is_err());
+
+    let json = r#"{"width": 1, "height": 1, "tilewidth": 16, "tileheight": 16,
+ "layers": [{"type": "tilelayer", "name": "ground", "width": 0, "height": 1, "data": [1]}]}"#;
+    assert!(TiledMap::from_json(json.as_bytes()).is_err());
+}
@@ -0,0 +1,195 @@
+extern crate serde_json;
+extern crate skunkworks;
+
+use skunkworks::game_timer::GameTimer;
+use skunkworks::rewind::Snapshot;
+use skunkworks::tween::{ease_transform, Animator, Easing, RepeatMode, Sequence, Track, Tween};
+use skunkworks::{affine_transform, Color, Point2, Vec2};
+
+const EPSILON: f64 = 1e-9;
+
+const EASINGS: [Easing; 16] = [
+    Easing::Linear,
+    Easing::QuadIn,
+    Easing::QuadOut,
+    Easing::QuadInOut,
+    Easing::CubicIn,
+    Easing::CubicOut,
+    Easing::CubicInOut,
+    Easing::ElasticIn,
+    Easing::ElasticOut,
+    Easing::ElasticInOut,
+    Easing::BounceIn,
+    Easing::BounceOut,
+    Easing::BounceInOut,
+    Easing::BackIn,
+    Easing::BackOut,
+    Easing::BackInOut,
+];
+
+fn assert_near(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < EPSILON, "{} is not close to {}", actual, expected);
+}
+
+#[test]
+fn easings_start_and_end_in_place() {
+    for &easing in EASINGS.iter() {
+        assert_near(easing.apply(0.0), 0.0);
+        assert_near(easing.apply(1.0), 1.0);
+        assert_near(easing.apply(-1.0), 0.0);
+        assert_near(easing.apply(2.0), 1.0);
+    }
+}
+
+#[test]
+fn in_outs_cross_halfway_at_the_middle() {
+    for &easing in EASINGS.iter() {
+        let name = format!("{:?}", easing);
+        if name.ends_with("InOut") || easing == Easing::Linear {
+            assert_near(easing.apply(0.5), 0.5);
+        }
+    }
+}
+
+#[test]
+fn easings_follow_their_curves() {
+    assert_near(Easing::QuadIn.apply(0.5), 0.25);
+    assert_near(Easing::QuadOut.apply(0.5), 0.75);
+    assert_near(Easing::QuadInOut.apply(0.25), 0.125);
+    assert_near(Easing::CubicIn.apply(0.5), 0.125);
+    assert_near(Easing::CubicOut.apply(0.5), 0.875);
+    assert_near(Easing::BounceOut.apply(1.0 / 2.75), 1.0);
+    assert_near(Easing::BounceIn.apply(0.5), 1.0 - Easing::BounceOut.apply(0.5));
+
+    // Back pulls behind the start, elastic overshoots the end.
+    assert!(Easing::BackIn.apply(0.2) < 0.0);
+    assert!(Easing::BackOut.apply(0.8) > 1.0);
+    assert!(Easing::ElasticOut.apply(0.1) > 1.0);
+    assert!((0..100).any(|i| Easing::ElasticIn.apply(f64::from(i) / 100.0) < 0.0));
+}
+
+#[test]
+fn easings_load_by_name() {
+    let easing: Easing = serde_json::from_str(r#""elastic_in_out""#).unwrap();
+    assert_eq!(easing, Easing::ElasticInOut);
+    assert_eq!(Easing::default(), Easing::Linear);
+}
+
+#[test]
+fn tweens_values_of_each_kind() {
+    let tween = Tween::new(10.0f32, 20.0, 2.0, Easing::Linear);
+    assert_eq!(tween.value_at(1.0), 15.0);
+    assert_eq!(tween.value_at(-1.0), 10.0);
+    assert_eq!(tween.value_at(5.0), 20.0);
+
+    let position = Tween::new(Point2::new(0.0, 0.0), Point2::new(4.0, 8.0), 1.0, Easing::QuadIn);
+    assert_eq!(position.value_at(0.5), Point2::new(1.0, 2.0));
+
+    let velocity = Tween::new(Vec2::new(0.0f32, 0.0), Vec2::new(2.0, 0.0), 1.0, Easing::Linear);
+    assert_eq!(velocity.value_at(0.5), Vec2::new(1.0, 0.0));
+
+    let black = Color {
+        r: 0.0,
+        g: 0.0,
+        b: 0.0,
+        a: 1.0,
+    };
+    let white = Color {
+        r: 1.0,
+        g: 1.0,
+        b: 1.0,
+        a: 1.0,
+    };
+    let grey = Tween::new(black, white, 1.0, Easing::Linear).value_at(0.5);
+    assert_eq!(grey, Color { r: 0.5, g: 0.5, b: 0.5, a: 1.0 });
+
+    let instant = Tween::new(1.0, 2.0, 0.0, Easing::Linear);
+    assert_eq!(instant.value_at(0.0), 2.0);
+}
+
+#[test]
+fn eases_remaps() {
+    for &value in [0.0, 3.0, 7.5, 10.0].iter() {
+        let eased = ease_transform(Easing::Linear, value, 0.0, 10.0, 100.0, 200.0);
+        assert_near(eased, affine_transform(value, 0.0, 10.0, 100.0, 200.0));
+    }
+    assert_near(ease_transform(Easing::QuadIn, 5.0, 0.0, 10.0, 100.0, 200.0), 125.0);
+    assert_near(ease_transform(Easing::Linear, 20.0, 0.0, 10.0, 100.0, 200.0), 200.0);
+}
+
+#[test]
+fn sequences_play_in_order() {
+    let sequence = Sequence::new(Tween::new(0.0, 10.0, 1.0, Easing::Linear))
+        .then(Tween::hold(10.0, 0.5))
+        .then_to(0.0, 2.0, Easing::Linear);
+    assert_eq!(sequence.steps().len(), 3);
+    assert_near(sequence.duration(), 3.5);
+    assert_near(sequence.value_at(0.5), 5.0);
+    assert_near(sequence.value_at(1.25), 10.0);
+    assert_near(sequence.value_at(2.5), 5.0);
+    assert_near(sequence.value_at(10.0), 0.0);
+    assert_near(sequence.value_at(-1.0), 0.0);
+}
+
+#[test]
+fn parallel_tracks_share_the_clock() {
+    let scale = Tween::new(1.0, 2.0, 1.0, Easing::Linear);
+    let position = Tween::new(Point2::new(0.0, 0.0), Point2::new(10.0, 0.0), 2.0, Easing::Linear);
+    let group = (scale, position);
+    assert_near(group.duration(), 2.0);
+
+    let (size, at) = group.value_at(1.0);
+    assert_near(size, 2.0);
+    assert_eq!(at, Point2::new(5.0, 0.0));
+
+    let (_, _, last) = (scale, position, Tween::hold(3.0, 4.0)).value_at(0.0);
+    assert_near(last, 3.0);
+}
+
+#[test]
+fn loops_and_yoyos() {
+    let tween = Tween::new(0.0, 10.0, 1.0, Easing::Linear);
+
+    let looped = tween.looped();
+    assert!(looped.duration().is_infinite());
+    assert_near(looped.value_at(0.25), 2.5);
+    assert_near(looped.value_at(3.25), 2.5);
+
+    let yoyo = tween.yoyo();
+    assert_near(yoyo.value_at(1.25), 7.5);
+    assert_near(yoyo.value_at(2.25), 2.5);
+
+    let twice = tween.repeat(RepeatMode::Loop, Some(2));
+    assert_near(twice.duration(), 2.0);
+    assert_near(twice.value_at(1.5), 5.0);
+    assert_near(twice.value_at(2.0), 10.0);
+    assert_near(twice.value_at(9.0), 10.0);
+
+    // There and back again ends where it started.
+    let round_trip = tween.repeat(RepeatMode::Yoyo, Some(2));
+    assert_near(round_trip.value_at(2.0), 0.0);
+    assert_near(round_trip.value_at(5.0), 0.0);
+}
+
+#[test]
+fn animators_play_tracks_back() {
+    let mut animator = Animator::new(Tween::new(0.0, 10.0, 1.0, Easing::Linear));
+    assert_near(animator.value(), 0.0);
+    animator.advance(0.5);
+    assert_near(animator.value(), 5.0);
+    assert!(!animator.is_finished());
+
+    let saved = animator.snapshot();
+    animator.advance(0.75);
+    assert!(animator.is_finished());
+    assert_near(animator.value(), 10.0);
+
+    animator.restore(&saved);
+    assert_near(animator.value(), 5.0);
+    animator.restart();
+    assert_near(animator.time(), 0.0);
+
+    // A fresh timer has measured no frame yet.
+    animator.tick(&GameTimer::new());
+    assert_near(animator.time(), 0.0);
+}
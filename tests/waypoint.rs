@@ -0,0 +1,268 @@
+extern crate skunkworks;
+
+use skunkworks::rng::SimRng;
+use skunkworks::waypoint::{actor_eta, closest_point, eta, leg_lengths, max_deviation,
+                           optimize_route, path_length, progress, resample, round_corners,
+                           simplify_rdp, simplify_visvalingam, RouteOptions};
+use skunkworks::{Actor, Point2, Waypoint};
+
+type Simplifier = fn(&[Waypoint], f32) -> Vec<Waypoint>;
+
+fn scattered(count: usize, seed: u64) -> Vec<Waypoint> {
+    let mut rng = SimRng::new(seed);
+    (0..count)
+        .map(|_| Waypoint::new(rng.gen_range(0.0, 500.0) as f32, rng.gen_range(0.0, 500.0) as f32))
+        .collect()
+}
+
+fn every_option() -> Vec<RouteOptions> {
+    let mut all = Vec::new();
+    for &origin in [None, Some(Point2::new(250.0, 250.0))].iter() {
+        for bits in 0..8 {
+            all.push(RouteOptions {
+                origin,
+                fixed_start: bits & 1 != 0,
+                fixed_end: bits & 2 != 0,
+                closed: bits & 4 != 0,
+            });
+        }
+    }
+    all
+}
+
+fn route(points: &[(f32, f32)]) -> Vec<Waypoint> {
+    points.iter().map(|&(x, y)| Waypoint::new(x, y)).collect()
+}
+
+// A shaky hand-drawn line, wandering right with plenty of small wiggles.
+fn wobbly(count: usize, seed: u64) -> Vec<Waypoint> {
+    let mut rng = SimRng::new(seed);
+    let mut y = 0.0;
+    (0..count)
+        .map(|index| {
+            y += rng.gen_range(-3.0, 3.0) as f32;
+            Waypoint::new(index as f32 * 2.0, y)
+        })
+        .collect()
+}
+
+fn assert_at(waypoint: &Waypoint, x: f32, y: f32) {
+    let expected = Point2::new(x, y);
+    assert!(
+        waypoint.position.distance(expected) < 1e-3,
+        "{:?} != {:?}",
+        waypoint.position,
+        expected
+    );
+}
+
+#[test]
+fn optimizing_keeps_fixed_ends_in_place() {
+    for seed in 0..20 {
+        for options in every_option() {
+            let original = scattered(12, seed);
+            let mut waypoints = original.clone();
+            optimize_route(&mut waypoints, &options);
+            assert_eq!(waypoints.len(), original.len());
+            if options.fixed_start {
+                assert_eq!(waypoints[0].position, original[0].position, "{:?}", options);
+            }
+            if options.fixed_end {
+                assert_eq!(waypoints[11].position, original[11].position, "{:?}", options);
+            }
+        }
+    }
+}
+
+#[test]
+fn closed_routes_keep_their_end_when_joined_next_to_it() {
+    let mut waypoints = vec![
+        Waypoint::new(0.0, 0.0),
+        Waypoint::new(100.0, 0.0),
+        Waypoint::new(100.0, 100.0),
+        Waypoint::new(0.0, 100.0),
+    ];
+    let options = RouteOptions {
+        // Right beside the last waypoint, which is where the loop would
+        // otherwise be joined.
+        origin: Some(Point2::new(-5.0, 105.0)),
+        fixed_end: true,
+        closed: true,
+        ..Default::default()
+    };
+    optimize_route(&mut waypoints, &options);
+    assert_eq!(waypoints[3].position, Point2::new(0.0, 100.0));
+}
+
+#[test]
+fn optimized_routes_are_never_longer() {
+    for seed in 0..20 {
+        for options in every_option() {
+            let mut waypoints = scattered(15, seed);
+            let report = optimize_route(&mut waypoints, &options);
+            assert!(report.length_after <= report.length_before, "{:?}", options);
+            let length = path_length(&waypoints, options.origin, options.closed);
+            assert!((length - report.length_after).abs() < 1e-3);
+        }
+    }
+
+    // An already short route is left as it was.
+    let mut square = vec![
+        Waypoint::new(0.0, 0.0),
+        Waypoint::new(10.0, 0.0),
+        Waypoint::new(10.0, 10.0),
+        Waypoint::new(0.0, 10.0),
+    ];
+    let report = optimize_route(&mut square, &RouteOptions::default());
+    assert_eq!(report.saved(), 0.0);
+    assert_eq!(square[1].position, Point2::new(10.0, 0.0));
+}
+
+#[test]
+fn optimizing_survives_nan_positions() {
+    let nan = ::std::f32::NAN;
+    for options in every_option() {
+        let mut waypoints = scattered(6, 3);
+        waypoints[2] = Waypoint::new(nan, 0.0);
+        optimize_route(&mut waypoints, &options);
+        assert_eq!(waypoints.len(), 6);
+        assert_eq!(waypoints.iter().filter(|waypoint| waypoint.position.x.is_nan()).count(), 1);
+
+        let mut waypoints = scattered(6, 3);
+        let options = RouteOptions {
+            origin: Some(Point2::new(nan, nan)),
+            ..options
+        };
+        optimize_route(&mut waypoints, &options);
+        assert_eq!(waypoints.len(), 6);
+    }
+}
+
+#[test]
+fn projects_onto_single_waypoints_and_empty_legs() {
+    let single = closest_point(&route(&[(10.0, 0.0)]), Point2::new(13.0, 4.0)).unwrap();
+    assert_eq!(single.point, Point2::new(10.0, 0.0));
+    assert_eq!((single.segment, single.distance, single.distance_along), (0, 5.0, 0.0));
+    assert!(closest_point(&[], Point2::new(0.0, 0.0)).is_none());
+
+    // The doubled first waypoint makes a leg of no length, which is skipped
+    // over rather than dividing by zero.
+    let doubled = route(&[(0.0, 0.0), (0.0, 0.0), (10.0, 0.0)]);
+    let along = closest_point(&doubled, Point2::new(5.0, 3.0)).unwrap();
+    assert_eq!(along.point, Point2::new(5.0, 0.0));
+    assert_eq!((along.segment, along.distance_along), (1, 5.0));
+    let before = closest_point(&doubled, Point2::new(-3.0, 0.0)).unwrap();
+    assert_eq!(before.point, Point2::new(0.0, 0.0));
+    assert_eq!((before.segment_fraction, before.distance_along), (0.0, 0.0));
+}
+
+#[test]
+fn measures_progress_along_the_route() {
+    let corner = route(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)]);
+    assert_eq!(progress(&corner, Point2::new(-5.0, 0.0)), 0.0);
+    assert_eq!(progress(&corner, Point2::new(12.0, 5.0)), 0.75);
+    assert_eq!(progress(&corner, Point2::new(10.0, 20.0)), 1.0);
+    // A route with nowhere to go is already finished.
+    assert_eq!(progress(&route(&[(3.0, 3.0)]), Point2::new(0.0, 0.0)), 1.0);
+    assert_eq!(progress(&[], Point2::new(0.0, 0.0)), 0.0);
+}
+
+#[test]
+fn standing_still_never_arrives() {
+    let corner = route(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)]);
+    assert_eq!(eta(&corner, Point2::new(5.0, 0.0), 5.0), Some(3.0));
+    assert_eq!(eta(&corner, Point2::new(5.0, 0.0), 0.0), None);
+    // Unless it's already there.
+    assert_eq!(eta(&corner, Point2::new(10.0, 10.0), 0.0), Some(0.0));
+
+    let mut actor = Actor {
+        position: Point2::new(0.0, 0.0),
+        speed: 0.0,
+        waypoints: route(&[(3.0, 4.0)]),
+    };
+    assert_eq!(actor_eta(&actor), None);
+    actor.speed = 10.0;
+    assert_eq!(actor_eta(&actor), Some(0.5));
+}
+
+#[test]
+fn measures_f64_routes_far_from_the_origin() {
+    // Far enough out that f32 can't tell these waypoints apart.
+    let far = 1e8;
+    let corner: Vec<Waypoint<f64>> = vec![
+        Waypoint::new(far, far),
+        Waypoint::new(far + 3.0, far),
+        Waypoint::new(far + 3.0, far + 4.0),
+    ];
+    assert_eq!(path_length(&corner, None, false), 7.0);
+    assert_eq!(path_length(&corner, None, true), 12.0);
+    assert_eq!(leg_lengths(&corner), vec![3.0, 4.0]);
+
+    let halfway = Point2::new(far + 3.0, far + 0.5);
+    let projection = closest_point(&corner, Point2::new(far + 4.0, far + 0.5)).unwrap();
+    assert_eq!((projection.point, projection.segment), (halfway, 1));
+    assert_eq!((projection.distance, projection.distance_along), (1.0, 3.5));
+    assert_eq!(progress(&corner, halfway), 0.5);
+    assert_eq!(eta(&corner, halfway, 0.5), Some(7.0));
+
+    let actor = Actor {
+        position: Point2::new(far, far - 1.0),
+        speed: 2.0,
+        waypoints: corner,
+    };
+    assert_eq!(actor_eta(&actor), Some(4.0));
+}
+
+#[test]
+fn simplifying_stays_within_tolerance() {
+    for seed in 0..10 {
+        let original = wobbly(200, seed);
+        for &tolerance in [0.5, 2.0, 5.0, 20.0].iter() {
+            let simplifiers: [Simplifier; 2] = [simplify_rdp, simplify_visvalingam];
+            for simplify in simplifiers.iter() {
+                let simplified = simplify(&original, tolerance);
+                assert!(simplified.len() < original.len());
+                assert_eq!(simplified[0].position, original[0].position);
+                assert_eq!(simplified.last().unwrap().position, original[199].position);
+                let deviation = max_deviation(&original, &simplified);
+                assert!(deviation <= tolerance + 1e-4, "{} > {}", deviation, tolerance);
+            }
+        }
+    }
+}
+
+#[test]
+fn resamples_at_even_spacing() {
+    let straight = resample(&route(&[(0.0, 0.0), (10.0, 0.0)]), 3.0);
+    let xs: Vec<f32> = straight.iter().map(|waypoint| waypoint.position.x).collect();
+    assert_eq!(xs, vec![0.0, 3.0, 6.0, 9.0, 10.0]);
+
+    // Spacing carries on round corners, and a last waypoint that falls due
+    // anyway isn't doubled up.
+    let corner = resample(&route(&[(0.0, 0.0), (4.0, 0.0), (4.0, 5.0)]), 3.0);
+    assert_eq!(corner.len(), 4);
+    assert_at(&corner[1], 3.0, 0.0);
+    assert_at(&corner[2], 4.0, 2.0);
+    assert_at(&corner[3], 4.0, 5.0);
+}
+
+#[test]
+fn rounds_corners_between_the_same_endpoints() {
+    let corner = route(&[(0.0, 0.0), (100.0, 0.0), (100.0, 100.0)]);
+    let rounded = round_corners(&corner, 20.0, 4);
+    assert_eq!(rounded.len(), 7);
+    assert_at(&rounded[0], 0.0, 0.0);
+    assert_at(&rounded[1], 80.0, 0.0);
+    assert_at(&rounded[5], 100.0, 20.0);
+    assert_at(&rounded[6], 100.0, 100.0);
+    // The arc keeps its distance from the corner's centre.
+    let center = Point2::new(80.0, 20.0);
+    for waypoint in &rounded[1..6] {
+        assert!((waypoint.position.distance(center) - 20.0).abs() < 1e-3);
+    }
+
+    // Short legs shrink the arc to fit.
+    let tight = round_corners(&route(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)]), 20.0, 4);
+    assert_at(&tight[1], 5.0, 0.0);
+    assert_at(&tight[6], 10.0, 10.0);
+}